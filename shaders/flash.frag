@@ -0,0 +1,11 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+	vec4 tint;
+} push_constants;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+	out_color = push_constants.tint;
+}
@@ -0,0 +1,26 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+	float alpha;
+} push_constants;
+
+layout(set = 1, binding = 0) uniform sampler2D texture_sampler;
+
+layout(location = 0) in vec2 frag_texture_coord;
+layout(location = 1) in float frag_light_level;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+	float light_factor = 60.0 - 64.0 * frag_light_level;
+	float distance_factor = 80.0 / ((0.0625 / gl_FragCoord.w) + 1.0);
+	float light_level = 1.0 - (light_factor - distance_factor) / 32.0;
+	light_level = clamp(light_level, 0.0, 1.0);
+
+	vec4 texture_texel = texture(texture_sampler, frag_texture_coord);
+
+	if (texture_texel.a < 0.5)
+		discard;
+
+	out_color = vec4(texture_texel.rgb * light_level, push_constants.alpha);
+}
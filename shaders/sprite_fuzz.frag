@@ -0,0 +1,33 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+	uvec2 fuzz_table;
+	uint fuzz_pos;
+} push_constants;
+
+layout(set = 1, binding = 0) uniform sampler2D texture_sampler;
+
+layout(location = 0) in vec2 frag_texture_coord;
+layout(location = 1) in float frag_light_level;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+	vec4 texture_texel = texture(texture_sampler, frag_texture_coord);
+
+	if (texture_texel.a < 0.5)
+		discard;
+
+	// Vanilla draws the sprite by copying the pixel one row above or below through
+	// a dark colormap. The framebuffer can't be read here, so darken the background
+	// by a varying amount instead, picked from the same offset table.
+	uint index = (uint(gl_FragCoord.y) + uint(gl_FragCoord.x) * 7u + push_constants.fuzz_pos) % 50u;
+	uint bit;
+
+	if (index < 32u)
+		bit = (push_constants.fuzz_table.x >> index) & 1u;
+	else
+		bit = (push_constants.fuzz_table.y >> (index - 32u)) & 1u;
+
+	out_color = vec4(0.0, 0.0, 0.0, bit == 1u ? 0.25 : 0.125);
+}
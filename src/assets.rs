@@ -31,6 +31,16 @@ impl AssetStorage {
 		})
 	}
 
+	#[inline]
+	pub fn get_mut<A: Asset>(&mut self, handle: &AssetHandle<A>) -> Option<&mut A::Data> {
+		self.storages.get_mut(&TypeId::of::<A>()).and_then(|entry| {
+			entry
+				.downcast_mut::<AssetStorageTyped<A>>()
+				.unwrap()
+				.get_mut(handle)
+		})
+	}
+
 	#[inline]
 	pub fn handle_for<A: Asset>(&self, name: &str) -> Option<AssetHandle<A>> {
 		self.storages.get(&TypeId::of::<A>()).and_then(|entry| {
@@ -121,6 +131,11 @@ impl<A: Asset> AssetStorageTyped<A> {
 		self.assets.get(&handle.id())
 	}
 
+	#[inline]
+	fn get_mut(&mut self, handle: &AssetHandle<A>) -> Option<&mut A::Data> {
+		self.assets.get_mut(&handle.id())
+	}
+
 	#[inline]
 	fn handle_for(&self, name: &str) -> Option<AssetHandle<A>> {
 		self.names.get(name).and_then(WeakHandle::upgrade)
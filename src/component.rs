@@ -1,19 +1,28 @@
-use crate::assets::{Asset, DataSource};
 use fnv::FnvHashMap;
 use legion::{
 	prelude::{CommandBuffer, Entity},
 	storage::Component,
 };
-use std::any::TypeId;
+use std::any::{Any, TypeId};
 
 pub trait DynComponent: Send + Sync {
 	fn add_to_entity(&self, entity: Entity, command_buffer: &mut CommandBuffer);
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
 impl<T: Component + Clone> DynComponent for T {
 	fn add_to_entity(&self, entity: Entity, command_buffer: &mut CommandBuffer) {
 		command_buffer.add_component(entity, self.clone());
 	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
 }
 
 pub struct EntityTemplate {
@@ -37,19 +46,25 @@ impl EntityTemplate {
 		self
 	}
 
+	pub fn get_component<T: Component>(&self) -> Option<&T> {
+		self.components
+			.get(&TypeId::of::<T>())
+			.and_then(|dyn_component| (**dyn_component).as_any().downcast_ref())
+	}
+
+	pub fn get_component_mut<T: Component>(&mut self) -> Option<&mut T> {
+		self.components
+			.get_mut(&TypeId::of::<T>())
+			.and_then(|dyn_component| (**dyn_component).as_any_mut().downcast_mut())
+	}
+
+	pub fn remove_component<T: Component>(&mut self) {
+		self.components.remove(&TypeId::of::<T>());
+	}
+
 	pub fn add_to_entity(&self, entity: Entity, command_buffer: &mut CommandBuffer) {
 		for dyn_component in self.components.values() {
 			(*dyn_component).add_to_entity(entity, command_buffer);
 		}
 	}
 }
-
-impl Asset for EntityTemplate {
-	type Data = Self;
-	type Intermediate = Self;
-	const NAME: &'static str = "EntityTemplate";
-
-	fn import(_name: &str, _source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
-		unimplemented!();
-	}
-}
@@ -0,0 +1,482 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		client::{Client, UseAction, UseEvent},
+		damage::DamageEvent,
+		data::{States, FRAME_TIME},
+		door::{activate_switch, DoorActive, SwitchActive},
+		floor::{activate_floors, crush_entities, FloorActive, FloorParams},
+		map::{LinedefRef, Map, MapDynamic, SectorRef},
+		physics::{move_ceiling, CrossAction, CrossEvent},
+		plat::PlatActive,
+	},
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn ceiling_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut use_event_reader = resources
+		.get_mut::<EventChannel<UseEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, use_event_channel, mut sound_queue) = <(
+			Read<AssetStorage>,
+			Read<EventChannel<UseEvent>>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for use_event in use_event_channel.read(&mut use_event_reader) {
+			if let Some(UseAction::CeilingSwitchUse(ceiling_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				let used = activate_ceilings(
+					world,
+					&ceiling_use.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut command_buffer,
+				);
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&ceiling_use.switch_sound,
+						if ceiling_use.retrigger {
+							Some(ceiling_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !ceiling_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn ceiling_cross_system(
+	resources: &mut Resources,
+) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, cross_event_channel) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+		)>::fetch(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			match world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				Some(CrossAction::CeilingCross(ceiling_cross)) => {
+					// Only some linedefs can be triggered by monsters
+					if !ceiling_cross.monsters && client.entity != Some(cross_event.entity) {
+						continue;
+					}
+
+					let linedef_ref = world
+						.get_component::<LinedefRef>(cross_event.linedef_entity)
+						.unwrap();
+					let mut map_dynamic = unsafe {
+						world
+							.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+							.unwrap()
+					};
+					let map_dynamic = map_dynamic.as_mut();
+					let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+					if let Some(floor_params) = &ceiling_cross.floor {
+						activate_floors(
+							world,
+							floor_params,
+							linedef_ref.index,
+							map,
+							map_dynamic,
+							&asset_storage,
+							&mut command_buffer,
+						);
+					}
+
+					activate_ceilings(
+						world,
+						&ceiling_cross.params,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&mut command_buffer,
+					);
+
+					if !ceiling_cross.retrigger {
+						command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+					}
+				}
+				Some(CrossAction::CeilingStopCross(ceiling_stop_cross)) => {
+					if client.entity != Some(cross_event.entity) {
+						continue;
+					}
+
+					let linedef_ref = world
+						.get_component::<LinedefRef>(cross_event.linedef_entity)
+						.unwrap();
+					let map_dynamic = world
+						.get_component::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap();
+					let map = asset_storage.get(&map_dynamic.map).unwrap();
+					let linedef = &map.linedefs[linedef_ref.index];
+
+					// Put all the active ceilings with the same tag in stasis
+					for (i, _) in map
+						.sectors
+						.iter()
+						.enumerate()
+						.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+					{
+						let sector_entity = map_dynamic.sectors[i].entity;
+
+						if let Some(mut ceiling_active) = unsafe {
+							world.get_component_mut_unchecked::<CeilingActive>(sector_entity)
+						} {
+							ceiling_active.in_stasis = true;
+						}
+					}
+
+					if !ceiling_stop_cross.retrigger {
+						command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+					}
+				}
+				_ => {}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Starts a ceiling mover in every sector with the same tag as the linedef.
+// Returns whether any ceiling was started or resumed.
+fn activate_ceilings(
+	world: &World,
+	params: &CeilingParams,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &MapDynamic,
+	command_buffer: &mut CommandBuffer,
+) -> bool {
+	let linedef = &map.linedefs[linedef_index];
+	let mut used = false;
+
+	for (i, sector) in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+	{
+		let sector_entity = map_dynamic.sectors[i].entity;
+
+		// Crushers that were stopped start moving again
+		if let Some(mut ceiling_active) =
+			unsafe { world.get_component_mut_unchecked::<CeilingActive>(sector_entity) }
+		{
+			if ceiling_active.in_stasis && params.ceiling_type.is_crusher() {
+				ceiling_active.in_stasis = false;
+				used = true;
+			}
+
+			continue;
+		}
+
+		if world.has_component::<DoorActive>(sector_entity)
+			|| world.has_component::<FloorActive>(sector_entity)
+			|| world.has_component::<PlatActive>(sector_entity)
+		{
+			continue;
+		}
+
+		let floor_height = map_dynamic.sectors[i].interval.min;
+		let ceiling_height = map_dynamic.sectors[i].interval.max;
+
+		let (state, low_height, high_height) = match params.ceiling_type {
+			CeilingType::LowerToFloor => (CeilingState::Down, floor_height, ceiling_height),
+			CeilingType::LowerAndCrush => (CeilingState::Down, floor_height + 8.0, ceiling_height),
+			CeilingType::RaiseToHighest => (
+				CeilingState::Up,
+				floor_height,
+				sector
+					.neighbours
+					.iter()
+					.map(|index| map_dynamic.sectors[*index].interval.max)
+					.fold(ceiling_height, |x, y| f32::max(x, y)),
+			),
+			CeilingType::CrushAndRaise | CeilingType::FastCrushAndRaise => {
+				(CeilingState::Down, floor_height + 8.0, ceiling_height)
+			}
+		};
+
+		command_buffer.add_component(
+			sector_entity,
+			CeilingActive {
+				ceiling_type: params.ceiling_type,
+				speed: params.speed,
+				base_speed: params.speed,
+				state,
+				in_stasis: false,
+				low_height,
+				high_height,
+
+				move_sound: params.move_sound.clone(),
+				move_sound_time: Duration::default(),
+				stop_sound: params.stop_sound.clone(),
+				crush_time: Duration::default(),
+			},
+		);
+		used = true;
+	}
+
+	used
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum CeilingType {
+	LowerToFloor,
+	LowerAndCrush,
+	RaiseToHighest,
+	CrushAndRaise,
+	FastCrushAndRaise,
+}
+
+impl CeilingType {
+	pub fn is_crusher(self) -> bool {
+		match self {
+			CeilingType::LowerToFloor | CeilingType::RaiseToHighest => false,
+			_ => true,
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct CeilingParams {
+	pub ceiling_type: CeilingType,
+	pub speed: f32,
+	pub move_sound: Option<AssetHandle<Sound>>,
+	pub stop_sound: Option<AssetHandle<Sound>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CeilingSwitchUse {
+	pub params: CeilingParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CeilingCross {
+	pub params: CeilingParams,
+	// Moves the floor at the same time
+	pub floor: Option<FloorParams>,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CeilingStopCross {
+	pub retrigger: bool,
+}
+
+pub fn ceiling_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(move |world, resources| {
+		let (asset_storage, delta, mut damage_event_channel, mut sound_queue, states) =
+			<(
+				Read<AssetStorage>,
+				Read<Duration>,
+				Write<EventChannel<DamageEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+				Read<States>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for (entity, (sector_ref, mut ceiling_active)) in unsafe {
+			<(Read<SectorRef>, Write<CeilingActive>)>::query().iter_entities_unchecked(world)
+		} {
+			if ceiling_active.in_stasis {
+				continue;
+			}
+
+			let mut map_dynamic = unsafe {
+				world
+					.get_component_mut_unchecked::<MapDynamic>(sector_ref.map_entity)
+					.unwrap()
+			};
+			let map_dynamic = map_dynamic.as_mut();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+			let ceiling_height = map_dynamic.sectors[sector_ref.index].interval.max;
+
+			// Play the moving sound every 8 tics
+			if let Some(move_sound) = &ceiling_active.move_sound {
+				if let Some(new_time) = ceiling_active.move_sound_time.checked_sub(*delta) {
+					ceiling_active.move_sound_time = new_time;
+				} else {
+					ceiling_active.move_sound_time = 8 * FRAME_TIME;
+					sound_queue.push((move_sound.clone(), entity));
+				}
+			}
+
+			match ceiling_active.state {
+				CeilingState::Up => {
+					let move_step = f32::min(
+						ceiling_active.speed * delta.as_secs_f32(),
+						ceiling_active.high_height - ceiling_height,
+					);
+					move_ceiling(
+						world,
+						map,
+						map_dynamic,
+						&states,
+						sector_ref.index,
+						move_step,
+						false,
+					);
+
+					if move_step == ceiling_active.high_height - ceiling_height {
+						map_dynamic.sectors[sector_ref.index].interval.max =
+							ceiling_active.high_height;
+
+						if let Some(stop_sound) = &ceiling_active.stop_sound {
+							sound_queue.push((stop_sound.clone(), entity));
+						}
+
+						if ceiling_active.ceiling_type.is_crusher() {
+							ceiling_active.state = CeilingState::Down;
+						} else {
+							command_buffer.remove_component::<CeilingActive>(entity);
+						}
+					}
+				}
+				CeilingState::Down => {
+					let crush = ceiling_active.ceiling_type.is_crusher();
+					let move_step = f32::max(
+						-ceiling_active.speed * delta.as_secs_f32(),
+						ceiling_active.low_height - ceiling_height,
+					);
+					let blocked = move_ceiling(
+						world,
+						map,
+						map_dynamic,
+						&states,
+						sector_ref.index,
+						move_step,
+						crush,
+					);
+
+					if !blocked.is_empty() {
+						// Crushers keep moving, others wait until the way is clear
+						if crush {
+							crush_entities(
+								&blocked,
+								&mut ceiling_active.crush_time,
+								*delta,
+								&mut damage_event_channel,
+							);
+
+							// Slow down while crushing something
+							if ceiling_active.ceiling_type != CeilingType::FastCrushAndRaise {
+								ceiling_active.speed = ceiling_active.base_speed / 8.0;
+							}
+						} else {
+							continue;
+						}
+					}
+
+					if move_step == ceiling_active.low_height - ceiling_height {
+						map_dynamic.sectors[sector_ref.index].interval.max =
+							ceiling_active.low_height;
+
+						if let Some(stop_sound) = &ceiling_active.stop_sound {
+							sound_queue.push((stop_sound.clone(), entity));
+						}
+
+						match ceiling_active.ceiling_type {
+							CeilingType::CrushAndRaise | CeilingType::FastCrushAndRaise => {
+								ceiling_active.state = CeilingState::Up;
+								ceiling_active.speed = ceiling_active.base_speed;
+							}
+							_ => {
+								command_buffer.remove_component::<CeilingActive>(entity);
+							}
+						}
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+#[derive(Clone, Debug)]
+pub struct CeilingActive {
+	pub ceiling_type: CeilingType,
+	pub speed: f32,
+	pub base_speed: f32,
+	pub state: CeilingState,
+	pub in_stasis: bool,
+	pub low_height: f32,
+	pub high_height: f32,
+
+	pub move_sound: Option<AssetHandle<Sound>>,
+	pub move_sound_time: Duration,
+	pub stop_sound: Option<AssetHandle<Sound>>,
+	pub crush_time: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CeilingState {
+	Up,
+	Down,
+}
@@ -1,12 +1,17 @@
 use crate::{
 	assets::AssetStorage,
 	doom::{
+		ceiling::CeilingSwitchUse,
 		components::{Transform, Velocity},
 		data::{FORWARD_ACCEL, STRAFE_ACCEL},
-		door::{DoorSwitchUse, DoorUse},
+		door::{DoorShoot, DoorSwitchUse, DoorUse},
+		floor::{DonutSwitchUse, FloorShoot, FloorSwitchUse, StairsSwitchUse},
+		game::ExitSwitchUse,
 		input::{Action, Axis, UserCommand},
+		light::LightSwitchUse,
 		map::MapDynamic,
 		physics::{BoxCollider, EntityTracer, SolidMask},
+		plat::{PlatShoot, PlatSwitchUse},
 	},
 	geometry::{Line2, AABB3},
 	input::{Bindings, InputState},
@@ -33,6 +38,8 @@ pub fn player_command_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 		)>::fetch_mut(resources);
 		let mut command = UserCommand {
 			action_attack: bindings.action_is_down(&Action::Attack, &input_state),
+			action_switch_weapon: (1..=7)
+				.find(|&slot| bindings.action_is_down(&Action::SwitchWeapon(slot), &input_state)),
 			action_use: bindings.action_is_down(&Action::Use, &input_state),
 			axis_forward: bindings.axis_value(&Axis::Forward, &input_state) as f32,
 			axis_pitch: bindings.axis_value(&Axis::Pitch, &input_state) as f32,
@@ -182,7 +189,10 @@ pub fn player_use_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 					let linedef_entity = map_dynamic.linedefs[linedef_index].entity;
 
 					if world.get_component::<UseAction>(linedef_entity).is_some() {
-						use_event_channel.single_write(UseEvent { linedef_entity });
+						use_event_channel.single_write(UseEvent {
+							entity,
+							linedef_entity,
+						});
 					}
 				}
 			}
@@ -192,11 +202,58 @@ pub fn player_use_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 
 #[derive(Clone, Debug)]
 pub enum UseAction {
+	CeilingSwitchUse(CeilingSwitchUse),
+	DonutSwitchUse(DonutSwitchUse),
 	DoorUse(DoorUse),
 	DoorSwitchUse(DoorSwitchUse),
+	ExitSwitchUse(ExitSwitchUse),
+	FloorSwitchUse(FloorSwitchUse),
+	LightSwitchUse(LightSwitchUse),
+	PlatSwitchUse(PlatSwitchUse),
+	StairsSwitchUse(StairsSwitchUse),
 }
 
 #[derive(Clone, Debug)]
 pub struct UseEvent {
+	pub entity: Entity,
 	pub linedef_entity: Entity,
 }
+
+#[derive(Clone, Debug)]
+pub enum ShootAction {
+	DoorShoot(DoorShoot),
+	FloorShoot(FloorShoot),
+	PlatShoot(PlatShoot),
+}
+
+#[derive(Clone, Debug)]
+pub struct ShootEvent {
+	pub entity: Entity,
+	pub linedef_entity: Entity,
+}
+
+pub fn player_message_system(
+	resources: &mut Resources,
+) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut message_event_reader = resources
+		.get_mut::<EventChannel<MessageEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |_world, resources| {
+		let (client, message_event_channel) =
+			<(Read<Client>, Read<EventChannel<MessageEvent>>)>::fetch(resources);
+
+		for message_event in message_event_channel.read(&mut message_event_reader) {
+			if client.entity == Some(message_event.entity) {
+				log::info!("{}", message_event.message);
+			}
+		}
+	})
+}
+
+#[derive(Clone, Debug)]
+pub struct MessageEvent {
+	pub entity: Entity,
+	pub message: String,
+}
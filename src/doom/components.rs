@@ -1,13 +1,15 @@
 use crate::geometry::Angle;
 use derivative::Derivative;
+use legion::prelude::Entity;
 use nalgebra::Vector3;
+use serde::Deserialize;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize)]
 pub struct SpawnOnCeiling {
 	pub offset: f32,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize)]
 pub struct SpawnPoint {
 	pub player_num: usize,
 }
@@ -36,3 +38,29 @@ pub struct Velocity {
 	#[derivative(Default(value = "Vector3::zeros()"))]
 	pub velocity: Vector3<f32>,
 }
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct Armor {
+	pub current: i32,
+	// Class 1 absorbs a third of the damage, higher classes absorb half, 0 means no armor
+	pub class: u8,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Health {
+	pub current: i32,
+	// Also the amount of damage beyond death that gibs
+	pub max: i32,
+	// Chance out of 256 to go into the pain state when damaged
+	#[serde(default)]
+	pub pain_chance: u16,
+	// Spawns puffs instead of blood when hit by bullets
+	#[serde(default)]
+	pub no_blood: bool,
+	// Immune to the splash damage of explosions
+	#[serde(default)]
+	pub no_radius_damage: bool,
+	// Whoever dealt the killing blow, explosions of dead things are blamed on them
+	#[serde(skip)]
+	pub killer: Option<Entity>,
+}
@@ -0,0 +1,210 @@
+use crate::doom::{
+	components::{Armor, Health},
+	data::States,
+	inventory::Powers,
+	monster::Monster,
+	palette::PaletteFlash,
+	physics::{BoxCollider, SolidMask},
+	state::{StateAction, StateActionEvent, StateMachine},
+};
+use legion::prelude::{Entity, Read, ResourceSet, Resources, World, Write};
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn damage_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut damage_event_reader = resources
+		.get_mut::<EventChannel<DamageEvent>>()
+		.unwrap()
+		.register_reader();
+	let mut state_action_event_reader = resources
+		.get_mut::<EventChannel<StateActionEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (damage_event_channel, state_action_event_channel, mut rng, states) =
+			<(
+				Read<EventChannel<DamageEvent>>,
+				Read<EventChannel<StateActionEvent>>,
+				Write<Pcg64Mcg>,
+				Read<States>,
+			)>::fetch_mut(resources);
+
+		for damage_event in damage_event_channel.read(&mut damage_event_reader) {
+			let mut health =
+				match unsafe { world.get_component_mut_unchecked::<Health>(damage_event.target) } {
+					Some(health) => health,
+					None => continue,
+				};
+
+			// Corpses are crushed into gibs, but otherwise can't be damaged any further
+			if health.current <= 0 {
+				if damage_event.damage_type == DamageType::Crush {
+					gib(world, &states, damage_event.target);
+				}
+
+				continue;
+			}
+
+			let mut amount = damage_event.amount;
+
+			// Invulnerability only gives way to telefrags and the like
+			if amount < 1000
+				&& world
+					.get_component::<Powers>(damage_event.target)
+					.map_or(false, |powers| powers.invulnerability > Duration::default())
+			{
+				continue;
+			}
+
+			if let Some(mut armor) =
+				unsafe { world.get_component_mut_unchecked::<Armor>(damage_event.target) }
+			{
+				if armor.class != 0 {
+					let mut saved = if armor.class == 1 {
+						amount / 3
+					} else {
+						amount / 2
+					};
+
+					// The armor is used up
+					if armor.current <= saved {
+						saved = armor.current;
+						armor.class = 0;
+					}
+
+					armor.current -= saved;
+					amount -= saved;
+				}
+			}
+
+			health.current -= amount;
+
+			if let Some(mut palette_flash) =
+				unsafe { world.get_component_mut_unchecked::<PaletteFlash>(damage_event.target) }
+			{
+				palette_flash.add_damage(amount as u32);
+			}
+
+			if health.current <= 0 {
+				health.killer = damage_event.source;
+
+				// Corpses are a quarter of the height, so they can be stepped over
+				if let Some(mut box_collider) =
+					unsafe { world.get_component_mut_unchecked::<BoxCollider>(damage_event.target) }
+				{
+					box_collider.height /= 4.0;
+				}
+			}
+
+			let mut state_machine = match unsafe {
+				world.get_component_mut_unchecked::<StateMachine>(damage_event.target)
+			} {
+				Some(x) => x,
+				None => continue,
+			};
+
+			if health.current <= 0 {
+				// Gib if the damage went far enough beyond death
+				let state = if health.current < -health.max {
+					state_machine.xdeath.or(state_machine.death)
+				} else {
+					state_machine.death
+				};
+
+				if let Some(state) = state {
+					state_machine.next = Some(state);
+				}
+			} else {
+				// Charging lost souls don't flinch
+				let charging = world
+					.get_component::<Monster>(damage_event.target)
+					.map_or(false, |monster| monster.charge.is_some());
+
+				if (rng.gen::<u8>() as u16) < health.pain_chance && !charging {
+					if let Some(state) = state_machine.pain {
+						state_machine.next = Some(state);
+					}
+
+					if let Some(mut monster) =
+						unsafe { world.get_component_mut_unchecked::<Monster>(damage_event.target) }
+					{
+						monster.just_hit = true;
+					}
+				}
+
+				// Monsters go after whoever hurt them, waking up if they were asleep
+				if let Some(source) = damage_event
+					.source
+					.filter(|&source| source != damage_event.target)
+				{
+					if let Some(mut monster) =
+						unsafe { world.get_component_mut_unchecked::<Monster>(damage_event.target) }
+					{
+						monster.target = Some(source);
+
+						if state_machine.next.is_none()
+							&& state_machine.current == state_machine.spawn
+						{
+							state_machine.next = state_machine.see;
+						}
+					}
+				}
+			}
+		}
+
+		// Corpses stop being solid once they fall over
+		for state_action_event in state_action_event_channel.read(&mut state_action_event_reader) {
+			if state_action_event.action != StateAction::Fall
+				&& state_action_event.action != StateAction::PainDie
+			{
+				continue;
+			}
+
+			if let Some(mut box_collider) = unsafe {
+				world.get_component_mut_unchecked::<BoxCollider>(state_action_event.entity)
+			} {
+				box_collider.solid_mask = SolidMask::empty();
+			}
+		}
+	})
+}
+
+// Turns a corpse into a pile of gibs, which nothing collides with
+pub fn gib(world: &World, states: &States, entity: Entity) {
+	if let Some(mut box_collider) =
+		unsafe { world.get_component_mut_unchecked::<BoxCollider>(entity) }
+	{
+		box_collider.height = 0.0;
+		box_collider.radius = 0.0;
+		box_collider.solid_mask = SolidMask::empty();
+	}
+
+	if let Some(mut state_machine) =
+		unsafe { world.get_component_mut_unchecked::<StateMachine>(entity) }
+	{
+		state_machine.next = Some(states.names["GIBS"]);
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct DamageEvent {
+	pub source: Option<Entity>,
+	pub inflictor: Option<Entity>,
+	pub target: Entity,
+	pub amount: i32,
+	pub damage_type: DamageType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageType {
+	Crush,
+	Explosion,
+	Hitscan,
+	Melee,
+	Projectile,
+	Sector,
+	Telefrag,
+}
@@ -2,10 +2,14 @@ pub mod anims;
 mod linedefs;
 mod mobjs;
 mod sectors;
+mod states;
+mod weapons;
 
 pub use linedefs::LinedefTypes;
 pub use mobjs::MobjTypes;
 pub use sectors::SectorTypes;
+pub use states::States;
+pub use weapons::WeaponTypes;
 
 use lazy_static::lazy_static;
 use std::time::Duration;
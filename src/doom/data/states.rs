@@ -0,0 +1,1017 @@
+use crate::{
+	assets::AssetStorage,
+	doom::{
+		data::FRAME_TIME,
+		state::{State, StateAction},
+		wad::WadLoader,
+	},
+};
+use fnv::FnvHashMap;
+use legion::prelude::{ResourceSet, Resources, Write};
+
+pub struct States {
+	pub states: Vec<State>,
+	pub names: FnvHashMap<&'static str, usize>,
+}
+
+impl States {
+	#[rustfmt::skip]
+	pub fn new(resources: &mut Resources) -> States {
+		let (mut asset_storage, mut loader) = <(
+			Write<AssetStorage>,
+			Write<WadLoader>,
+		)>::fetch_mut(resources);
+
+		let mut states = Vec::new();
+		let mut names = FnvHashMap::default();
+		let mut next_names = Vec::new();
+
+		// The states are in the same order as in vanilla, so that they can be referred to by number
+		let mut state = |name: &'static str, sprite: &str, frame: usize, full_bright: bool, tics: i32, action: Option<StateAction>, next: &'static str| {
+			names.insert(name, states.len());
+			next_names.push(next);
+			states.push(State {
+				sprite: asset_storage.load(sprite, &mut *loader),
+				frame,
+				full_bright,
+				time: if tics < 0 { None } else { Some(tics as u32 * FRAME_TIME) },
+				action,
+				next: 0,
+			});
+		};
+
+		state("NULL", "TROO", 0, false, -1, None, "NULL");
+		state("LIGHTDONE", "SHTG", 4, false, 0, Some(StateAction::Light0), "NULL");
+		state("PUNCH", "PUNG", 0, false, 1, Some(StateAction::WeaponReady), "PUNCH");
+		state("PUNCHDOWN", "PUNG", 0, false, 1, Some(StateAction::Lower), "PUNCHDOWN");
+		state("PUNCHUP", "PUNG", 0, false, 1, Some(StateAction::Raise), "PUNCHUP");
+		state("PUNCH1", "PUNG", 1, false, 4, None, "PUNCH2");
+		state("PUNCH2", "PUNG", 2, false, 4, Some(StateAction::Punch), "PUNCH3");
+		state("PUNCH3", "PUNG", 3, false, 5, None, "PUNCH4");
+		state("PUNCH4", "PUNG", 2, false, 4, None, "PUNCH5");
+		state("PUNCH5", "PUNG", 1, false, 5, Some(StateAction::ReFire), "PUNCH");
+		state("PISTOL", "PISG", 0, false, 1, Some(StateAction::WeaponReady), "PISTOL");
+		state("PISTOLDOWN", "PISG", 0, false, 1, Some(StateAction::Lower), "PISTOLDOWN");
+		state("PISTOLUP", "PISG", 0, false, 1, Some(StateAction::Raise), "PISTOLUP");
+		state("PISTOL1", "PISG", 0, false, 4, None, "PISTOL2");
+		state("PISTOL2", "PISG", 1, false, 6, Some(StateAction::FirePistol), "PISTOL3");
+		state("PISTOL3", "PISG", 2, false, 4, None, "PISTOL4");
+		state("PISTOL4", "PISG", 1, false, 5, Some(StateAction::ReFire), "PISTOL");
+		state("PISTOLFLASH", "PISF", 0, true, 7, Some(StateAction::Light1), "LIGHTDONE");
+		state("SGUN", "SHTG", 0, false, 1, Some(StateAction::WeaponReady), "SGUN");
+		state("SGUNDOWN", "SHTG", 0, false, 1, Some(StateAction::Lower), "SGUNDOWN");
+		state("SGUNUP", "SHTG", 0, false, 1, Some(StateAction::Raise), "SGUNUP");
+		state("SGUN1", "SHTG", 0, false, 3, None, "SGUN2");
+		state("SGUN2", "SHTG", 0, false, 7, Some(StateAction::FireShotgun), "SGUN3");
+		state("SGUN3", "SHTG", 1, false, 5, None, "SGUN4");
+		state("SGUN4", "SHTG", 2, false, 5, None, "SGUN5");
+		state("SGUN5", "SHTG", 3, false, 4, None, "SGUN6");
+		state("SGUN6", "SHTG", 2, false, 5, None, "SGUN7");
+		state("SGUN7", "SHTG", 1, false, 5, None, "SGUN8");
+		state("SGUN8", "SHTG", 0, false, 3, None, "SGUN9");
+		state("SGUN9", "SHTG", 0, false, 7, Some(StateAction::ReFire), "SGUN");
+		state("SGUNFLASH1", "SHTF", 0, true, 4, Some(StateAction::Light1), "SGUNFLASH2");
+		state("SGUNFLASH2", "SHTF", 1, true, 3, Some(StateAction::Light2), "LIGHTDONE");
+		state("DSGUN", "SHT2", 0, false, 1, Some(StateAction::WeaponReady), "DSGUN");
+		state("DSGUNDOWN", "SHT2", 0, false, 1, Some(StateAction::Lower), "DSGUNDOWN");
+		state("DSGUNUP", "SHT2", 0, false, 1, Some(StateAction::Raise), "DSGUNUP");
+		state("DSGUN1", "SHT2", 0, false, 3, None, "DSGUN2");
+		state("DSGUN2", "SHT2", 0, false, 7, Some(StateAction::FireShotgun2), "DSGUN3");
+		state("DSGUN3", "SHT2", 1, false, 7, None, "DSGUN4");
+		state("DSGUN4", "SHT2", 2, false, 7, Some(StateAction::CheckReload), "DSGUN5");
+		state("DSGUN5", "SHT2", 3, false, 7, Some(StateAction::OpenShotgun2), "DSGUN6");
+		state("DSGUN6", "SHT2", 4, false, 7, None, "DSGUN7");
+		state("DSGUN7", "SHT2", 5, false, 7, Some(StateAction::LoadShotgun2), "DSGUN8");
+		state("DSGUN8", "SHT2", 6, false, 6, None, "DSGUN9");
+		state("DSGUN9", "SHT2", 7, false, 6, Some(StateAction::CloseShotgun2), "DSGUN10");
+		state("DSGUN10", "SHT2", 0, false, 5, Some(StateAction::ReFire), "DSGUN");
+		state("DSNR1", "SHT2", 1, false, 7, None, "DSNR2");
+		state("DSNR2", "SHT2", 0, false, 3, None, "DSGUNDOWN");
+		state("DSGUNFLASH1", "SHT2", 8, true, 5, Some(StateAction::Light1), "DSGUNFLASH2");
+		state("DSGUNFLASH2", "SHT2", 9, true, 4, Some(StateAction::Light2), "LIGHTDONE");
+		state("CHAIN", "CHGG", 0, false, 1, Some(StateAction::WeaponReady), "CHAIN");
+		state("CHAINDOWN", "CHGG", 0, false, 1, Some(StateAction::Lower), "CHAINDOWN");
+		state("CHAINUP", "CHGG", 0, false, 1, Some(StateAction::Raise), "CHAINUP");
+		state("CHAIN1", "CHGG", 0, false, 4, Some(StateAction::FireCGun), "CHAIN2");
+		state("CHAIN2", "CHGG", 1, false, 4, Some(StateAction::FireCGun), "CHAIN3");
+		state("CHAIN3", "CHGG", 1, false, 0, Some(StateAction::ReFire), "CHAIN");
+		state("CHAINFLASH1", "CHGF", 0, true, 5, Some(StateAction::Light1), "LIGHTDONE");
+		state("CHAINFLASH2", "CHGF", 1, true, 5, Some(StateAction::Light2), "LIGHTDONE");
+		state("MISSILE", "MISG", 0, false, 1, Some(StateAction::WeaponReady), "MISSILE");
+		state("MISSILEDOWN", "MISG", 0, false, 1, Some(StateAction::Lower), "MISSILEDOWN");
+		state("MISSILEUP", "MISG", 0, false, 1, Some(StateAction::Raise), "MISSILEUP");
+		state("MISSILE1", "MISG", 1, false, 8, Some(StateAction::GunFlash), "MISSILE2");
+		state("MISSILE2", "MISG", 1, false, 12, Some(StateAction::FireMissile), "MISSILE3");
+		state("MISSILE3", "MISG", 1, false, 0, Some(StateAction::ReFire), "MISSILE");
+		state("MISSILEFLASH1", "MISF", 0, true, 3, Some(StateAction::Light1), "MISSILEFLASH2");
+		state("MISSILEFLASH2", "MISF", 1, true, 4, None, "MISSILEFLASH3");
+		state("MISSILEFLASH3", "MISF", 2, true, 4, Some(StateAction::Light2), "MISSILEFLASH4");
+		state("MISSILEFLASH4", "MISF", 3, true, 4, Some(StateAction::Light2), "LIGHTDONE");
+		state("SAW", "SAWG", 2, false, 4, Some(StateAction::WeaponReady), "SAWB");
+		state("SAWB", "SAWG", 3, false, 4, Some(StateAction::WeaponReady), "SAW");
+		state("SAWDOWN", "SAWG", 2, false, 1, Some(StateAction::Lower), "SAWDOWN");
+		state("SAWUP", "SAWG", 2, false, 1, Some(StateAction::Raise), "SAWUP");
+		state("SAW1", "SAWG", 0, false, 4, Some(StateAction::Saw), "SAW2");
+		state("SAW2", "SAWG", 1, false, 4, Some(StateAction::Saw), "SAW3");
+		state("SAW3", "SAWG", 1, false, 0, Some(StateAction::ReFire), "SAW");
+		state("PLASMA", "PLSG", 0, false, 1, Some(StateAction::WeaponReady), "PLASMA");
+		state("PLASMADOWN", "PLSG", 0, false, 1, Some(StateAction::Lower), "PLASMADOWN");
+		state("PLASMAUP", "PLSG", 0, false, 1, Some(StateAction::Raise), "PLASMAUP");
+		state("PLASMA1", "PLSG", 0, false, 3, Some(StateAction::FirePlasma), "PLASMA2");
+		state("PLASMA2", "PLSG", 1, false, 20, Some(StateAction::ReFire), "PLASMA");
+		state("PLASMAFLASH1", "PLSF", 0, true, 4, Some(StateAction::Light1), "LIGHTDONE");
+		state("PLASMAFLASH2", "PLSF", 1, true, 4, Some(StateAction::Light1), "LIGHTDONE");
+		state("BFG", "BFGG", 0, false, 1, Some(StateAction::WeaponReady), "BFG");
+		state("BFGDOWN", "BFGG", 0, false, 1, Some(StateAction::Lower), "BFGDOWN");
+		state("BFGUP", "BFGG", 0, false, 1, Some(StateAction::Raise), "BFGUP");
+		state("BFG1", "BFGG", 0, false, 20, Some(StateAction::BfgSound), "BFG2");
+		state("BFG2", "BFGG", 1, false, 10, Some(StateAction::GunFlash), "BFG3");
+		state("BFG3", "BFGG", 1, false, 10, Some(StateAction::FireBfg), "BFG4");
+		state("BFG4", "BFGG", 1, false, 20, Some(StateAction::ReFire), "BFG");
+		state("BFGFLASH1", "BFGF", 0, true, 11, Some(StateAction::Light1), "BFGFLASH2");
+		state("BFGFLASH2", "BFGF", 1, true, 6, Some(StateAction::Light2), "LIGHTDONE");
+		state("BLOOD1", "BLUD", 2, false, 8, None, "BLOOD2");
+		state("BLOOD2", "BLUD", 1, false, 8, None, "BLOOD3");
+		state("BLOOD3", "BLUD", 0, false, 8, None, "NULL");
+		state("PUFF1", "PUFF", 0, true, 4, None, "PUFF2");
+		state("PUFF2", "PUFF", 1, false, 4, None, "PUFF3");
+		state("PUFF3", "PUFF", 2, false, 4, None, "PUFF4");
+		state("PUFF4", "PUFF", 3, false, 4, None, "NULL");
+		state("TBALL1", "BAL1", 0, true, 4, None, "TBALL2");
+		state("TBALL2", "BAL1", 1, true, 4, None, "TBALL1");
+		state("TBALLX1", "BAL1", 2, true, 6, None, "TBALLX2");
+		state("TBALLX2", "BAL1", 3, true, 6, None, "TBALLX3");
+		state("TBALLX3", "BAL1", 4, true, 6, None, "NULL");
+		state("RBALL1", "BAL2", 0, true, 4, None, "RBALL2");
+		state("RBALL2", "BAL2", 1, true, 4, None, "RBALL1");
+		state("RBALLX1", "BAL2", 2, true, 6, None, "RBALLX2");
+		state("RBALLX2", "BAL2", 3, true, 6, None, "RBALLX3");
+		state("RBALLX3", "BAL2", 4, true, 6, None, "NULL");
+		state("PLASBALL", "PLSS", 0, true, 6, None, "PLASBALL2");
+		state("PLASBALL2", "PLSS", 1, true, 6, None, "PLASBALL");
+		state("PLASEXP", "PLSE", 0, true, 4, None, "PLASEXP2");
+		state("PLASEXP2", "PLSE", 1, true, 4, None, "PLASEXP3");
+		state("PLASEXP3", "PLSE", 2, true, 4, None, "PLASEXP4");
+		state("PLASEXP4", "PLSE", 3, true, 4, None, "PLASEXP5");
+		state("PLASEXP5", "PLSE", 4, true, 4, None, "NULL");
+		state("ROCKET", "MISL", 0, true, 1, None, "ROCKET");
+		state("BFGSHOT", "BFS1", 0, true, 4, None, "BFGSHOT2");
+		state("BFGSHOT2", "BFS1", 1, true, 4, None, "BFGSHOT");
+		state("BFGLAND", "BFE1", 0, true, 8, None, "BFGLAND2");
+		state("BFGLAND2", "BFE1", 1, true, 8, None, "BFGLAND3");
+		state("BFGLAND3", "BFE1", 2, true, 8, Some(StateAction::BfgSpray), "BFGLAND4");
+		state("BFGLAND4", "BFE1", 3, true, 8, None, "BFGLAND5");
+		state("BFGLAND5", "BFE1", 4, true, 8, None, "BFGLAND6");
+		state("BFGLAND6", "BFE1", 5, true, 8, None, "NULL");
+		state("BFGEXP", "BFE2", 0, true, 8, None, "BFGEXP2");
+		state("BFGEXP2", "BFE2", 1, true, 8, None, "BFGEXP3");
+		state("BFGEXP3", "BFE2", 2, true, 8, None, "BFGEXP4");
+		state("BFGEXP4", "BFE2", 3, true, 8, None, "NULL");
+		state("EXPLODE1", "MISL", 1, true, 8, Some(StateAction::Explode), "EXPLODE2");
+		state("EXPLODE2", "MISL", 2, true, 6, None, "EXPLODE3");
+		state("EXPLODE3", "MISL", 3, true, 4, None, "NULL");
+		state("TFOG", "TFOG", 0, true, 6, None, "TFOG01");
+		state("TFOG01", "TFOG", 1, true, 6, None, "TFOG02");
+		state("TFOG02", "TFOG", 0, true, 6, None, "TFOG2");
+		state("TFOG2", "TFOG", 1, true, 6, None, "TFOG3");
+		state("TFOG3", "TFOG", 2, true, 6, None, "TFOG4");
+		state("TFOG4", "TFOG", 3, true, 6, None, "TFOG5");
+		state("TFOG5", "TFOG", 4, true, 6, None, "TFOG6");
+		state("TFOG6", "TFOG", 5, true, 6, None, "TFOG7");
+		state("TFOG7", "TFOG", 6, true, 6, None, "TFOG8");
+		state("TFOG8", "TFOG", 7, true, 6, None, "TFOG9");
+		state("TFOG9", "TFOG", 8, true, 6, None, "TFOG10");
+		state("TFOG10", "TFOG", 9, true, 6, None, "NULL");
+		state("IFOG", "IFOG", 0, true, 6, None, "IFOG01");
+		state("IFOG01", "IFOG", 1, true, 6, None, "IFOG02");
+		state("IFOG02", "IFOG", 0, true, 6, None, "IFOG2");
+		state("IFOG2", "IFOG", 1, true, 6, None, "IFOG3");
+		state("IFOG3", "IFOG", 2, true, 6, None, "IFOG4");
+		state("IFOG4", "IFOG", 3, true, 6, None, "IFOG5");
+		state("IFOG5", "IFOG", 4, true, 6, None, "NULL");
+		state("PLAY", "PLAY", 0, false, -1, None, "NULL");
+		state("PLAY_RUN1", "PLAY", 0, false, 4, None, "PLAY_RUN2");
+		state("PLAY_RUN2", "PLAY", 1, false, 4, None, "PLAY_RUN3");
+		state("PLAY_RUN3", "PLAY", 2, false, 4, None, "PLAY_RUN4");
+		state("PLAY_RUN4", "PLAY", 3, false, 4, None, "PLAY_RUN1");
+		state("PLAY_ATK1", "PLAY", 4, false, 12, None, "PLAY");
+		state("PLAY_ATK2", "PLAY", 5, true, 6, None, "PLAY_ATK1");
+		state("PLAY_PAIN", "PLAY", 6, false, 4, None, "PLAY_PAIN2");
+		state("PLAY_PAIN2", "PLAY", 6, false, 4, Some(StateAction::Pain), "PLAY");
+		state("PLAY_DIE1", "PLAY", 7, false, 10, None, "PLAY_DIE2");
+		state("PLAY_DIE2", "PLAY", 8, false, 10, Some(StateAction::PlayerScream), "PLAY_DIE3");
+		state("PLAY_DIE3", "PLAY", 9, false, 10, Some(StateAction::Fall), "PLAY_DIE4");
+		state("PLAY_DIE4", "PLAY", 10, false, 10, None, "PLAY_DIE5");
+		state("PLAY_DIE5", "PLAY", 11, false, 10, None, "PLAY_DIE6");
+		state("PLAY_DIE6", "PLAY", 12, false, 10, None, "PLAY_DIE7");
+		state("PLAY_DIE7", "PLAY", 13, false, -1, None, "NULL");
+		state("PLAY_XDIE1", "PLAY", 14, false, 5, None, "PLAY_XDIE2");
+		state("PLAY_XDIE2", "PLAY", 15, false, 5, Some(StateAction::XScream), "PLAY_XDIE3");
+		state("PLAY_XDIE3", "PLAY", 16, false, 5, Some(StateAction::Fall), "PLAY_XDIE4");
+		state("PLAY_XDIE4", "PLAY", 17, false, 5, None, "PLAY_XDIE5");
+		state("PLAY_XDIE5", "PLAY", 18, false, 5, None, "PLAY_XDIE6");
+		state("PLAY_XDIE6", "PLAY", 19, false, 5, None, "PLAY_XDIE7");
+		state("PLAY_XDIE7", "PLAY", 20, false, 5, None, "PLAY_XDIE8");
+		state("PLAY_XDIE8", "PLAY", 21, false, 5, None, "PLAY_XDIE9");
+		state("PLAY_XDIE9", "PLAY", 22, false, -1, None, "NULL");
+		state("POSS_STND", "POSS", 0, false, 10, Some(StateAction::Look), "POSS_STND2");
+		state("POSS_STND2", "POSS", 1, false, 10, Some(StateAction::Look), "POSS_STND");
+		state("POSS_RUN1", "POSS", 0, false, 4, Some(StateAction::Chase), "POSS_RUN2");
+		state("POSS_RUN2", "POSS", 0, false, 4, Some(StateAction::Chase), "POSS_RUN3");
+		state("POSS_RUN3", "POSS", 1, false, 4, Some(StateAction::Chase), "POSS_RUN4");
+		state("POSS_RUN4", "POSS", 1, false, 4, Some(StateAction::Chase), "POSS_RUN5");
+		state("POSS_RUN5", "POSS", 2, false, 4, Some(StateAction::Chase), "POSS_RUN6");
+		state("POSS_RUN6", "POSS", 2, false, 4, Some(StateAction::Chase), "POSS_RUN7");
+		state("POSS_RUN7", "POSS", 3, false, 4, Some(StateAction::Chase), "POSS_RUN8");
+		state("POSS_RUN8", "POSS", 3, false, 4, Some(StateAction::Chase), "POSS_RUN1");
+		state("POSS_ATK1", "POSS", 4, false, 10, Some(StateAction::FaceTarget), "POSS_ATK2");
+		state("POSS_ATK2", "POSS", 5, false, 8, Some(StateAction::PosAttack), "POSS_ATK3");
+		state("POSS_ATK3", "POSS", 4, false, 8, None, "POSS_RUN1");
+		state("POSS_PAIN", "POSS", 6, false, 3, None, "POSS_PAIN2");
+		state("POSS_PAIN2", "POSS", 6, false, 3, Some(StateAction::Pain), "POSS_RUN1");
+		state("POSS_DIE1", "POSS", 7, false, 5, None, "POSS_DIE2");
+		state("POSS_DIE2", "POSS", 8, false, 5, Some(StateAction::Scream), "POSS_DIE3");
+		state("POSS_DIE3", "POSS", 9, false, 5, Some(StateAction::Fall), "POSS_DIE4");
+		state("POSS_DIE4", "POSS", 10, false, 5, None, "POSS_DIE5");
+		state("POSS_DIE5", "POSS", 11, false, -1, None, "NULL");
+		state("POSS_XDIE1", "POSS", 12, false, 5, None, "POSS_XDIE2");
+		state("POSS_XDIE2", "POSS", 13, false, 5, Some(StateAction::XScream), "POSS_XDIE3");
+		state("POSS_XDIE3", "POSS", 14, false, 5, Some(StateAction::Fall), "POSS_XDIE4");
+		state("POSS_XDIE4", "POSS", 15, false, 5, None, "POSS_XDIE5");
+		state("POSS_XDIE5", "POSS", 16, false, 5, None, "POSS_XDIE6");
+		state("POSS_XDIE6", "POSS", 17, false, 5, None, "POSS_XDIE7");
+		state("POSS_XDIE7", "POSS", 18, false, 5, None, "POSS_XDIE8");
+		state("POSS_XDIE8", "POSS", 19, false, 5, None, "POSS_XDIE9");
+		state("POSS_XDIE9", "POSS", 20, false, -1, None, "NULL");
+		state("POSS_RAISE1", "POSS", 10, false, 5, None, "POSS_RAISE2");
+		state("POSS_RAISE2", "POSS", 9, false, 5, None, "POSS_RAISE3");
+		state("POSS_RAISE3", "POSS", 8, false, 5, None, "POSS_RAISE4");
+		state("POSS_RAISE4", "POSS", 7, false, 5, None, "POSS_RUN1");
+		state("SPOS_STND", "SPOS", 0, false, 10, Some(StateAction::Look), "SPOS_STND2");
+		state("SPOS_STND2", "SPOS", 1, false, 10, Some(StateAction::Look), "SPOS_STND");
+		state("SPOS_RUN1", "SPOS", 0, false, 3, Some(StateAction::Chase), "SPOS_RUN2");
+		state("SPOS_RUN2", "SPOS", 0, false, 3, Some(StateAction::Chase), "SPOS_RUN3");
+		state("SPOS_RUN3", "SPOS", 1, false, 3, Some(StateAction::Chase), "SPOS_RUN4");
+		state("SPOS_RUN4", "SPOS", 1, false, 3, Some(StateAction::Chase), "SPOS_RUN5");
+		state("SPOS_RUN5", "SPOS", 2, false, 3, Some(StateAction::Chase), "SPOS_RUN6");
+		state("SPOS_RUN6", "SPOS", 2, false, 3, Some(StateAction::Chase), "SPOS_RUN7");
+		state("SPOS_RUN7", "SPOS", 3, false, 3, Some(StateAction::Chase), "SPOS_RUN8");
+		state("SPOS_RUN8", "SPOS", 3, false, 3, Some(StateAction::Chase), "SPOS_RUN1");
+		state("SPOS_ATK1", "SPOS", 4, false, 10, Some(StateAction::FaceTarget), "SPOS_ATK2");
+		state("SPOS_ATK2", "SPOS", 5, true, 10, Some(StateAction::SPosAttack), "SPOS_ATK3");
+		state("SPOS_ATK3", "SPOS", 4, false, 10, None, "SPOS_RUN1");
+		state("SPOS_PAIN", "SPOS", 6, false, 3, None, "SPOS_PAIN2");
+		state("SPOS_PAIN2", "SPOS", 6, false, 3, Some(StateAction::Pain), "SPOS_RUN1");
+		state("SPOS_DIE1", "SPOS", 7, false, 5, None, "SPOS_DIE2");
+		state("SPOS_DIE2", "SPOS", 8, false, 5, Some(StateAction::Scream), "SPOS_DIE3");
+		state("SPOS_DIE3", "SPOS", 9, false, 5, Some(StateAction::Fall), "SPOS_DIE4");
+		state("SPOS_DIE4", "SPOS", 10, false, 5, None, "SPOS_DIE5");
+		state("SPOS_DIE5", "SPOS", 11, false, -1, None, "NULL");
+		state("SPOS_XDIE1", "SPOS", 12, false, 5, None, "SPOS_XDIE2");
+		state("SPOS_XDIE2", "SPOS", 13, false, 5, Some(StateAction::XScream), "SPOS_XDIE3");
+		state("SPOS_XDIE3", "SPOS", 14, false, 5, Some(StateAction::Fall), "SPOS_XDIE4");
+		state("SPOS_XDIE4", "SPOS", 15, false, 5, None, "SPOS_XDIE5");
+		state("SPOS_XDIE5", "SPOS", 16, false, 5, None, "SPOS_XDIE6");
+		state("SPOS_XDIE6", "SPOS", 17, false, 5, None, "SPOS_XDIE7");
+		state("SPOS_XDIE7", "SPOS", 18, false, 5, None, "SPOS_XDIE8");
+		state("SPOS_XDIE8", "SPOS", 19, false, 5, None, "SPOS_XDIE9");
+		state("SPOS_XDIE9", "SPOS", 20, false, -1, None, "NULL");
+		state("SPOS_RAISE1", "SPOS", 11, false, 5, None, "SPOS_RAISE2");
+		state("SPOS_RAISE2", "SPOS", 10, false, 5, None, "SPOS_RAISE3");
+		state("SPOS_RAISE3", "SPOS", 9, false, 5, None, "SPOS_RAISE4");
+		state("SPOS_RAISE4", "SPOS", 8, false, 5, None, "SPOS_RAISE5");
+		state("SPOS_RAISE5", "SPOS", 7, false, 5, None, "SPOS_RUN1");
+		state("VILE_STND", "VILE", 0, false, 10, Some(StateAction::Look), "VILE_STND2");
+		state("VILE_STND2", "VILE", 1, false, 10, Some(StateAction::Look), "VILE_STND");
+		state("VILE_RUN1", "VILE", 0, false, 2, Some(StateAction::VileChase), "VILE_RUN2");
+		state("VILE_RUN2", "VILE", 0, false, 2, Some(StateAction::VileChase), "VILE_RUN3");
+		state("VILE_RUN3", "VILE", 1, false, 2, Some(StateAction::VileChase), "VILE_RUN4");
+		state("VILE_RUN4", "VILE", 1, false, 2, Some(StateAction::VileChase), "VILE_RUN5");
+		state("VILE_RUN5", "VILE", 2, false, 2, Some(StateAction::VileChase), "VILE_RUN6");
+		state("VILE_RUN6", "VILE", 2, false, 2, Some(StateAction::VileChase), "VILE_RUN7");
+		state("VILE_RUN7", "VILE", 3, false, 2, Some(StateAction::VileChase), "VILE_RUN8");
+		state("VILE_RUN8", "VILE", 3, false, 2, Some(StateAction::VileChase), "VILE_RUN9");
+		state("VILE_RUN9", "VILE", 4, false, 2, Some(StateAction::VileChase), "VILE_RUN10");
+		state("VILE_RUN10", "VILE", 4, false, 2, Some(StateAction::VileChase), "VILE_RUN11");
+		state("VILE_RUN11", "VILE", 5, false, 2, Some(StateAction::VileChase), "VILE_RUN12");
+		state("VILE_RUN12", "VILE", 5, false, 2, Some(StateAction::VileChase), "VILE_RUN1");
+		state("VILE_ATK1", "VILE", 6, true, 0, Some(StateAction::VileStart), "VILE_ATK2");
+		state("VILE_ATK2", "VILE", 7, true, 10, Some(StateAction::FaceTarget), "VILE_ATK3");
+		state("VILE_ATK3", "VILE", 8, true, 8, Some(StateAction::VileTarget), "VILE_ATK4");
+		state("VILE_ATK4", "VILE", 9, true, 8, Some(StateAction::FaceTarget), "VILE_ATK5");
+		state("VILE_ATK5", "VILE", 10, true, 8, Some(StateAction::FaceTarget), "VILE_ATK6");
+		state("VILE_ATK6", "VILE", 11, true, 8, Some(StateAction::FaceTarget), "VILE_ATK7");
+		state("VILE_ATK7", "VILE", 12, true, 8, Some(StateAction::FaceTarget), "VILE_ATK8");
+		state("VILE_ATK8", "VILE", 13, true, 8, Some(StateAction::FaceTarget), "VILE_ATK9");
+		state("VILE_ATK9", "VILE", 14, true, 8, Some(StateAction::FaceTarget), "VILE_ATK10");
+		state("VILE_ATK10", "VILE", 15, true, 8, Some(StateAction::VileAttack), "VILE_ATK11");
+		state("VILE_ATK11", "VILE", 15, true, 20, None, "VILE_RUN1");
+		state("VILE_HEAL1", "VILE", 26, true, 10, None, "VILE_HEAL2");
+		state("VILE_HEAL2", "VILE", 27, true, 10, None, "VILE_HEAL3");
+		state("VILE_HEAL3", "VILE", 28, true, 10, None, "VILE_RUN1");
+		state("VILE_PAIN", "VILE", 16, false, 5, None, "VILE_PAIN2");
+		state("VILE_PAIN2", "VILE", 16, false, 5, Some(StateAction::Pain), "VILE_RUN1");
+		state("VILE_DIE1", "VILE", 16, false, 7, None, "VILE_DIE2");
+		state("VILE_DIE2", "VILE", 17, false, 7, Some(StateAction::Scream), "VILE_DIE3");
+		state("VILE_DIE3", "VILE", 18, false, 7, Some(StateAction::Fall), "VILE_DIE4");
+		state("VILE_DIE4", "VILE", 19, false, 7, None, "VILE_DIE5");
+		state("VILE_DIE5", "VILE", 20, false, 7, None, "VILE_DIE6");
+		state("VILE_DIE6", "VILE", 21, false, 7, None, "VILE_DIE7");
+		state("VILE_DIE7", "VILE", 22, false, 7, None, "VILE_DIE8");
+		state("VILE_DIE8", "VILE", 23, false, 5, None, "VILE_DIE9");
+		state("VILE_DIE9", "VILE", 24, false, 5, None, "VILE_DIE10");
+		state("VILE_DIE10", "VILE", 25, false, -1, None, "NULL");
+		state("FIRE1", "FIRE", 0, true, 2, Some(StateAction::StartFire), "FIRE2");
+		state("FIRE2", "FIRE", 1, true, 2, Some(StateAction::Fire), "FIRE3");
+		state("FIRE3", "FIRE", 0, true, 2, Some(StateAction::Fire), "FIRE4");
+		state("FIRE4", "FIRE", 1, true, 2, Some(StateAction::Fire), "FIRE5");
+		state("FIRE5", "FIRE", 2, true, 2, Some(StateAction::FireCrackle), "FIRE6");
+		state("FIRE6", "FIRE", 1, true, 2, Some(StateAction::Fire), "FIRE7");
+		state("FIRE7", "FIRE", 2, true, 2, Some(StateAction::Fire), "FIRE8");
+		state("FIRE8", "FIRE", 1, true, 2, Some(StateAction::Fire), "FIRE9");
+		state("FIRE9", "FIRE", 2, true, 2, Some(StateAction::Fire), "FIRE10");
+		state("FIRE10", "FIRE", 3, true, 2, Some(StateAction::Fire), "FIRE11");
+		state("FIRE11", "FIRE", 2, true, 2, Some(StateAction::Fire), "FIRE12");
+		state("FIRE12", "FIRE", 3, true, 2, Some(StateAction::Fire), "FIRE13");
+		state("FIRE13", "FIRE", 2, true, 2, Some(StateAction::Fire), "FIRE14");
+		state("FIRE14", "FIRE", 3, true, 2, Some(StateAction::Fire), "FIRE15");
+		state("FIRE15", "FIRE", 4, true, 2, Some(StateAction::Fire), "FIRE16");
+		state("FIRE16", "FIRE", 3, true, 2, Some(StateAction::Fire), "FIRE17");
+		state("FIRE17", "FIRE", 4, true, 2, Some(StateAction::Fire), "FIRE18");
+		state("FIRE18", "FIRE", 3, true, 2, Some(StateAction::Fire), "FIRE19");
+		state("FIRE19", "FIRE", 4, true, 2, Some(StateAction::FireCrackle), "FIRE20");
+		state("FIRE20", "FIRE", 5, true, 2, Some(StateAction::Fire), "FIRE21");
+		state("FIRE21", "FIRE", 4, true, 2, Some(StateAction::Fire), "FIRE22");
+		state("FIRE22", "FIRE", 5, true, 2, Some(StateAction::Fire), "FIRE23");
+		state("FIRE23", "FIRE", 4, true, 2, Some(StateAction::Fire), "FIRE24");
+		state("FIRE24", "FIRE", 5, true, 2, Some(StateAction::Fire), "FIRE25");
+		state("FIRE25", "FIRE", 6, true, 2, Some(StateAction::Fire), "FIRE26");
+		state("FIRE26", "FIRE", 7, true, 2, Some(StateAction::Fire), "FIRE27");
+		state("FIRE27", "FIRE", 6, true, 2, Some(StateAction::Fire), "FIRE28");
+		state("FIRE28", "FIRE", 7, true, 2, Some(StateAction::Fire), "FIRE29");
+		state("FIRE29", "FIRE", 6, true, 2, Some(StateAction::Fire), "FIRE30");
+		state("FIRE30", "FIRE", 7, true, 2, Some(StateAction::Fire), "NULL");
+		state("SMOKE1", "PUFF", 1, false, 4, None, "SMOKE2");
+		state("SMOKE2", "PUFF", 2, false, 4, None, "SMOKE3");
+		state("SMOKE3", "PUFF", 1, false, 4, None, "SMOKE4");
+		state("SMOKE4", "PUFF", 2, false, 4, None, "SMOKE5");
+		state("SMOKE5", "PUFF", 3, false, 4, None, "NULL");
+		state("TRACER", "FATB", 0, true, 2, Some(StateAction::Tracer), "TRACER2");
+		state("TRACER2", "FATB", 1, true, 2, Some(StateAction::Tracer), "TRACER");
+		state("TRACEEXP1", "FBXP", 0, true, 8, None, "TRACEEXP2");
+		state("TRACEEXP2", "FBXP", 1, true, 6, None, "TRACEEXP3");
+		state("TRACEEXP3", "FBXP", 2, true, 4, None, "NULL");
+		state("SKEL_STND", "SKEL", 0, false, 10, Some(StateAction::Look), "SKEL_STND2");
+		state("SKEL_STND2", "SKEL", 1, false, 10, Some(StateAction::Look), "SKEL_STND");
+		state("SKEL_RUN1", "SKEL", 0, false, 2, Some(StateAction::Chase), "SKEL_RUN2");
+		state("SKEL_RUN2", "SKEL", 0, false, 2, Some(StateAction::Chase), "SKEL_RUN3");
+		state("SKEL_RUN3", "SKEL", 1, false, 2, Some(StateAction::Chase), "SKEL_RUN4");
+		state("SKEL_RUN4", "SKEL", 1, false, 2, Some(StateAction::Chase), "SKEL_RUN5");
+		state("SKEL_RUN5", "SKEL", 2, false, 2, Some(StateAction::Chase), "SKEL_RUN6");
+		state("SKEL_RUN6", "SKEL", 2, false, 2, Some(StateAction::Chase), "SKEL_RUN7");
+		state("SKEL_RUN7", "SKEL", 3, false, 2, Some(StateAction::Chase), "SKEL_RUN8");
+		state("SKEL_RUN8", "SKEL", 3, false, 2, Some(StateAction::Chase), "SKEL_RUN9");
+		state("SKEL_RUN9", "SKEL", 4, false, 2, Some(StateAction::Chase), "SKEL_RUN10");
+		state("SKEL_RUN10", "SKEL", 4, false, 2, Some(StateAction::Chase), "SKEL_RUN11");
+		state("SKEL_RUN11", "SKEL", 5, false, 2, Some(StateAction::Chase), "SKEL_RUN12");
+		state("SKEL_RUN12", "SKEL", 5, false, 2, Some(StateAction::Chase), "SKEL_RUN1");
+		state("SKEL_FIST1", "SKEL", 6, false, 0, Some(StateAction::FaceTarget), "SKEL_FIST2");
+		state("SKEL_FIST2", "SKEL", 6, false, 6, Some(StateAction::SkelWhoosh), "SKEL_FIST3");
+		state("SKEL_FIST3", "SKEL", 7, false, 6, Some(StateAction::FaceTarget), "SKEL_FIST4");
+		state("SKEL_FIST4", "SKEL", 8, false, 6, Some(StateAction::SkelFist), "SKEL_RUN1");
+		state("SKEL_MISS1", "SKEL", 9, true, 0, Some(StateAction::FaceTarget), "SKEL_MISS2");
+		state("SKEL_MISS2", "SKEL", 9, true, 10, Some(StateAction::FaceTarget), "SKEL_MISS3");
+		state("SKEL_MISS3", "SKEL", 10, false, 10, Some(StateAction::SkelMissile), "SKEL_MISS4");
+		state("SKEL_MISS4", "SKEL", 10, false, 10, Some(StateAction::FaceTarget), "SKEL_RUN1");
+		state("SKEL_PAIN", "SKEL", 11, false, 5, None, "SKEL_PAIN2");
+		state("SKEL_PAIN2", "SKEL", 11, false, 5, Some(StateAction::Pain), "SKEL_RUN1");
+		state("SKEL_DIE1", "SKEL", 11, false, 7, None, "SKEL_DIE2");
+		state("SKEL_DIE2", "SKEL", 12, false, 7, None, "SKEL_DIE3");
+		state("SKEL_DIE3", "SKEL", 13, false, 7, Some(StateAction::Scream), "SKEL_DIE4");
+		state("SKEL_DIE4", "SKEL", 14, false, 7, Some(StateAction::Fall), "SKEL_DIE5");
+		state("SKEL_DIE5", "SKEL", 15, false, 7, None, "SKEL_DIE6");
+		state("SKEL_DIE6", "SKEL", 16, false, -1, None, "NULL");
+		state("SKEL_RAISE1", "SKEL", 16, false, 5, None, "SKEL_RAISE2");
+		state("SKEL_RAISE2", "SKEL", 15, false, 5, None, "SKEL_RAISE3");
+		state("SKEL_RAISE3", "SKEL", 14, false, 5, None, "SKEL_RAISE4");
+		state("SKEL_RAISE4", "SKEL", 13, false, 5, None, "SKEL_RAISE5");
+		state("SKEL_RAISE5", "SKEL", 12, false, 5, None, "SKEL_RAISE6");
+		state("SKEL_RAISE6", "SKEL", 11, false, 5, None, "SKEL_RUN1");
+		state("FATSHOT1", "MANF", 0, true, 4, None, "FATSHOT2");
+		state("FATSHOT2", "MANF", 1, true, 4, None, "FATSHOT1");
+		state("FATSHOTX1", "MISL", 1, true, 8, None, "FATSHOTX2");
+		state("FATSHOTX2", "MISL", 2, true, 6, None, "FATSHOTX3");
+		state("FATSHOTX3", "MISL", 3, true, 4, None, "NULL");
+		state("FATT_STND", "FATT", 0, false, 15, Some(StateAction::Look), "FATT_STND2");
+		state("FATT_STND2", "FATT", 1, false, 15, Some(StateAction::Look), "FATT_STND");
+		state("FATT_RUN1", "FATT", 0, false, 4, Some(StateAction::Chase), "FATT_RUN2");
+		state("FATT_RUN2", "FATT", 0, false, 4, Some(StateAction::Chase), "FATT_RUN3");
+		state("FATT_RUN3", "FATT", 1, false, 4, Some(StateAction::Chase), "FATT_RUN4");
+		state("FATT_RUN4", "FATT", 1, false, 4, Some(StateAction::Chase), "FATT_RUN5");
+		state("FATT_RUN5", "FATT", 2, false, 4, Some(StateAction::Chase), "FATT_RUN6");
+		state("FATT_RUN6", "FATT", 2, false, 4, Some(StateAction::Chase), "FATT_RUN7");
+		state("FATT_RUN7", "FATT", 3, false, 4, Some(StateAction::Chase), "FATT_RUN8");
+		state("FATT_RUN8", "FATT", 3, false, 4, Some(StateAction::Chase), "FATT_RUN9");
+		state("FATT_RUN9", "FATT", 4, false, 4, Some(StateAction::Chase), "FATT_RUN10");
+		state("FATT_RUN10", "FATT", 4, false, 4, Some(StateAction::Chase), "FATT_RUN11");
+		state("FATT_RUN11", "FATT", 5, false, 4, Some(StateAction::Chase), "FATT_RUN12");
+		state("FATT_RUN12", "FATT", 5, false, 4, Some(StateAction::Chase), "FATT_RUN1");
+		state("FATT_ATK1", "FATT", 6, false, 20, Some(StateAction::FatRaise), "FATT_ATK2");
+		state("FATT_ATK2", "FATT", 7, true, 10, Some(StateAction::FatAttack1), "FATT_ATK3");
+		state("FATT_ATK3", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_ATK4");
+		state("FATT_ATK4", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_ATK5");
+		state("FATT_ATK5", "FATT", 7, true, 10, Some(StateAction::FatAttack2), "FATT_ATK6");
+		state("FATT_ATK6", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_ATK7");
+		state("FATT_ATK7", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_ATK8");
+		state("FATT_ATK8", "FATT", 7, true, 10, Some(StateAction::FatAttack3), "FATT_ATK9");
+		state("FATT_ATK9", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_ATK10");
+		state("FATT_ATK10", "FATT", 6, false, 5, Some(StateAction::FaceTarget), "FATT_RUN1");
+		state("FATT_PAIN", "FATT", 9, false, 3, None, "FATT_PAIN2");
+		state("FATT_PAIN2", "FATT", 9, false, 3, Some(StateAction::Pain), "FATT_RUN1");
+		state("FATT_DIE1", "FATT", 10, false, 6, None, "FATT_DIE2");
+		state("FATT_DIE2", "FATT", 11, false, 6, Some(StateAction::Scream), "FATT_DIE3");
+		state("FATT_DIE3", "FATT", 12, false, 6, Some(StateAction::Fall), "FATT_DIE4");
+		state("FATT_DIE4", "FATT", 13, false, 6, None, "FATT_DIE5");
+		state("FATT_DIE5", "FATT", 14, false, 6, None, "FATT_DIE6");
+		state("FATT_DIE6", "FATT", 15, false, 6, None, "FATT_DIE7");
+		state("FATT_DIE7", "FATT", 16, false, 6, None, "FATT_DIE8");
+		state("FATT_DIE8", "FATT", 17, false, 6, None, "FATT_DIE9");
+		state("FATT_DIE9", "FATT", 18, false, 6, None, "FATT_DIE10");
+		state("FATT_DIE10", "FATT", 19, false, -1, Some(StateAction::BossDeath), "NULL");
+		state("FATT_RAISE1", "FATT", 17, false, 5, None, "FATT_RAISE2");
+		state("FATT_RAISE2", "FATT", 16, false, 5, None, "FATT_RAISE3");
+		state("FATT_RAISE3", "FATT", 15, false, 5, None, "FATT_RAISE4");
+		state("FATT_RAISE4", "FATT", 14, false, 5, None, "FATT_RAISE5");
+		state("FATT_RAISE5", "FATT", 13, false, 5, None, "FATT_RAISE6");
+		state("FATT_RAISE6", "FATT", 12, false, 5, None, "FATT_RAISE7");
+		state("FATT_RAISE7", "FATT", 11, false, 5, None, "FATT_RAISE8");
+		state("FATT_RAISE8", "FATT", 10, false, 5, None, "FATT_RUN1");
+		state("CPOS_STND", "CPOS", 0, false, 10, Some(StateAction::Look), "CPOS_STND2");
+		state("CPOS_STND2", "CPOS", 1, false, 10, Some(StateAction::Look), "CPOS_STND");
+		state("CPOS_RUN1", "CPOS", 0, false, 3, Some(StateAction::Chase), "CPOS_RUN2");
+		state("CPOS_RUN2", "CPOS", 0, false, 3, Some(StateAction::Chase), "CPOS_RUN3");
+		state("CPOS_RUN3", "CPOS", 1, false, 3, Some(StateAction::Chase), "CPOS_RUN4");
+		state("CPOS_RUN4", "CPOS", 1, false, 3, Some(StateAction::Chase), "CPOS_RUN5");
+		state("CPOS_RUN5", "CPOS", 2, false, 3, Some(StateAction::Chase), "CPOS_RUN6");
+		state("CPOS_RUN6", "CPOS", 2, false, 3, Some(StateAction::Chase), "CPOS_RUN7");
+		state("CPOS_RUN7", "CPOS", 3, false, 3, Some(StateAction::Chase), "CPOS_RUN8");
+		state("CPOS_RUN8", "CPOS", 3, false, 3, Some(StateAction::Chase), "CPOS_RUN1");
+		state("CPOS_ATK1", "CPOS", 4, false, 10, Some(StateAction::FaceTarget), "CPOS_ATK2");
+		state("CPOS_ATK2", "CPOS", 5, true, 4, Some(StateAction::CPosAttack), "CPOS_ATK3");
+		state("CPOS_ATK3", "CPOS", 4, true, 4, Some(StateAction::CPosAttack), "CPOS_ATK4");
+		state("CPOS_ATK4", "CPOS", 5, false, 1, Some(StateAction::CPosRefire), "CPOS_ATK2");
+		state("CPOS_PAIN", "CPOS", 6, false, 3, None, "CPOS_PAIN2");
+		state("CPOS_PAIN2", "CPOS", 6, false, 3, Some(StateAction::Pain), "CPOS_RUN1");
+		state("CPOS_DIE1", "CPOS", 7, false, 5, None, "CPOS_DIE2");
+		state("CPOS_DIE2", "CPOS", 8, false, 5, Some(StateAction::Scream), "CPOS_DIE3");
+		state("CPOS_DIE3", "CPOS", 9, false, 5, Some(StateAction::Fall), "CPOS_DIE4");
+		state("CPOS_DIE4", "CPOS", 10, false, 5, None, "CPOS_DIE5");
+		state("CPOS_DIE5", "CPOS", 11, false, 5, None, "CPOS_DIE6");
+		state("CPOS_DIE6", "CPOS", 12, false, 5, None, "CPOS_DIE7");
+		state("CPOS_DIE7", "CPOS", 13, false, -1, None, "NULL");
+		state("CPOS_XDIE1", "CPOS", 14, false, 5, None, "CPOS_XDIE2");
+		state("CPOS_XDIE2", "CPOS", 15, false, 5, Some(StateAction::XScream), "CPOS_XDIE3");
+		state("CPOS_XDIE3", "CPOS", 16, false, 5, Some(StateAction::Fall), "CPOS_XDIE4");
+		state("CPOS_XDIE4", "CPOS", 17, false, 5, None, "CPOS_XDIE5");
+		state("CPOS_XDIE5", "CPOS", 18, false, 5, None, "CPOS_XDIE6");
+		state("CPOS_XDIE6", "CPOS", 19, false, -1, None, "NULL");
+		state("CPOS_RAISE1", "CPOS", 13, false, 5, None, "CPOS_RAISE2");
+		state("CPOS_RAISE2", "CPOS", 12, false, 5, None, "CPOS_RAISE3");
+		state("CPOS_RAISE3", "CPOS", 11, false, 5, None, "CPOS_RAISE4");
+		state("CPOS_RAISE4", "CPOS", 10, false, 5, None, "CPOS_RAISE5");
+		state("CPOS_RAISE5", "CPOS", 9, false, 5, None, "CPOS_RAISE6");
+		state("CPOS_RAISE6", "CPOS", 8, false, 5, None, "CPOS_RAISE7");
+		state("CPOS_RAISE7", "CPOS", 7, false, 5, None, "CPOS_RUN1");
+		state("TROO_STND", "TROO", 0, false, 10, Some(StateAction::Look), "TROO_STND2");
+		state("TROO_STND2", "TROO", 1, false, 10, Some(StateAction::Look), "TROO_STND");
+		state("TROO_RUN1", "TROO", 0, false, 3, Some(StateAction::Chase), "TROO_RUN2");
+		state("TROO_RUN2", "TROO", 0, false, 3, Some(StateAction::Chase), "TROO_RUN3");
+		state("TROO_RUN3", "TROO", 1, false, 3, Some(StateAction::Chase), "TROO_RUN4");
+		state("TROO_RUN4", "TROO", 1, false, 3, Some(StateAction::Chase), "TROO_RUN5");
+		state("TROO_RUN5", "TROO", 2, false, 3, Some(StateAction::Chase), "TROO_RUN6");
+		state("TROO_RUN6", "TROO", 2, false, 3, Some(StateAction::Chase), "TROO_RUN7");
+		state("TROO_RUN7", "TROO", 3, false, 3, Some(StateAction::Chase), "TROO_RUN8");
+		state("TROO_RUN8", "TROO", 3, false, 3, Some(StateAction::Chase), "TROO_RUN1");
+		state("TROO_ATK1", "TROO", 4, false, 8, Some(StateAction::FaceTarget), "TROO_ATK2");
+		state("TROO_ATK2", "TROO", 5, false, 8, Some(StateAction::FaceTarget), "TROO_ATK3");
+		state("TROO_ATK3", "TROO", 6, false, 6, Some(StateAction::TroopAttack), "TROO_RUN1");
+		state("TROO_PAIN", "TROO", 7, false, 2, None, "TROO_PAIN2");
+		state("TROO_PAIN2", "TROO", 7, false, 2, Some(StateAction::Pain), "TROO_RUN1");
+		state("TROO_DIE1", "TROO", 8, false, 8, None, "TROO_DIE2");
+		state("TROO_DIE2", "TROO", 9, false, 8, Some(StateAction::Scream), "TROO_DIE3");
+		state("TROO_DIE3", "TROO", 10, false, 6, None, "TROO_DIE4");
+		state("TROO_DIE4", "TROO", 11, false, 6, Some(StateAction::Fall), "TROO_DIE5");
+		state("TROO_DIE5", "TROO", 12, false, -1, None, "NULL");
+		state("TROO_XDIE1", "TROO", 13, false, 5, None, "TROO_XDIE2");
+		state("TROO_XDIE2", "TROO", 14, false, 5, Some(StateAction::XScream), "TROO_XDIE3");
+		state("TROO_XDIE3", "TROO", 15, false, 5, None, "TROO_XDIE4");
+		state("TROO_XDIE4", "TROO", 16, false, 5, Some(StateAction::Fall), "TROO_XDIE5");
+		state("TROO_XDIE5", "TROO", 17, false, 5, None, "TROO_XDIE6");
+		state("TROO_XDIE6", "TROO", 18, false, 5, None, "TROO_XDIE7");
+		state("TROO_XDIE7", "TROO", 19, false, 5, None, "TROO_XDIE8");
+		state("TROO_XDIE8", "TROO", 20, false, -1, None, "NULL");
+		state("TROO_RAISE1", "TROO", 12, false, 8, None, "TROO_RAISE2");
+		state("TROO_RAISE2", "TROO", 11, false, 8, None, "TROO_RAISE3");
+		state("TROO_RAISE3", "TROO", 10, false, 6, None, "TROO_RAISE4");
+		state("TROO_RAISE4", "TROO", 9, false, 6, None, "TROO_RAISE5");
+		state("TROO_RAISE5", "TROO", 8, false, 6, None, "TROO_RUN1");
+		state("SARG_STND", "SARG", 0, false, 10, Some(StateAction::Look), "SARG_STND2");
+		state("SARG_STND2", "SARG", 1, false, 10, Some(StateAction::Look), "SARG_STND");
+		state("SARG_RUN1", "SARG", 0, false, 2, Some(StateAction::Chase), "SARG_RUN2");
+		state("SARG_RUN2", "SARG", 0, false, 2, Some(StateAction::Chase), "SARG_RUN3");
+		state("SARG_RUN3", "SARG", 1, false, 2, Some(StateAction::Chase), "SARG_RUN4");
+		state("SARG_RUN4", "SARG", 1, false, 2, Some(StateAction::Chase), "SARG_RUN5");
+		state("SARG_RUN5", "SARG", 2, false, 2, Some(StateAction::Chase), "SARG_RUN6");
+		state("SARG_RUN6", "SARG", 2, false, 2, Some(StateAction::Chase), "SARG_RUN7");
+		state("SARG_RUN7", "SARG", 3, false, 2, Some(StateAction::Chase), "SARG_RUN8");
+		state("SARG_RUN8", "SARG", 3, false, 2, Some(StateAction::Chase), "SARG_RUN1");
+		state("SARG_ATK1", "SARG", 4, false, 8, Some(StateAction::FaceTarget), "SARG_ATK2");
+		state("SARG_ATK2", "SARG", 5, false, 8, Some(StateAction::FaceTarget), "SARG_ATK3");
+		state("SARG_ATK3", "SARG", 6, false, 8, Some(StateAction::SargAttack), "SARG_RUN1");
+		state("SARG_PAIN", "SARG", 7, false, 2, None, "SARG_PAIN2");
+		state("SARG_PAIN2", "SARG", 7, false, 2, Some(StateAction::Pain), "SARG_RUN1");
+		state("SARG_DIE1", "SARG", 8, false, 8, None, "SARG_DIE2");
+		state("SARG_DIE2", "SARG", 9, false, 8, Some(StateAction::Scream), "SARG_DIE3");
+		state("SARG_DIE3", "SARG", 10, false, 4, None, "SARG_DIE4");
+		state("SARG_DIE4", "SARG", 11, false, 4, Some(StateAction::Fall), "SARG_DIE5");
+		state("SARG_DIE5", "SARG", 12, false, 4, None, "SARG_DIE6");
+		state("SARG_DIE6", "SARG", 13, false, -1, None, "NULL");
+		state("SARG_RAISE1", "SARG", 13, false, 5, None, "SARG_RAISE2");
+		state("SARG_RAISE2", "SARG", 12, false, 5, None, "SARG_RAISE3");
+		state("SARG_RAISE3", "SARG", 11, false, 5, None, "SARG_RAISE4");
+		state("SARG_RAISE4", "SARG", 10, false, 5, None, "SARG_RAISE5");
+		state("SARG_RAISE5", "SARG", 9, false, 5, None, "SARG_RAISE6");
+		state("SARG_RAISE6", "SARG", 8, false, 5, None, "SARG_RUN1");
+		state("HEAD_STND", "HEAD", 0, false, 10, Some(StateAction::Look), "HEAD_STND");
+		state("HEAD_RUN1", "HEAD", 0, false, 3, Some(StateAction::Chase), "HEAD_RUN1");
+		state("HEAD_ATK1", "HEAD", 1, false, 5, Some(StateAction::FaceTarget), "HEAD_ATK2");
+		state("HEAD_ATK2", "HEAD", 2, false, 5, Some(StateAction::FaceTarget), "HEAD_ATK3");
+		state("HEAD_ATK3", "HEAD", 3, true, 5, Some(StateAction::HeadAttack), "HEAD_RUN1");
+		state("HEAD_PAIN", "HEAD", 4, false, 3, None, "HEAD_PAIN2");
+		state("HEAD_PAIN2", "HEAD", 4, false, 3, Some(StateAction::Pain), "HEAD_PAIN3");
+		state("HEAD_PAIN3", "HEAD", 5, false, 6, None, "HEAD_RUN1");
+		state("HEAD_DIE1", "HEAD", 6, false, 8, None, "HEAD_DIE2");
+		state("HEAD_DIE2", "HEAD", 7, false, 8, Some(StateAction::Scream), "HEAD_DIE3");
+		state("HEAD_DIE3", "HEAD", 8, false, 8, None, "HEAD_DIE4");
+		state("HEAD_DIE4", "HEAD", 9, false, 8, None, "HEAD_DIE5");
+		state("HEAD_DIE5", "HEAD", 10, false, 8, Some(StateAction::Fall), "HEAD_DIE6");
+		state("HEAD_DIE6", "HEAD", 11, false, -1, None, "NULL");
+		state("HEAD_RAISE1", "HEAD", 11, false, 8, None, "HEAD_RAISE2");
+		state("HEAD_RAISE2", "HEAD", 10, false, 8, None, "HEAD_RAISE3");
+		state("HEAD_RAISE3", "HEAD", 9, false, 8, None, "HEAD_RAISE4");
+		state("HEAD_RAISE4", "HEAD", 8, false, 8, None, "HEAD_RAISE5");
+		state("HEAD_RAISE5", "HEAD", 7, false, 8, None, "HEAD_RAISE6");
+		state("HEAD_RAISE6", "HEAD", 6, false, 8, None, "HEAD_RUN1");
+		state("BRBALL1", "BAL7", 0, true, 4, None, "BRBALL2");
+		state("BRBALL2", "BAL7", 1, true, 4, None, "BRBALL1");
+		state("BRBALLX1", "BAL7", 2, true, 6, None, "BRBALLX2");
+		state("BRBALLX2", "BAL7", 3, true, 6, None, "BRBALLX3");
+		state("BRBALLX3", "BAL7", 4, true, 6, None, "NULL");
+		state("BOSS_STND", "BOSS", 0, false, 10, Some(StateAction::Look), "BOSS_STND2");
+		state("BOSS_STND2", "BOSS", 1, false, 10, Some(StateAction::Look), "BOSS_STND");
+		state("BOSS_RUN1", "BOSS", 0, false, 3, Some(StateAction::Chase), "BOSS_RUN2");
+		state("BOSS_RUN2", "BOSS", 0, false, 3, Some(StateAction::Chase), "BOSS_RUN3");
+		state("BOSS_RUN3", "BOSS", 1, false, 3, Some(StateAction::Chase), "BOSS_RUN4");
+		state("BOSS_RUN4", "BOSS", 1, false, 3, Some(StateAction::Chase), "BOSS_RUN5");
+		state("BOSS_RUN5", "BOSS", 2, false, 3, Some(StateAction::Chase), "BOSS_RUN6");
+		state("BOSS_RUN6", "BOSS", 2, false, 3, Some(StateAction::Chase), "BOSS_RUN7");
+		state("BOSS_RUN7", "BOSS", 3, false, 3, Some(StateAction::Chase), "BOSS_RUN8");
+		state("BOSS_RUN8", "BOSS", 3, false, 3, Some(StateAction::Chase), "BOSS_RUN1");
+		state("BOSS_ATK1", "BOSS", 4, false, 8, Some(StateAction::FaceTarget), "BOSS_ATK2");
+		state("BOSS_ATK2", "BOSS", 5, false, 8, Some(StateAction::FaceTarget), "BOSS_ATK3");
+		state("BOSS_ATK3", "BOSS", 6, false, 8, Some(StateAction::BruisAttack), "BOSS_RUN1");
+		state("BOSS_PAIN", "BOSS", 7, false, 2, None, "BOSS_PAIN2");
+		state("BOSS_PAIN2", "BOSS", 7, false, 2, Some(StateAction::Pain), "BOSS_RUN1");
+		state("BOSS_DIE1", "BOSS", 8, false, 8, None, "BOSS_DIE2");
+		state("BOSS_DIE2", "BOSS", 9, false, 8, Some(StateAction::Scream), "BOSS_DIE3");
+		state("BOSS_DIE3", "BOSS", 10, false, 8, None, "BOSS_DIE4");
+		state("BOSS_DIE4", "BOSS", 11, false, 8, Some(StateAction::Fall), "BOSS_DIE5");
+		state("BOSS_DIE5", "BOSS", 12, false, 8, None, "BOSS_DIE6");
+		state("BOSS_DIE6", "BOSS", 13, false, 8, None, "BOSS_DIE7");
+		state("BOSS_DIE7", "BOSS", 14, false, -1, Some(StateAction::BossDeath), "NULL");
+		state("BOSS_RAISE1", "BOSS", 14, false, 8, None, "BOSS_RAISE2");
+		state("BOSS_RAISE2", "BOSS", 13, false, 8, None, "BOSS_RAISE3");
+		state("BOSS_RAISE3", "BOSS", 12, false, 8, None, "BOSS_RAISE4");
+		state("BOSS_RAISE4", "BOSS", 11, false, 8, None, "BOSS_RAISE5");
+		state("BOSS_RAISE5", "BOSS", 10, false, 8, None, "BOSS_RAISE6");
+		state("BOSS_RAISE6", "BOSS", 9, false, 8, None, "BOSS_RAISE7");
+		state("BOSS_RAISE7", "BOSS", 8, false, 8, None, "BOSS_RUN1");
+		state("BOS2_STND", "BOS2", 0, false, 10, Some(StateAction::Look), "BOS2_STND2");
+		state("BOS2_STND2", "BOS2", 1, false, 10, Some(StateAction::Look), "BOS2_STND");
+		state("BOS2_RUN1", "BOS2", 0, false, 3, Some(StateAction::Chase), "BOS2_RUN2");
+		state("BOS2_RUN2", "BOS2", 0, false, 3, Some(StateAction::Chase), "BOS2_RUN3");
+		state("BOS2_RUN3", "BOS2", 1, false, 3, Some(StateAction::Chase), "BOS2_RUN4");
+		state("BOS2_RUN4", "BOS2", 1, false, 3, Some(StateAction::Chase), "BOS2_RUN5");
+		state("BOS2_RUN5", "BOS2", 2, false, 3, Some(StateAction::Chase), "BOS2_RUN6");
+		state("BOS2_RUN6", "BOS2", 2, false, 3, Some(StateAction::Chase), "BOS2_RUN7");
+		state("BOS2_RUN7", "BOS2", 3, false, 3, Some(StateAction::Chase), "BOS2_RUN8");
+		state("BOS2_RUN8", "BOS2", 3, false, 3, Some(StateAction::Chase), "BOS2_RUN1");
+		state("BOS2_ATK1", "BOS2", 4, false, 8, Some(StateAction::FaceTarget), "BOS2_ATK2");
+		state("BOS2_ATK2", "BOS2", 5, false, 8, Some(StateAction::FaceTarget), "BOS2_ATK3");
+		state("BOS2_ATK3", "BOS2", 6, false, 8, Some(StateAction::BruisAttack), "BOS2_RUN1");
+		state("BOS2_PAIN", "BOS2", 7, false, 2, None, "BOS2_PAIN2");
+		state("BOS2_PAIN2", "BOS2", 7, false, 2, Some(StateAction::Pain), "BOS2_RUN1");
+		state("BOS2_DIE1", "BOS2", 8, false, 8, None, "BOS2_DIE2");
+		state("BOS2_DIE2", "BOS2", 9, false, 8, Some(StateAction::Scream), "BOS2_DIE3");
+		state("BOS2_DIE3", "BOS2", 10, false, 8, None, "BOS2_DIE4");
+		state("BOS2_DIE4", "BOS2", 11, false, 8, Some(StateAction::Fall), "BOS2_DIE5");
+		state("BOS2_DIE5", "BOS2", 12, false, 8, None, "BOS2_DIE6");
+		state("BOS2_DIE6", "BOS2", 13, false, 8, None, "BOS2_DIE7");
+		state("BOS2_DIE7", "BOS2", 14, false, -1, None, "NULL");
+		state("BOS2_RAISE1", "BOS2", 14, false, 8, None, "BOS2_RAISE2");
+		state("BOS2_RAISE2", "BOS2", 13, false, 8, None, "BOS2_RAISE3");
+		state("BOS2_RAISE3", "BOS2", 12, false, 8, None, "BOS2_RAISE4");
+		state("BOS2_RAISE4", "BOS2", 11, false, 8, None, "BOS2_RAISE5");
+		state("BOS2_RAISE5", "BOS2", 10, false, 8, None, "BOS2_RAISE6");
+		state("BOS2_RAISE6", "BOS2", 9, false, 8, None, "BOS2_RAISE7");
+		state("BOS2_RAISE7", "BOS2", 8, false, 8, None, "BOS2_RUN1");
+		state("SKULL_STND", "SKUL", 0, true, 10, Some(StateAction::Look), "SKULL_STND2");
+		state("SKULL_STND2", "SKUL", 1, true, 10, Some(StateAction::Look), "SKULL_STND");
+		state("SKULL_RUN1", "SKUL", 0, true, 6, Some(StateAction::Chase), "SKULL_RUN2");
+		state("SKULL_RUN2", "SKUL", 1, true, 6, Some(StateAction::Chase), "SKULL_RUN1");
+		state("SKULL_ATK1", "SKUL", 2, true, 10, Some(StateAction::FaceTarget), "SKULL_ATK2");
+		state("SKULL_ATK2", "SKUL", 3, true, 4, Some(StateAction::SkullAttack), "SKULL_ATK3");
+		state("SKULL_ATK3", "SKUL", 2, true, 4, None, "SKULL_ATK4");
+		state("SKULL_ATK4", "SKUL", 3, true, 4, None, "SKULL_ATK3");
+		state("SKULL_PAIN", "SKUL", 4, true, 3, None, "SKULL_PAIN2");
+		state("SKULL_PAIN2", "SKUL", 4, true, 3, Some(StateAction::Pain), "SKULL_RUN1");
+		state("SKULL_DIE1", "SKUL", 5, true, 6, None, "SKULL_DIE2");
+		state("SKULL_DIE2", "SKUL", 6, true, 6, Some(StateAction::Scream), "SKULL_DIE3");
+		state("SKULL_DIE3", "SKUL", 7, true, 6, None, "SKULL_DIE4");
+		state("SKULL_DIE4", "SKUL", 8, true, 6, Some(StateAction::Fall), "SKULL_DIE5");
+		state("SKULL_DIE5", "SKUL", 9, false, 6, None, "SKULL_DIE6");
+		state("SKULL_DIE6", "SKUL", 10, false, 6, None, "NULL");
+		state("SPID_STND", "SPID", 0, false, 10, Some(StateAction::Look), "SPID_STND2");
+		state("SPID_STND2", "SPID", 1, false, 10, Some(StateAction::Look), "SPID_STND");
+		state("SPID_RUN1", "SPID", 0, false, 3, Some(StateAction::Metal), "SPID_RUN2");
+		state("SPID_RUN2", "SPID", 0, false, 3, Some(StateAction::Chase), "SPID_RUN3");
+		state("SPID_RUN3", "SPID", 1, false, 3, Some(StateAction::Chase), "SPID_RUN4");
+		state("SPID_RUN4", "SPID", 1, false, 3, Some(StateAction::Chase), "SPID_RUN5");
+		state("SPID_RUN5", "SPID", 2, false, 3, Some(StateAction::Metal), "SPID_RUN6");
+		state("SPID_RUN6", "SPID", 2, false, 3, Some(StateAction::Chase), "SPID_RUN7");
+		state("SPID_RUN7", "SPID", 3, false, 3, Some(StateAction::Chase), "SPID_RUN8");
+		state("SPID_RUN8", "SPID", 3, false, 3, Some(StateAction::Chase), "SPID_RUN9");
+		state("SPID_RUN9", "SPID", 4, false, 3, Some(StateAction::Metal), "SPID_RUN10");
+		state("SPID_RUN10", "SPID", 4, false, 3, Some(StateAction::Chase), "SPID_RUN11");
+		state("SPID_RUN11", "SPID", 5, false, 3, Some(StateAction::Chase), "SPID_RUN12");
+		state("SPID_RUN12", "SPID", 5, false, 3, Some(StateAction::Chase), "SPID_RUN1");
+		state("SPID_ATK1", "SPID", 0, true, 20, Some(StateAction::FaceTarget), "SPID_ATK2");
+		state("SPID_ATK2", "SPID", 6, true, 4, Some(StateAction::SPosAttack), "SPID_ATK3");
+		state("SPID_ATK3", "SPID", 7, true, 4, Some(StateAction::SPosAttack), "SPID_ATK4");
+		state("SPID_ATK4", "SPID", 7, true, 1, Some(StateAction::SpidRefire), "SPID_ATK2");
+		state("SPID_PAIN", "SPID", 8, false, 3, None, "SPID_PAIN2");
+		state("SPID_PAIN2", "SPID", 8, false, 3, Some(StateAction::Pain), "SPID_RUN1");
+		state("SPID_DIE1", "SPID", 9, false, 20, Some(StateAction::Scream), "SPID_DIE2");
+		state("SPID_DIE2", "SPID", 10, false, 10, Some(StateAction::Fall), "SPID_DIE3");
+		state("SPID_DIE3", "SPID", 11, false, 10, None, "SPID_DIE4");
+		state("SPID_DIE4", "SPID", 12, false, 10, None, "SPID_DIE5");
+		state("SPID_DIE5", "SPID", 13, false, 10, None, "SPID_DIE6");
+		state("SPID_DIE6", "SPID", 14, false, 10, None, "SPID_DIE7");
+		state("SPID_DIE7", "SPID", 15, false, 10, None, "SPID_DIE8");
+		state("SPID_DIE8", "SPID", 16, false, 10, None, "SPID_DIE9");
+		state("SPID_DIE9", "SPID", 17, false, 10, None, "SPID_DIE10");
+		state("SPID_DIE10", "SPID", 18, false, 30, None, "SPID_DIE11");
+		state("SPID_DIE11", "SPID", 18, false, -1, Some(StateAction::BossDeath), "NULL");
+		state("BSPI_STND", "BSPI", 0, false, 10, Some(StateAction::Look), "BSPI_STND2");
+		state("BSPI_STND2", "BSPI", 1, false, 10, Some(StateAction::Look), "BSPI_STND");
+		state("BSPI_SIGHT", "BSPI", 0, false, 20, None, "BSPI_RUN1");
+		state("BSPI_RUN1", "BSPI", 0, false, 3, Some(StateAction::BabyMetal), "BSPI_RUN2");
+		state("BSPI_RUN2", "BSPI", 0, false, 3, Some(StateAction::Chase), "BSPI_RUN3");
+		state("BSPI_RUN3", "BSPI", 1, false, 3, Some(StateAction::Chase), "BSPI_RUN4");
+		state("BSPI_RUN4", "BSPI", 1, false, 3, Some(StateAction::Chase), "BSPI_RUN5");
+		state("BSPI_RUN5", "BSPI", 2, false, 3, Some(StateAction::Chase), "BSPI_RUN6");
+		state("BSPI_RUN6", "BSPI", 2, false, 3, Some(StateAction::Chase), "BSPI_RUN7");
+		state("BSPI_RUN7", "BSPI", 3, false, 3, Some(StateAction::BabyMetal), "BSPI_RUN8");
+		state("BSPI_RUN8", "BSPI", 3, false, 3, Some(StateAction::Chase), "BSPI_RUN9");
+		state("BSPI_RUN9", "BSPI", 4, false, 3, Some(StateAction::Chase), "BSPI_RUN10");
+		state("BSPI_RUN10", "BSPI", 4, false, 3, Some(StateAction::Chase), "BSPI_RUN11");
+		state("BSPI_RUN11", "BSPI", 5, false, 3, Some(StateAction::Chase), "BSPI_RUN12");
+		state("BSPI_RUN12", "BSPI", 5, false, 3, Some(StateAction::Chase), "BSPI_RUN1");
+		state("BSPI_ATK1", "BSPI", 0, true, 20, Some(StateAction::FaceTarget), "BSPI_ATK2");
+		state("BSPI_ATK2", "BSPI", 6, true, 4, Some(StateAction::BspiAttack), "BSPI_ATK3");
+		state("BSPI_ATK3", "BSPI", 7, true, 4, None, "BSPI_ATK4");
+		state("BSPI_ATK4", "BSPI", 7, true, 1, Some(StateAction::SpidRefire), "BSPI_ATK2");
+		state("BSPI_PAIN", "BSPI", 8, false, 3, None, "BSPI_PAIN2");
+		state("BSPI_PAIN2", "BSPI", 8, false, 3, Some(StateAction::Pain), "BSPI_RUN1");
+		state("BSPI_DIE1", "BSPI", 9, false, 20, Some(StateAction::Scream), "BSPI_DIE2");
+		state("BSPI_DIE2", "BSPI", 10, false, 7, Some(StateAction::Fall), "BSPI_DIE3");
+		state("BSPI_DIE3", "BSPI", 11, false, 7, None, "BSPI_DIE4");
+		state("BSPI_DIE4", "BSPI", 12, false, 7, None, "BSPI_DIE5");
+		state("BSPI_DIE5", "BSPI", 13, false, 7, None, "BSPI_DIE6");
+		state("BSPI_DIE6", "BSPI", 14, false, 7, None, "BSPI_DIE7");
+		state("BSPI_DIE7", "BSPI", 15, false, -1, Some(StateAction::BossDeath), "NULL");
+		state("BSPI_RAISE1", "BSPI", 15, false, 5, None, "BSPI_RAISE2");
+		state("BSPI_RAISE2", "BSPI", 14, false, 5, None, "BSPI_RAISE3");
+		state("BSPI_RAISE3", "BSPI", 13, false, 5, None, "BSPI_RAISE4");
+		state("BSPI_RAISE4", "BSPI", 12, false, 5, None, "BSPI_RAISE5");
+		state("BSPI_RAISE5", "BSPI", 11, false, 5, None, "BSPI_RAISE6");
+		state("BSPI_RAISE6", "BSPI", 10, false, 5, None, "BSPI_RAISE7");
+		state("BSPI_RAISE7", "BSPI", 9, false, 5, None, "BSPI_RUN1");
+		state("ARACH_PLAZ", "APLS", 0, true, 5, None, "ARACH_PLAZ2");
+		state("ARACH_PLAZ2", "APLS", 1, true, 5, None, "ARACH_PLAZ");
+		state("ARACH_PLEX", "APBX", 0, true, 5, None, "ARACH_PLEX2");
+		state("ARACH_PLEX2", "APBX", 1, true, 5, None, "ARACH_PLEX3");
+		state("ARACH_PLEX3", "APBX", 2, true, 5, None, "ARACH_PLEX4");
+		state("ARACH_PLEX4", "APBX", 3, true, 5, None, "ARACH_PLEX5");
+		state("ARACH_PLEX5", "APBX", 4, true, 5, None, "NULL");
+		state("CYBER_STND", "CYBR", 0, false, 10, Some(StateAction::Look), "CYBER_STND2");
+		state("CYBER_STND2", "CYBR", 1, false, 10, Some(StateAction::Look), "CYBER_STND");
+		state("CYBER_RUN1", "CYBR", 0, false, 3, Some(StateAction::Hoof), "CYBER_RUN2");
+		state("CYBER_RUN2", "CYBR", 0, false, 3, Some(StateAction::Chase), "CYBER_RUN3");
+		state("CYBER_RUN3", "CYBR", 1, false, 3, Some(StateAction::Chase), "CYBER_RUN4");
+		state("CYBER_RUN4", "CYBR", 1, false, 3, Some(StateAction::Chase), "CYBER_RUN5");
+		state("CYBER_RUN5", "CYBR", 2, false, 3, Some(StateAction::Chase), "CYBER_RUN6");
+		state("CYBER_RUN6", "CYBR", 2, false, 3, Some(StateAction::Chase), "CYBER_RUN7");
+		state("CYBER_RUN7", "CYBR", 3, false, 3, Some(StateAction::Metal), "CYBER_RUN8");
+		state("CYBER_RUN8", "CYBR", 3, false, 3, Some(StateAction::Chase), "CYBER_RUN1");
+		state("CYBER_ATK1", "CYBR", 4, false, 6, Some(StateAction::FaceTarget), "CYBER_ATK2");
+		state("CYBER_ATK2", "CYBR", 5, false, 12, Some(StateAction::CyberAttack), "CYBER_ATK3");
+		state("CYBER_ATK3", "CYBR", 4, false, 12, Some(StateAction::FaceTarget), "CYBER_ATK4");
+		state("CYBER_ATK4", "CYBR", 5, false, 12, Some(StateAction::CyberAttack), "CYBER_ATK5");
+		state("CYBER_ATK5", "CYBR", 4, false, 12, Some(StateAction::FaceTarget), "CYBER_ATK6");
+		state("CYBER_ATK6", "CYBR", 5, false, 12, Some(StateAction::CyberAttack), "CYBER_RUN1");
+		state("CYBER_PAIN", "CYBR", 6, false, 10, Some(StateAction::Pain), "CYBER_RUN1");
+		state("CYBER_DIE1", "CYBR", 7, false, 10, None, "CYBER_DIE2");
+		state("CYBER_DIE2", "CYBR", 8, false, 10, Some(StateAction::Scream), "CYBER_DIE3");
+		state("CYBER_DIE3", "CYBR", 9, false, 10, None, "CYBER_DIE4");
+		state("CYBER_DIE4", "CYBR", 10, false, 10, None, "CYBER_DIE5");
+		state("CYBER_DIE5", "CYBR", 11, false, 10, None, "CYBER_DIE6");
+		state("CYBER_DIE6", "CYBR", 12, false, 10, Some(StateAction::Fall), "CYBER_DIE7");
+		state("CYBER_DIE7", "CYBR", 13, false, 10, None, "CYBER_DIE8");
+		state("CYBER_DIE8", "CYBR", 14, false, 10, None, "CYBER_DIE9");
+		state("CYBER_DIE9", "CYBR", 15, false, 30, None, "CYBER_DIE10");
+		state("CYBER_DIE10", "CYBR", 15, false, -1, Some(StateAction::BossDeath), "NULL");
+		state("PAIN_STND", "PAIN", 0, false, 10, Some(StateAction::Look), "PAIN_STND");
+		state("PAIN_RUN1", "PAIN", 0, false, 3, Some(StateAction::Chase), "PAIN_RUN2");
+		state("PAIN_RUN2", "PAIN", 0, false, 3, Some(StateAction::Chase), "PAIN_RUN3");
+		state("PAIN_RUN3", "PAIN", 1, false, 3, Some(StateAction::Chase), "PAIN_RUN4");
+		state("PAIN_RUN4", "PAIN", 1, false, 3, Some(StateAction::Chase), "PAIN_RUN5");
+		state("PAIN_RUN5", "PAIN", 2, false, 3, Some(StateAction::Chase), "PAIN_RUN6");
+		state("PAIN_RUN6", "PAIN", 2, false, 3, Some(StateAction::Chase), "PAIN_RUN1");
+		state("PAIN_ATK1", "PAIN", 3, false, 5, Some(StateAction::FaceTarget), "PAIN_ATK2");
+		state("PAIN_ATK2", "PAIN", 4, false, 5, Some(StateAction::FaceTarget), "PAIN_ATK3");
+		state("PAIN_ATK3", "PAIN", 5, true, 5, Some(StateAction::FaceTarget), "PAIN_ATK4");
+		state("PAIN_ATK4", "PAIN", 5, true, 0, Some(StateAction::PainAttack), "PAIN_RUN1");
+		state("PAIN_PAIN", "PAIN", 6, false, 6, None, "PAIN_PAIN2");
+		state("PAIN_PAIN2", "PAIN", 6, false, 6, Some(StateAction::Pain), "PAIN_RUN1");
+		state("PAIN_DIE1", "PAIN", 7, true, 8, None, "PAIN_DIE2");
+		state("PAIN_DIE2", "PAIN", 8, true, 8, Some(StateAction::Scream), "PAIN_DIE3");
+		state("PAIN_DIE3", "PAIN", 9, true, 8, None, "PAIN_DIE4");
+		state("PAIN_DIE4", "PAIN", 10, true, 8, None, "PAIN_DIE5");
+		state("PAIN_DIE5", "PAIN", 11, true, 8, Some(StateAction::PainDie), "PAIN_DIE6");
+		state("PAIN_DIE6", "PAIN", 12, true, 8, None, "NULL");
+		state("PAIN_RAISE1", "PAIN", 12, false, 8, None, "PAIN_RAISE2");
+		state("PAIN_RAISE2", "PAIN", 11, false, 8, None, "PAIN_RAISE3");
+		state("PAIN_RAISE3", "PAIN", 10, false, 8, None, "PAIN_RAISE4");
+		state("PAIN_RAISE4", "PAIN", 9, false, 8, None, "PAIN_RAISE5");
+		state("PAIN_RAISE5", "PAIN", 8, false, 8, None, "PAIN_RAISE6");
+		state("PAIN_RAISE6", "PAIN", 7, false, 8, None, "PAIN_RUN1");
+		state("SSWV_STND", "SSWV", 0, false, 10, Some(StateAction::Look), "SSWV_STND2");
+		state("SSWV_STND2", "SSWV", 1, false, 10, Some(StateAction::Look), "SSWV_STND");
+		state("SSWV_RUN1", "SSWV", 0, false, 3, Some(StateAction::Chase), "SSWV_RUN2");
+		state("SSWV_RUN2", "SSWV", 0, false, 3, Some(StateAction::Chase), "SSWV_RUN3");
+		state("SSWV_RUN3", "SSWV", 1, false, 3, Some(StateAction::Chase), "SSWV_RUN4");
+		state("SSWV_RUN4", "SSWV", 1, false, 3, Some(StateAction::Chase), "SSWV_RUN5");
+		state("SSWV_RUN5", "SSWV", 2, false, 3, Some(StateAction::Chase), "SSWV_RUN6");
+		state("SSWV_RUN6", "SSWV", 2, false, 3, Some(StateAction::Chase), "SSWV_RUN7");
+		state("SSWV_RUN7", "SSWV", 3, false, 3, Some(StateAction::Chase), "SSWV_RUN8");
+		state("SSWV_RUN8", "SSWV", 3, false, 3, Some(StateAction::Chase), "SSWV_RUN1");
+		state("SSWV_ATK1", "SSWV", 4, false, 10, Some(StateAction::FaceTarget), "SSWV_ATK2");
+		state("SSWV_ATK2", "SSWV", 5, false, 10, Some(StateAction::FaceTarget), "SSWV_ATK3");
+		state("SSWV_ATK3", "SSWV", 6, true, 4, Some(StateAction::CPosAttack), "SSWV_ATK4");
+		state("SSWV_ATK4", "SSWV", 5, false, 6, Some(StateAction::FaceTarget), "SSWV_ATK5");
+		state("SSWV_ATK5", "SSWV", 6, true, 4, Some(StateAction::CPosAttack), "SSWV_ATK6");
+		state("SSWV_ATK6", "SSWV", 5, false, 1, Some(StateAction::CPosRefire), "SSWV_ATK2");
+		state("SSWV_PAIN", "SSWV", 7, false, 3, None, "SSWV_PAIN2");
+		state("SSWV_PAIN2", "SSWV", 7, false, 3, Some(StateAction::Pain), "SSWV_RUN1");
+		state("SSWV_DIE1", "SSWV", 8, false, 5, None, "SSWV_DIE2");
+		state("SSWV_DIE2", "SSWV", 9, false, 5, Some(StateAction::Scream), "SSWV_DIE3");
+		state("SSWV_DIE3", "SSWV", 10, false, 5, Some(StateAction::Fall), "SSWV_DIE4");
+		state("SSWV_DIE4", "SSWV", 11, false, 5, None, "SSWV_DIE5");
+		state("SSWV_DIE5", "SSWV", 12, false, -1, None, "NULL");
+		state("SSWV_XDIE1", "SSWV", 13, false, 5, None, "SSWV_XDIE2");
+		state("SSWV_XDIE2", "SSWV", 14, false, 5, Some(StateAction::XScream), "SSWV_XDIE3");
+		state("SSWV_XDIE3", "SSWV", 15, false, 5, Some(StateAction::Fall), "SSWV_XDIE4");
+		state("SSWV_XDIE4", "SSWV", 16, false, 5, None, "SSWV_XDIE5");
+		state("SSWV_XDIE5", "SSWV", 17, false, 5, None, "SSWV_XDIE6");
+		state("SSWV_XDIE6", "SSWV", 18, false, 5, None, "SSWV_XDIE7");
+		state("SSWV_XDIE7", "SSWV", 19, false, 5, None, "SSWV_XDIE8");
+		state("SSWV_XDIE8", "SSWV", 20, false, 5, None, "SSWV_XDIE9");
+		state("SSWV_XDIE9", "SSWV", 21, false, -1, None, "NULL");
+		state("SSWV_RAISE1", "SSWV", 12, false, 5, None, "SSWV_RAISE2");
+		state("SSWV_RAISE2", "SSWV", 11, false, 5, None, "SSWV_RAISE3");
+		state("SSWV_RAISE3", "SSWV", 10, false, 5, None, "SSWV_RAISE4");
+		state("SSWV_RAISE4", "SSWV", 9, false, 5, None, "SSWV_RAISE5");
+		state("SSWV_RAISE5", "SSWV", 8, false, 5, None, "SSWV_RUN1");
+		state("KEENSTND", "KEEN", 0, false, -1, None, "KEENSTND");
+		state("COMMKEEN", "KEEN", 0, false, 6, None, "COMMKEEN2");
+		state("COMMKEEN2", "KEEN", 1, false, 6, None, "COMMKEEN3");
+		state("COMMKEEN3", "KEEN", 2, false, 6, Some(StateAction::Scream), "COMMKEEN4");
+		state("COMMKEEN4", "KEEN", 3, false, 6, None, "COMMKEEN5");
+		state("COMMKEEN5", "KEEN", 4, false, 6, None, "COMMKEEN6");
+		state("COMMKEEN6", "KEEN", 5, false, 6, None, "COMMKEEN7");
+		state("COMMKEEN7", "KEEN", 6, false, 6, None, "COMMKEEN8");
+		state("COMMKEEN8", "KEEN", 7, false, 6, None, "COMMKEEN9");
+		state("COMMKEEN9", "KEEN", 8, false, 6, None, "COMMKEEN10");
+		state("COMMKEEN10", "KEEN", 9, false, 6, None, "COMMKEEN11");
+		state("COMMKEEN11", "KEEN", 10, false, 6, Some(StateAction::KeenDie), "COMMKEEN12");
+		state("COMMKEEN12", "KEEN", 11, false, -1, None, "NULL");
+		state("KEENPAIN", "KEEN", 12, false, 4, None, "KEENPAIN2");
+		state("KEENPAIN2", "KEEN", 12, false, 8, Some(StateAction::Pain), "KEENSTND");
+		state("BRAIN", "BBRN", 0, false, -1, None, "NULL");
+		state("BRAIN_PAIN", "BBRN", 1, false, 36, Some(StateAction::BrainPain), "BRAIN");
+		state("BRAIN_DIE1", "BBRN", 0, false, 100, Some(StateAction::BrainScream), "BRAIN_DIE2");
+		state("BRAIN_DIE2", "BBRN", 0, false, 10, None, "BRAIN_DIE3");
+		state("BRAIN_DIE3", "BBRN", 0, false, 10, None, "BRAIN_DIE4");
+		state("BRAIN_DIE4", "BBRN", 0, false, -1, Some(StateAction::BrainDie), "NULL");
+		state("BRAINEYE", "SSWV", 0, false, 10, Some(StateAction::Look), "BRAINEYE");
+		state("BRAINEYESEE", "SSWV", 0, false, 181, Some(StateAction::BrainAwake), "BRAINEYE1");
+		state("BRAINEYE1", "SSWV", 0, false, 150, Some(StateAction::BrainSpit), "BRAINEYE1");
+		state("SPAWN1", "BOSF", 0, true, 3, Some(StateAction::SpawnSound), "SPAWN2");
+		state("SPAWN2", "BOSF", 1, true, 3, Some(StateAction::SpawnFly), "SPAWN3");
+		state("SPAWN3", "BOSF", 2, true, 3, Some(StateAction::SpawnFly), "SPAWN4");
+		state("SPAWN4", "BOSF", 3, true, 3, Some(StateAction::SpawnFly), "SPAWN1");
+		state("SPAWNFIRE1", "FIRE", 0, true, 4, Some(StateAction::Fire), "SPAWNFIRE2");
+		state("SPAWNFIRE2", "FIRE", 1, true, 4, Some(StateAction::Fire), "SPAWNFIRE3");
+		state("SPAWNFIRE3", "FIRE", 2, true, 4, Some(StateAction::Fire), "SPAWNFIRE4");
+		state("SPAWNFIRE4", "FIRE", 3, true, 4, Some(StateAction::Fire), "SPAWNFIRE5");
+		state("SPAWNFIRE5", "FIRE", 4, true, 4, Some(StateAction::Fire), "SPAWNFIRE6");
+		state("SPAWNFIRE6", "FIRE", 5, true, 4, Some(StateAction::Fire), "SPAWNFIRE7");
+		state("SPAWNFIRE7", "FIRE", 6, true, 4, Some(StateAction::Fire), "SPAWNFIRE8");
+		state("SPAWNFIRE8", "FIRE", 7, true, 4, Some(StateAction::Fire), "NULL");
+		state("BRAINEXPLODE1", "MISL", 1, true, 10, None, "BRAINEXPLODE2");
+		state("BRAINEXPLODE2", "MISL", 2, true, 10, None, "BRAINEXPLODE3");
+		state("BRAINEXPLODE3", "MISL", 3, true, 10, Some(StateAction::BrainExplode), "NULL");
+		state("ARM1", "ARM1", 0, false, 6, None, "ARM1A");
+		state("ARM1A", "ARM1", 1, true, 7, None, "ARM1");
+		state("ARM2", "ARM2", 0, false, 6, None, "ARM2A");
+		state("ARM2A", "ARM2", 1, true, 6, None, "ARM2");
+		state("BAR1", "BAR1", 0, false, 6, None, "BAR2");
+		state("BAR2", "BAR1", 1, false, 6, None, "BAR1");
+		state("BEXP", "BEXP", 0, true, 5, None, "BEXP2");
+		state("BEXP2", "BEXP", 1, true, 5, Some(StateAction::Scream), "BEXP3");
+		state("BEXP3", "BEXP", 2, true, 5, None, "BEXP4");
+		state("BEXP4", "BEXP", 3, true, 10, Some(StateAction::Explode), "BEXP5");
+		state("BEXP5", "BEXP", 4, true, 10, None, "NULL");
+		state("BBAR1", "FCAN", 0, true, 4, None, "BBAR2");
+		state("BBAR2", "FCAN", 1, true, 4, None, "BBAR3");
+		state("BBAR3", "FCAN", 2, true, 4, None, "BBAR1");
+		state("BON1", "BON1", 0, false, 6, None, "BON1A");
+		state("BON1A", "BON1", 1, false, 6, None, "BON1B");
+		state("BON1B", "BON1", 2, false, 6, None, "BON1C");
+		state("BON1C", "BON1", 3, false, 6, None, "BON1D");
+		state("BON1D", "BON1", 2, false, 6, None, "BON1E");
+		state("BON1E", "BON1", 1, false, 6, None, "BON1");
+		state("BON2", "BON2", 0, false, 6, None, "BON2A");
+		state("BON2A", "BON2", 1, false, 6, None, "BON2B");
+		state("BON2B", "BON2", 2, false, 6, None, "BON2C");
+		state("BON2C", "BON2", 3, false, 6, None, "BON2D");
+		state("BON2D", "BON2", 2, false, 6, None, "BON2E");
+		state("BON2E", "BON2", 1, false, 6, None, "BON2");
+		state("BKEY", "BKEY", 0, false, 10, None, "BKEY2");
+		state("BKEY2", "BKEY", 1, true, 10, None, "BKEY");
+		state("RKEY", "RKEY", 0, false, 10, None, "RKEY2");
+		state("RKEY2", "RKEY", 1, true, 10, None, "RKEY");
+		state("YKEY", "YKEY", 0, false, 10, None, "YKEY2");
+		state("YKEY2", "YKEY", 1, true, 10, None, "YKEY");
+		state("BSKULL", "BSKU", 0, false, 10, None, "BSKULL2");
+		state("BSKULL2", "BSKU", 1, true, 10, None, "BSKULL");
+		state("RSKULL", "RSKU", 0, false, 10, None, "RSKULL2");
+		state("RSKULL2", "RSKU", 1, true, 10, None, "RSKULL");
+		state("YSKULL", "YSKU", 0, false, 10, None, "YSKULL2");
+		state("YSKULL2", "YSKU", 1, true, 10, None, "YSKULL");
+		state("STIM", "STIM", 0, false, -1, None, "NULL");
+		state("MEDI", "MEDI", 0, false, -1, None, "NULL");
+		state("SOUL", "SOUL", 0, true, 6, None, "SOUL2");
+		state("SOUL2", "SOUL", 1, true, 6, None, "SOUL3");
+		state("SOUL3", "SOUL", 2, true, 6, None, "SOUL4");
+		state("SOUL4", "SOUL", 3, true, 6, None, "SOUL5");
+		state("SOUL5", "SOUL", 2, true, 6, None, "SOUL6");
+		state("SOUL6", "SOUL", 1, true, 6, None, "SOUL");
+		state("PINV", "PINV", 0, true, 6, None, "PINV2");
+		state("PINV2", "PINV", 1, true, 6, None, "PINV3");
+		state("PINV3", "PINV", 2, true, 6, None, "PINV4");
+		state("PINV4", "PINV", 3, true, 6, None, "PINV");
+		state("PSTR", "PSTR", 0, true, -1, None, "NULL");
+		state("PINS", "PINS", 0, true, 6, None, "PINS2");
+		state("PINS2", "PINS", 1, true, 6, None, "PINS3");
+		state("PINS3", "PINS", 2, true, 6, None, "PINS4");
+		state("PINS4", "PINS", 3, true, 6, None, "PINS");
+		state("MEGA", "MEGA", 0, true, 6, None, "MEGA2");
+		state("MEGA2", "MEGA", 1, true, 6, None, "MEGA3");
+		state("MEGA3", "MEGA", 2, true, 6, None, "MEGA4");
+		state("MEGA4", "MEGA", 3, true, 6, None, "MEGA");
+		state("SUIT", "SUIT", 0, true, -1, None, "NULL");
+		state("PMAP", "PMAP", 0, true, 6, None, "PMAP2");
+		state("PMAP2", "PMAP", 1, true, 6, None, "PMAP3");
+		state("PMAP3", "PMAP", 2, true, 6, None, "PMAP4");
+		state("PMAP4", "PMAP", 3, true, 6, None, "PMAP5");
+		state("PMAP5", "PMAP", 2, true, 6, None, "PMAP6");
+		state("PMAP6", "PMAP", 1, true, 6, None, "PMAP");
+		state("PVIS", "PVIS", 0, true, 6, None, "PVIS2");
+		state("PVIS2", "PVIS", 1, false, 6, None, "PVIS");
+		state("CLIP", "CLIP", 0, false, -1, None, "NULL");
+		state("AMMO", "AMMO", 0, false, -1, None, "NULL");
+		state("ROCK", "ROCK", 0, false, -1, None, "NULL");
+		state("BROK", "BROK", 0, false, -1, None, "NULL");
+		state("CELL", "CELL", 0, false, -1, None, "NULL");
+		state("CELP", "CELP", 0, false, -1, None, "NULL");
+		state("SHEL", "SHEL", 0, false, -1, None, "NULL");
+		state("SBOX", "SBOX", 0, false, -1, None, "NULL");
+		state("BPAK", "BPAK", 0, false, -1, None, "NULL");
+		state("BFUG", "BFUG", 0, false, -1, None, "NULL");
+		state("MGUN", "MGUN", 0, false, -1, None, "NULL");
+		state("CSAW", "CSAW", 0, false, -1, None, "NULL");
+		state("LAUN", "LAUN", 0, false, -1, None, "NULL");
+		state("PLAS", "PLAS", 0, false, -1, None, "NULL");
+		state("SHOT", "SHOT", 0, false, -1, None, "NULL");
+		state("SHOT2", "SGN2", 0, false, -1, None, "NULL");
+		state("COLU", "COLU", 0, true, -1, None, "NULL");
+		state("STALAG", "SMT2", 0, false, -1, None, "NULL");
+		state("BLOODYTWITCH", "GOR1", 0, false, 10, None, "BLOODYTWITCH2");
+		state("BLOODYTWITCH2", "GOR1", 1, false, 15, None, "BLOODYTWITCH3");
+		state("BLOODYTWITCH3", "GOR1", 2, false, 8, None, "BLOODYTWITCH4");
+		state("BLOODYTWITCH4", "GOR1", 1, false, 6, None, "BLOODYTWITCH");
+		state("DEADTORSO", "PLAY", 13, false, -1, None, "NULL");
+		state("DEADBOTTOM", "PLAY", 18, false, -1, None, "NULL");
+		state("HEADSONSTICK", "POL2", 0, false, -1, None, "NULL");
+		state("GIBS", "POL5", 0, false, -1, None, "NULL");
+		state("HEADONASTICK", "POL4", 0, false, -1, None, "NULL");
+		state("HEADCANDLES", "POL3", 0, true, 6, None, "HEADCANDLES2");
+		state("HEADCANDLES2", "POL3", 1, true, 6, None, "HEADCANDLES");
+		state("DEADSTICK", "POL1", 0, false, -1, None, "NULL");
+		state("LIVESTICK", "POL6", 0, false, 6, None, "LIVESTICK2");
+		state("LIVESTICK2", "POL6", 1, false, 8, None, "LIVESTICK");
+		state("MEAT2", "GOR2", 0, false, -1, None, "NULL");
+		state("MEAT3", "GOR3", 0, false, -1, None, "NULL");
+		state("MEAT4", "GOR4", 0, false, -1, None, "NULL");
+		state("MEAT5", "GOR5", 0, false, -1, None, "NULL");
+		state("STALAGTITE", "SMIT", 0, false, -1, None, "NULL");
+		state("TALLGRNCOL", "COL1", 0, false, -1, None, "NULL");
+		state("SHRTGRNCOL", "COL2", 0, false, -1, None, "NULL");
+		state("TALLREDCOL", "COL3", 0, false, -1, None, "NULL");
+		state("SHRTREDCOL", "COL4", 0, false, -1, None, "NULL");
+		state("CANDLESTIK", "CAND", 0, true, -1, None, "NULL");
+		state("CANDELABRA", "CBRA", 0, true, -1, None, "NULL");
+		state("SKULLCOL", "COL6", 0, false, -1, None, "NULL");
+		state("TORCHTREE", "TRE1", 0, false, -1, None, "NULL");
+		state("BIGTREE", "TRE2", 0, false, -1, None, "NULL");
+		state("TECHPILLAR", "ELEC", 0, false, -1, None, "NULL");
+		state("EVILEYE", "CEYE", 0, true, 6, None, "EVILEYE2");
+		state("EVILEYE2", "CEYE", 1, true, 6, None, "EVILEYE3");
+		state("EVILEYE3", "CEYE", 2, true, 6, None, "EVILEYE4");
+		state("EVILEYE4", "CEYE", 1, true, 6, None, "EVILEYE");
+		state("FLOATSKULL", "FSKU", 0, true, 6, None, "FLOATSKULL2");
+		state("FLOATSKULL2", "FSKU", 1, true, 6, None, "FLOATSKULL3");
+		state("FLOATSKULL3", "FSKU", 2, true, 6, None, "FLOATSKULL");
+		state("HEARTCOL", "COL5", 0, false, 14, None, "HEARTCOL2");
+		state("HEARTCOL2", "COL5", 1, false, 14, None, "HEARTCOL");
+		state("BLUETORCH", "TBLU", 0, true, 4, None, "BLUETORCH2");
+		state("BLUETORCH2", "TBLU", 1, true, 4, None, "BLUETORCH3");
+		state("BLUETORCH3", "TBLU", 2, true, 4, None, "BLUETORCH4");
+		state("BLUETORCH4", "TBLU", 3, true, 4, None, "BLUETORCH");
+		state("GREENTORCH", "TGRN", 0, true, 4, None, "GREENTORCH2");
+		state("GREENTORCH2", "TGRN", 1, true, 4, None, "GREENTORCH3");
+		state("GREENTORCH3", "TGRN", 2, true, 4, None, "GREENTORCH4");
+		state("GREENTORCH4", "TGRN", 3, true, 4, None, "GREENTORCH");
+		state("REDTORCH", "TRED", 0, true, 4, None, "REDTORCH2");
+		state("REDTORCH2", "TRED", 1, true, 4, None, "REDTORCH3");
+		state("REDTORCH3", "TRED", 2, true, 4, None, "REDTORCH4");
+		state("REDTORCH4", "TRED", 3, true, 4, None, "REDTORCH");
+		state("BTORCHSHRT", "SMBT", 0, true, 4, None, "BTORCHSHRT2");
+		state("BTORCHSHRT2", "SMBT", 1, true, 4, None, "BTORCHSHRT3");
+		state("BTORCHSHRT3", "SMBT", 2, true, 4, None, "BTORCHSHRT4");
+		state("BTORCHSHRT4", "SMBT", 3, true, 4, None, "BTORCHSHRT");
+		state("GTORCHSHRT", "SMGT", 0, true, 4, None, "GTORCHSHRT2");
+		state("GTORCHSHRT2", "SMGT", 1, true, 4, None, "GTORCHSHRT3");
+		state("GTORCHSHRT3", "SMGT", 2, true, 4, None, "GTORCHSHRT4");
+		state("GTORCHSHRT4", "SMGT", 3, true, 4, None, "GTORCHSHRT");
+		state("RTORCHSHRT", "SMRT", 0, true, 4, None, "RTORCHSHRT2");
+		state("RTORCHSHRT2", "SMRT", 1, true, 4, None, "RTORCHSHRT3");
+		state("RTORCHSHRT3", "SMRT", 2, true, 4, None, "RTORCHSHRT4");
+		state("RTORCHSHRT4", "SMRT", 3, true, 4, None, "RTORCHSHRT");
+		state("HANGNOGUTS", "HDB1", 0, false, -1, None, "NULL");
+		state("HANGBNOBRAIN", "HDB2", 0, false, -1, None, "NULL");
+		state("HANGTLOOKDN", "HDB3", 0, false, -1, None, "NULL");
+		state("HANGTSKULL", "HDB4", 0, false, -1, None, "NULL");
+		state("HANGTLOOKUP", "HDB5", 0, false, -1, None, "NULL");
+		state("HANGTNOBRAIN", "HDB6", 0, false, -1, None, "NULL");
+		state("COLONGIBS", "POB1", 0, false, -1, None, "NULL");
+		state("SMALLPOOL", "POB2", 0, false, -1, None, "NULL");
+		state("BRAINSTEM", "BRS1", 0, false, -1, None, "NULL");
+		state("TECHLAMP", "TLMP", 0, true, 4, None, "TECHLAMP2");
+		state("TECHLAMP2", "TLMP", 1, true, 4, None, "TECHLAMP3");
+		state("TECHLAMP3", "TLMP", 2, true, 4, None, "TECHLAMP4");
+		state("TECHLAMP4", "TLMP", 3, true, 4, None, "TECHLAMP");
+		state("TECH2LAMP", "TLP2", 0, true, 4, None, "TECH2LAMP2");
+		state("TECH2LAMP2", "TLP2", 1, true, 4, None, "TECH2LAMP3");
+		state("TECH2LAMP3", "TLP2", 2, true, 4, None, "TECH2LAMP4");
+		state("TECH2LAMP4", "TLP2", 3, true, 4, None, "TECH2LAMP");
+
+		for (state, next) in states.iter_mut().zip(next_names) {
+			state.next = names[next];
+		}
+
+		States { states, names }
+	}
+}
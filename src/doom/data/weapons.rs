@@ -0,0 +1,149 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		data::States,
+		wad::WadLoader,
+		weapon::{AmmoInfo, AmmoType, WeaponInfo},
+	},
+};
+use fnv::FnvHashMap;
+use legion::prelude::{Read, ResourceSet, Resources, Write};
+
+pub struct WeaponTypes {
+	// Indexed by WeaponType
+	pub weapons: Vec<WeaponInfo>,
+	// Indexed by AmmoType
+	pub ammo: Vec<AmmoInfo>,
+	pub sounds: FnvHashMap<&'static str, AssetHandle<Sound>>,
+}
+
+impl WeaponTypes {
+	#[rustfmt::skip]
+	pub fn new(resources: &mut Resources) -> WeaponTypes {
+		let (mut asset_storage, mut loader, states) = <(
+			Write<AssetStorage>,
+			Write<WadLoader>,
+			Read<States>,
+		)>::fetch_mut(resources);
+
+		let weapons = vec![
+			// Fist
+			WeaponInfo {
+				ammo_type: None,
+				ammo_per_shot: 0,
+				up_state: states.names["PUNCHUP"],
+				down_state: states.names["PUNCHDOWN"],
+				ready_state: states.names["PUNCH"],
+				attack_state: states.names["PUNCH1"],
+				flash_state: None,
+			},
+			// Pistol
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Clip),
+				ammo_per_shot: 1,
+				up_state: states.names["PISTOLUP"],
+				down_state: states.names["PISTOLDOWN"],
+				ready_state: states.names["PISTOL"],
+				attack_state: states.names["PISTOL1"],
+				flash_state: Some(states.names["PISTOLFLASH"]),
+			},
+			// Shotgun
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Shell),
+				ammo_per_shot: 1,
+				up_state: states.names["SGUNUP"],
+				down_state: states.names["SGUNDOWN"],
+				ready_state: states.names["SGUN"],
+				attack_state: states.names["SGUN1"],
+				flash_state: Some(states.names["SGUNFLASH1"]),
+			},
+			// Chaingun
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Clip),
+				ammo_per_shot: 1,
+				up_state: states.names["CHAINUP"],
+				down_state: states.names["CHAINDOWN"],
+				ready_state: states.names["CHAIN"],
+				attack_state: states.names["CHAIN1"],
+				flash_state: Some(states.names["CHAINFLASH1"]),
+			},
+			// Rocket launcher
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Missile),
+				ammo_per_shot: 1,
+				up_state: states.names["MISSILEUP"],
+				down_state: states.names["MISSILEDOWN"],
+				ready_state: states.names["MISSILE"],
+				attack_state: states.names["MISSILE1"],
+				flash_state: Some(states.names["MISSILEFLASH1"]),
+			},
+			// Plasma rifle
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Cell),
+				ammo_per_shot: 1,
+				up_state: states.names["PLASMAUP"],
+				down_state: states.names["PLASMADOWN"],
+				ready_state: states.names["PLASMA"],
+				attack_state: states.names["PLASMA1"],
+				flash_state: Some(states.names["PLASMAFLASH1"]),
+			},
+			// BFG 9000
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Cell),
+				ammo_per_shot: 40,
+				up_state: states.names["BFGUP"],
+				down_state: states.names["BFGDOWN"],
+				ready_state: states.names["BFG"],
+				attack_state: states.names["BFG1"],
+				flash_state: Some(states.names["BFGFLASH1"]),
+			},
+			// Chainsaw
+			WeaponInfo {
+				ammo_type: None,
+				ammo_per_shot: 0,
+				up_state: states.names["SAWUP"],
+				down_state: states.names["SAWDOWN"],
+				ready_state: states.names["SAW"],
+				attack_state: states.names["SAW1"],
+				flash_state: None,
+			},
+			// Super shotgun
+			WeaponInfo {
+				ammo_type: Some(AmmoType::Shell),
+				ammo_per_shot: 2,
+				up_state: states.names["DSGUNUP"],
+				down_state: states.names["DSGUNDOWN"],
+				ready_state: states.names["DSGUN"],
+				attack_state: states.names["DSGUN1"],
+				flash_state: Some(states.names["DSGUNFLASH1"]),
+			},
+		];
+
+		let ammo = vec![
+			// Clip
+			AmmoInfo { max: 200, clip: 10 },
+			// Shell
+			AmmoInfo { max: 50, clip: 4 },
+			// Cell
+			AmmoInfo { max: 300, clip: 20 },
+			// Missile
+			AmmoInfo { max: 50, clip: 1 },
+		];
+
+		let mut sounds = FnvHashMap::default();
+
+		for &name in [
+			"DSPISTOL", "DSSHOTGN", "DSDSHTGN", "DSDBOPN", "DSDBLOAD", "DSDBCLS",
+			"DSSAWUP", "DSSAWIDL", "DSSAWFUL", "DSSAWHIT", "DSPUNCH", "DSBFG",
+		].iter() {
+			sounds.insert(name, asset_storage.load(name, &mut *loader));
+		}
+
+		WeaponTypes {
+			weapons,
+			ammo,
+			sounds,
+		}
+	}
+}
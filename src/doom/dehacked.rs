@@ -0,0 +1,1117 @@
+use crate::{
+	assets::AssetStorage,
+	doom::{
+		client::UseAction,
+		components::{Health, SpawnOnCeiling},
+		data::{LinedefTypes, MobjTypes, States, WeaponTypes, FRAME_RATE, FRAME_TIME},
+		inventory::ItemPickup,
+		monster::Monster,
+		physics::{BoxCollider, SolidMask},
+		projectile::Projectile,
+		render::sprite::{RenderStyle, SpriteRender},
+		sprite::Sprite,
+		state::{StateAction, StateMachine},
+		wad::WadLoader,
+		weapon::AmmoType,
+	},
+};
+use anyhow::{anyhow, bail, Context};
+use bitflags::bitflags;
+use fnv::FnvHashMap;
+use legion::prelude::{Read, ResourceSet, Resources, Write};
+use std::time::Duration;
+
+// A DeHackEd patch, with BEX extensions. Several patches can be parsed into the same
+// value, later ones overriding earlier ones.
+#[derive(Clone, Debug, Default)]
+pub struct Dehacked {
+	pub things: FnvHashMap<usize, ThingPatch>,
+	pub frames: FnvHashMap<usize, FramePatch>,
+	pub code_pointers: FnvHashMap<usize, CodePointer>,
+	pub ammo: FnvHashMap<usize, AmmoPatch>,
+	pub weapons: FnvHashMap<usize, WeaponPatch>,
+	pub texts: Vec<(String, String)>,
+	pub strings: FnvHashMap<String, String>,
+	pub par_times: FnvHashMap<String, Duration>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ThingPatch {
+	pub doomednum: Option<i32>,
+	pub hit_points: Option<i32>,
+	pub pain_chance: Option<i32>,
+	pub radius: Option<f32>,
+	pub height: Option<f32>,
+	pub speed: Option<i32>,
+	pub damage: Option<i32>,
+	pub reaction_time: Option<i32>,
+	pub flags: Option<MobjFlags>,
+
+	pub spawn: Option<usize>,
+	pub see: Option<usize>,
+	pub pain: Option<usize>,
+	pub melee: Option<usize>,
+	pub missile: Option<usize>,
+	pub death: Option<usize>,
+	pub xdeath: Option<usize>,
+	pub raise: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FramePatch {
+	pub sprite: Option<usize>,
+	pub subnumber: Option<usize>,
+	pub duration: Option<i32>,
+	pub next: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AmmoPatch {
+	pub max: Option<i32>,
+	pub clip: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WeaponPatch {
+	pub ammo_type: Option<usize>,
+	pub ammo_per_shot: Option<i32>,
+	pub up: Option<usize>,
+	pub down: Option<usize>,
+	pub ready: Option<usize>,
+	pub attack: Option<usize>,
+	pub flash: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CodePointer {
+	// Use the action that the given state originally had
+	Frame(usize),
+	Action(Option<StateAction>),
+}
+
+bitflags! {
+	pub struct MobjFlags: u32 {
+		const SPECIAL = 0x1;
+		const SOLID = 0x2;
+		const SHOOTABLE = 0x4;
+		const NOSECTOR = 0x8;
+		const NOBLOCKMAP = 0x10;
+		const AMBUSH = 0x20;
+		const JUSTHIT = 0x40;
+		const JUSTATTACKED = 0x80;
+		const SPAWNCEILING = 0x100;
+		const NOGRAVITY = 0x200;
+		const DROPOFF = 0x400;
+		const PICKUP = 0x800;
+		const NOCLIP = 0x1000;
+		const SLIDE = 0x2000;
+		const FLOAT = 0x4000;
+		const TELEPORT = 0x8000;
+		const MISSILE = 0x10000;
+		const DROPPED = 0x20000;
+		const SHADOW = 0x40000;
+		const NOBLOOD = 0x80000;
+		const CORPSE = 0x100000;
+		const INFLOAT = 0x200000;
+		const COUNTKILL = 0x400000;
+		const COUNTITEM = 0x800000;
+		const SKULLFLY = 0x1000000;
+		const NOTDMATCH = 0x2000000;
+		const TRANSLATION = 0xC000000;
+	}
+}
+
+enum Block {
+	None,
+	Thing(usize),
+	Frame(usize),
+	Pointer(usize),
+	Ammo(usize),
+	Weapon(usize),
+	CodePointers,
+	Strings,
+	Pars,
+	Unsupported,
+}
+
+impl Dehacked {
+	pub fn parse(&mut self, text: &str) -> anyhow::Result<()> {
+		let text = text.replace("\r\n", "\n");
+		let mut rest = text.as_str();
+		let mut block = Block::None;
+
+		while let Some(line) = next_line(&mut rest) {
+			let line = line.trim();
+
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			// BEX sections
+			if line.starts_with('[') {
+				block = match line.to_ascii_uppercase().as_str() {
+					"[CODEPTR]" => Block::CodePointers,
+					"[STRINGS]" => Block::Strings,
+					"[PARS]" => Block::Pars,
+					_ => {
+						log::warn!("DeHackEd section {} is not supported", line);
+						Block::Unsupported
+					}
+				};
+				continue;
+			}
+
+			if let Some((key, value)) = split_key_value(line) {
+				match block {
+					Block::Thing(number) => {
+						let thing = self.things.entry(number).or_default();
+
+						match key.as_str() {
+							"id #" => thing.doomednum = Some(parse_int(value)?),
+							"hit points" => thing.hit_points = Some(parse_int(value)?),
+							"pain chance" => thing.pain_chance = Some(parse_int(value)?),
+							"width" => thing.radius = Some(parse_int(value)? as f32 / 65536.0),
+							"height" => thing.height = Some(parse_int(value)? as f32 / 65536.0),
+							"speed" => thing.speed = Some(parse_int(value)?),
+							"missile damage" => thing.damage = Some(parse_int(value)?),
+							"reaction time" => thing.reaction_time = Some(parse_int(value)?),
+							"bits" => thing.flags = Some(parse_flags(value)?),
+							"initial frame" => thing.spawn = Some(parse_index(value)?),
+							"first moving frame" => thing.see = Some(parse_index(value)?),
+							"injury frame" => thing.pain = Some(parse_index(value)?),
+							"close attack frame" => thing.melee = Some(parse_index(value)?),
+							"far attack frame" => thing.missile = Some(parse_index(value)?),
+							"death frame" => thing.death = Some(parse_index(value)?),
+							"exploding frame" => thing.xdeath = Some(parse_index(value)?),
+							"respawn frame" => thing.raise = Some(parse_index(value)?),
+							_ => log::warn!("DeHackEd thing property \"{}\" is not supported", key),
+						}
+					}
+					Block::Frame(number) => {
+						let frame = self.frames.entry(number).or_default();
+
+						match key.as_str() {
+							"sprite number" => frame.sprite = Some(parse_index(value)?),
+							"sprite subnumber" => frame.subnumber = Some(parse_index(value)?),
+							"duration" => frame.duration = Some(parse_int(value)?),
+							"next frame" => frame.next = Some(parse_index(value)?),
+							_ => log::warn!("DeHackEd frame property \"{}\" is not supported", key),
+						}
+					}
+					Block::Pointer(number) => {
+						if key == "codep frame" {
+							self.code_pointers
+								.insert(number, CodePointer::Frame(parse_index(value)?));
+						}
+					}
+					Block::Ammo(number) => {
+						let ammo = self.ammo.entry(number).or_default();
+
+						match key.as_str() {
+							"max ammo" => ammo.max = Some(parse_int(value)?),
+							"per ammo" => ammo.clip = Some(parse_int(value)?),
+							_ => log::warn!("DeHackEd ammo property \"{}\" is not supported", key),
+						}
+					}
+					Block::Weapon(number) => {
+						let weapon = self.weapons.entry(number).or_default();
+
+						// "Deselect" and "select" are the wrong way round in DeHackEd itself
+						match key.as_str() {
+							"ammo type" => weapon.ammo_type = Some(parse_index(value)?),
+							"ammo per shot" => weapon.ammo_per_shot = Some(parse_int(value)?),
+							"deselect frame" => weapon.up = Some(parse_index(value)?),
+							"select frame" => weapon.down = Some(parse_index(value)?),
+							"bobbing frame" => weapon.ready = Some(parse_index(value)?),
+							"shooting frame" => weapon.attack = Some(parse_index(value)?),
+							"firing frame" => weapon.flash = Some(parse_index(value)?),
+							_ => {
+								log::warn!("DeHackEd weapon property \"{}\" is not supported", key)
+							}
+						}
+					}
+					Block::CodePointers => {
+						if !key.starts_with("frame ") {
+							bail!("Invalid code pointer \"{}\"", line);
+						}
+
+						let number = parse_index(&key[6..])?;
+						self.code_pointers
+							.insert(number, CodePointer::Action(parse_action(value)?));
+					}
+					Block::Strings => {
+						// Values can be continued on the next line with a backslash
+						let mut value = value.to_owned();
+
+						while value.ends_with('\\') {
+							value.pop();
+
+							match next_line(&mut rest) {
+								Some(line) => value.push_str(line.trim()),
+								None => break,
+							}
+						}
+
+						self.strings
+							.insert(key.to_ascii_uppercase(), value.replace("\\n", "\n"));
+					}
+					Block::None | Block::Pars | Block::Unsupported => (),
+				}
+
+				continue;
+			}
+
+			let words: Vec<&str> = line.split_whitespace().collect();
+
+			if let Block::Pars = block {
+				if words[0].eq_ignore_ascii_case("par") {
+					self.parse_par(&words[1..])
+						.with_context(|| format!("Invalid par time \"{}\"", line))?;
+					continue;
+				}
+			}
+
+			let number = words.get(1).and_then(|word| word.parse::<usize>().ok());
+
+			block = match (words[0].to_ascii_lowercase().as_str(), number) {
+				("thing", Some(number)) => Block::Thing(number),
+				("frame", Some(number)) => Block::Frame(number),
+				("pointer", Some(_)) => {
+					// Pointer 1 (Frame 2)
+					let frame = line
+						.split('(')
+						.nth(1)
+						.and_then(|rest| rest.trim_end_matches(')').split_whitespace().nth(1))
+						.ok_or_else(|| anyhow!("Invalid pointer block \"{}\"", line))
+						.and_then(parse_index)?;
+					Block::Pointer(frame)
+				}
+				("text", Some(old_len)) => {
+					// The texts follow directly, and may span multiple lines
+					let new_len = words
+						.get(2)
+						.and_then(|word| word.parse::<usize>().ok())
+						.ok_or_else(|| anyhow!("Invalid text block \"{}\"", line))?;
+					let old_text = take_chars(&mut rest, old_len)?.to_owned();
+					let new_text = take_chars(&mut rest, new_len)?.to_owned();
+					self.texts.push((old_text, new_text));
+					Block::None
+				}
+				("ammo", Some(number)) => Block::Ammo(number),
+				("weapon", Some(number)) => Block::Weapon(number),
+				("sound", Some(_))
+				| ("sprite", Some(_))
+				| ("misc", Some(_))
+				| ("cheat", Some(_)) => {
+					log::warn!("DeHackEd block \"{}\" is not supported", line);
+					Block::Unsupported
+				}
+				_ => {
+					log::warn!("Unrecognised DeHackEd line \"{}\"", line);
+					block
+				}
+			};
+		}
+
+		Ok(())
+	}
+
+	fn parse_par(&mut self, words: &[&str]) -> anyhow::Result<()> {
+		let numbers = words
+			.iter()
+			.map(|word| Ok(word.parse::<u64>()?))
+			.collect::<anyhow::Result<Vec<u64>>>()?;
+
+		let (map, seconds) = match *numbers.as_slice() {
+			[episode, map, seconds] => (format!("E{}M{}", episode, map), seconds),
+			[map, seconds] => (format!("MAP{:02}", map), seconds),
+			_ => bail!("Wrong number of values"),
+		};
+
+		self.par_times.insert(map, Duration::from_secs(seconds));
+		Ok(())
+	}
+}
+
+// Applies the Dehacked resource to the entity types, weapons and states of the current map
+pub fn apply_dehacked(resources: &mut Resources) {
+	let (
+		dehacked,
+		linedef_types,
+		mut asset_storage,
+		mut loader,
+		mut mobj_types,
+		mut states,
+		mut weapon_types,
+	) = <(
+		Read<Dehacked>,
+		Read<LinedefTypes>,
+		Write<AssetStorage>,
+		Write<WadLoader>,
+		Write<MobjTypes>,
+		Write<States>,
+		Write<WeaponTypes>,
+	)>::fetch_mut(resources);
+
+	let state_count = states.states.len();
+	let mut sprite_names: Vec<String> = SPRITE_NAMES.iter().map(|&name| name.to_owned()).collect();
+	let mut messages: FnvHashMap<String, String> = FnvHashMap::default();
+
+	// Texts replace either sprite names or messages
+	for (old_text, new_text) in dehacked.texts.iter() {
+		let index = sprite_names
+			.iter()
+			.position(|name| name.eq_ignore_ascii_case(old_text));
+
+		match index {
+			Some(index) if new_text.len() == 4 => {
+				let new_name = new_text.to_ascii_uppercase();
+
+				if let Some(old_handle) = asset_storage.handle_for::<Sprite>(&sprite_names[index]) {
+					let new_handle = asset_storage.load::<Sprite>(&new_name, &mut *loader);
+
+					for state in states.states.iter_mut() {
+						if state.sprite == old_handle {
+							state.sprite = new_handle.clone();
+						}
+					}
+				}
+
+				sprite_names[index] = new_name;
+			}
+			_ => {
+				messages.insert(old_text.clone(), new_text.clone());
+			}
+		}
+	}
+
+	for (mnemonic, new_text) in dehacked.strings.iter() {
+		match STRINGS.iter().find(|(name, _)| name == mnemonic) {
+			Some((_, old_text)) => {
+				messages.insert((*old_text).to_owned(), new_text.clone());
+			}
+			None => log::warn!("DeHackEd string {} is not supported", mnemonic),
+		}
+	}
+
+	// Frames
+	for (&index, frame) in dehacked.frames.iter() {
+		let state = match states.states.get_mut(index) {
+			Some(state) => state,
+			None => {
+				log::warn!("DeHackEd frame {} does not exist", index);
+				continue;
+			}
+		};
+
+		if let Some(sprite) = frame.sprite {
+			match sprite_names.get(sprite) {
+				Some(name) => state.sprite = asset_storage.load(name, &mut *loader),
+				None => log::warn!("DeHackEd sprite {} does not exist", sprite),
+			}
+		}
+
+		if let Some(subnumber) = frame.subnumber {
+			state.frame = subnumber & 0x7FFF;
+			state.full_bright = subnumber & 0x8000 != 0;
+		}
+
+		if let Some(duration) = frame.duration {
+			state.time = if duration < 0 {
+				None
+			} else {
+				Some(FRAME_TIME * duration as u32)
+			};
+		}
+
+		if let Some(next) = frame.next {
+			if next < state_count {
+				state.next = next;
+			} else {
+				log::warn!("DeHackEd frame {} does not exist", next);
+			}
+		}
+	}
+
+	// Code pointers
+	let original_actions: Vec<Option<StateAction>> =
+		states.states.iter().map(|state| state.action).collect();
+
+	for (&index, code_pointer) in dehacked.code_pointers.iter() {
+		let action = match *code_pointer {
+			CodePointer::Frame(frame) => match original_actions.get(frame) {
+				Some(&action) => action,
+				None => {
+					log::warn!("DeHackEd frame {} does not exist", frame);
+					continue;
+				}
+			},
+			CodePointer::Action(action) => action,
+		};
+
+		match states.states.get_mut(index) {
+			Some(state) => state.action = action,
+			None => log::warn!("DeHackEd frame {} does not exist", index),
+		}
+	}
+
+	// Things
+	let state_index = |index: usize| -> Option<usize> {
+		if index != 0 && index < state_count {
+			Some(index)
+		} else {
+			None
+		}
+	};
+
+	for (&number, thing) in dehacked.things.iter() {
+		let handle = match number
+			.checked_sub(1)
+			.and_then(|index| MOBJ_NAMES.get(index))
+			.and_then(|name| mobj_types.names.get(*name))
+		{
+			Some(handle) => handle.clone(),
+			None => {
+				log::warn!("DeHackEd thing {} does not exist", number);
+				continue;
+			}
+		};
+
+		if let Some(doomednum) = thing.doomednum {
+			mobj_types.doomednums.retain(|_, other| *other != handle);
+
+			if doomednum >= 0 {
+				mobj_types
+					.doomednums
+					.insert(doomednum as u16, handle.clone());
+			}
+		}
+
+		let template = asset_storage.get_mut(&handle).unwrap();
+
+		if let Some(health) = template.get_component_mut::<Health>() {
+			if let Some(hit_points) = thing.hit_points {
+				health.current = hit_points;
+				health.max = hit_points;
+			}
+
+			if let Some(pain_chance) = thing.pain_chance {
+				health.pain_chance = pain_chance.max(0) as u16;
+			}
+
+			if let Some(flags) = thing.flags {
+				health.no_blood = flags.contains(MobjFlags::NOBLOOD);
+			}
+		}
+
+		if let Some(box_collider) = template.get_component_mut::<BoxCollider>() {
+			if let Some(radius) = thing.radius {
+				box_collider.radius = radius;
+			}
+
+			if let Some(height) = thing.height {
+				box_collider.height = height;
+			}
+
+			if let Some(flags) = thing.flags {
+				box_collider.solid_mask = if flags.contains(MobjFlags::SOLID) {
+					SolidMask::all()
+				} else {
+					SolidMask::empty()
+				};
+			}
+		}
+
+		if let Some(monster) = template.get_component_mut::<Monster>() {
+			if let Some(speed) = thing.speed {
+				monster.speed = speed as f32;
+			}
+
+			if let Some(reaction_time) = thing.reaction_time {
+				monster.reaction_time = reaction_time.max(0) as u32;
+			}
+		}
+
+		if let Some(projectile) = template.get_component_mut::<Projectile>() {
+			// Projectile speeds are given in fixed point
+			if let Some(speed) = thing.speed {
+				projectile.speed = speed as f32 / 65536.0 * FRAME_RATE;
+			}
+
+			if let Some(damage) = thing.damage {
+				projectile.damage = damage;
+			}
+		}
+
+		if let Some(flags) = thing.flags {
+			if !flags.contains(MobjFlags::SPAWNCEILING) {
+				template.remove_component::<SpawnOnCeiling>();
+			} else if template.get_component::<SpawnOnCeiling>().is_none() {
+				let offset = template
+					.get_component::<BoxCollider>()
+					.map_or(0.0, |box_collider| box_collider.height);
+				template.add_component(SpawnOnCeiling { offset });
+			}
+
+			if let Some(item_pickup) = template.get_component_mut::<ItemPickup>() {
+				item_pickup.count_item = flags.contains(MobjFlags::COUNTITEM);
+			}
+
+			if let Some(sprite_render) = template.get_component_mut::<SpriteRender>() {
+				if flags.contains(MobjFlags::SHADOW) {
+					sprite_render.render_style = RenderStyle::Fuzz;
+				} else if sprite_render.render_style == RenderStyle::Fuzz {
+					sprite_render.render_style = RenderStyle::Normal;
+				}
+			}
+		}
+
+		if let Some(state_machine) = template.get_component_mut::<StateMachine>() {
+			if let Some(spawn) = thing.spawn {
+				if spawn < state_count {
+					state_machine.spawn = spawn;
+				} else {
+					log::warn!("DeHackEd frame {} does not exist", spawn);
+				}
+			}
+
+			let fields = [
+				(thing.see, &mut state_machine.see),
+				(thing.pain, &mut state_machine.pain),
+				(thing.melee, &mut state_machine.melee),
+				(thing.missile, &mut state_machine.missile),
+				(thing.death, &mut state_machine.death),
+				(thing.xdeath, &mut state_machine.xdeath),
+				(thing.raise, &mut state_machine.raise),
+			];
+
+			for (patched, field) in fields.iter_mut() {
+				if let Some(index) = *patched {
+					**field = state_index(index);
+				}
+			}
+		}
+	}
+
+	// Ammo
+	for (&index, ammo) in dehacked.ammo.iter() {
+		let ammo_info = match weapon_types.ammo.get_mut(index) {
+			Some(ammo_info) => ammo_info,
+			None => {
+				log::warn!("DeHackEd ammo {} does not exist", index);
+				continue;
+			}
+		};
+
+		if let Some(max) = ammo.max {
+			ammo_info.max = max;
+		}
+
+		if let Some(clip) = ammo.clip {
+			ammo_info.clip = clip;
+		}
+	}
+
+	// Weapons
+	for (&index, weapon) in dehacked.weapons.iter() {
+		let weapon_info = match weapon_types.weapons.get_mut(index) {
+			Some(weapon_info) => weapon_info,
+			None => {
+				log::warn!("DeHackEd weapon {} does not exist", index);
+				continue;
+			}
+		};
+
+		// Anything past the last ammo type means the weapon needs no ammo
+		if let Some(ammo_type) = weapon.ammo_type {
+			weapon_info.ammo_type = AMMO_TYPES.get(ammo_type).copied();
+		}
+
+		if let Some(ammo_per_shot) = weapon.ammo_per_shot {
+			weapon_info.ammo_per_shot = ammo_per_shot;
+		}
+
+		let fields = [
+			(weapon.up, &mut weapon_info.up_state),
+			(weapon.down, &mut weapon_info.down_state),
+			(weapon.ready, &mut weapon_info.ready_state),
+			(weapon.attack, &mut weapon_info.attack_state),
+		];
+
+		for (patched, field) in fields.iter_mut() {
+			if let Some(index) = *patched {
+				if index < state_count {
+					**field = index;
+				} else {
+					log::warn!("DeHackEd frame {} does not exist", index);
+				}
+			}
+		}
+
+		if let Some(flash) = weapon.flash {
+			weapon_info.flash_state = state_index(flash);
+		}
+	}
+
+	// Spawn states may have changed, so bring the templates in line with them
+	for handle in mobj_types.names.values() {
+		let template = asset_storage.get_mut(handle).unwrap();
+
+		let spawn = match template.get_component_mut::<StateMachine>() {
+			Some(state_machine) => {
+				state_machine.current = state_machine.spawn;
+				state_machine.time_left = states.states[state_machine.spawn].time;
+				state_machine.spawn
+			}
+			None => continue,
+		};
+
+		if let Some(sprite_render) = template.get_component_mut::<SpriteRender>() {
+			let state = &states.states[spawn];
+			sprite_render.sprite = state.sprite.clone();
+			sprite_render.frame = state.frame;
+			sprite_render.full_bright = state.full_bright;
+		}
+	}
+
+	// Messages
+	if messages.is_empty() {
+		return;
+	}
+
+	let replace = |message: &mut String| {
+		if let Some(new_text) = messages.get(message.as_str()) {
+			*message = new_text.clone();
+		}
+	};
+
+	for handle in mobj_types.names.values() {
+		let template = asset_storage.get_mut(handle).unwrap();
+
+		if let Some(item_pickup) = template.get_component_mut::<ItemPickup>() {
+			replace(&mut item_pickup.message);
+		}
+	}
+
+	for handle in linedef_types.doomednums.values() {
+		let template = asset_storage.get_mut(handle).unwrap();
+
+		let lock = match template.get_component_mut::<UseAction>() {
+			Some(UseAction::DoorUse(door_use)) => door_use.lock.as_mut(),
+			Some(UseAction::DoorSwitchUse(door_use)) => door_use.lock.as_mut(),
+			_ => None,
+		};
+
+		if let Some(lock) = lock {
+			replace(&mut lock.message);
+		}
+	}
+}
+
+fn next_line<'a>(rest: &mut &'a str) -> Option<&'a str> {
+	if rest.is_empty() {
+		return None;
+	}
+
+	let (line, remainder) = match rest.find('\n') {
+		Some(index) => (&rest[..index], &rest[index + 1..]),
+		None => (*rest, ""),
+	};
+
+	*rest = remainder;
+	Some(line)
+}
+
+fn take_chars<'a>(rest: &mut &'a str, count: usize) -> anyhow::Result<&'a str> {
+	let end = rest
+		.char_indices()
+		.map(|(index, _)| index)
+		.chain(std::iter::once(rest.len()))
+		.nth(count)
+		.ok_or_else(|| anyhow!("Unexpected end of text"))?;
+
+	let (taken, remainder) = rest.split_at(end);
+	*rest = remainder;
+	Ok(taken)
+}
+
+// Keys are returned in lowercase, as DeHackEd is not case sensitive
+fn split_key_value(line: &str) -> Option<(String, &str)> {
+	let index = line.find('=')?;
+	Some((
+		line[..index].trim().to_ascii_lowercase(),
+		line[index + 1..].trim(),
+	))
+}
+
+fn parse_int(value: &str) -> anyhow::Result<i32> {
+	value
+		.parse()
+		.with_context(|| format!("Invalid number \"{}\"", value))
+}
+
+fn parse_index(value: &str) -> anyhow::Result<usize> {
+	value
+		.trim()
+		.parse()
+		.with_context(|| format!("Invalid number \"{}\"", value))
+}
+
+// Bits can be given as a number, or as a list of BEX mnemonics
+fn parse_flags(value: &str) -> anyhow::Result<MobjFlags> {
+	if let Ok(bits) = value.parse::<i32>() {
+		return Ok(MobjFlags::from_bits_truncate(bits as u32));
+	}
+
+	let mut flags = MobjFlags::empty();
+
+	for name in value
+		.split(|c: char| c == '+' || c == '|' || c == ',' || c.is_whitespace())
+		.filter(|name| !name.is_empty())
+	{
+		flags |= match name.to_ascii_uppercase().as_str() {
+			"SPECIAL" => MobjFlags::SPECIAL,
+			"SOLID" => MobjFlags::SOLID,
+			"SHOOTABLE" => MobjFlags::SHOOTABLE,
+			"NOSECTOR" => MobjFlags::NOSECTOR,
+			"NOBLOCKMAP" => MobjFlags::NOBLOCKMAP,
+			"AMBUSH" => MobjFlags::AMBUSH,
+			"JUSTHIT" => MobjFlags::JUSTHIT,
+			"JUSTATTACKED" => MobjFlags::JUSTATTACKED,
+			"SPAWNCEILING" => MobjFlags::SPAWNCEILING,
+			"NOGRAVITY" => MobjFlags::NOGRAVITY,
+			"DROPOFF" => MobjFlags::DROPOFF,
+			"PICKUP" => MobjFlags::PICKUP,
+			"NOCLIP" => MobjFlags::NOCLIP,
+			"SLIDE" => MobjFlags::SLIDE,
+			"FLOAT" => MobjFlags::FLOAT,
+			"TELEPORT" => MobjFlags::TELEPORT,
+			"MISSILE" => MobjFlags::MISSILE,
+			"DROPPED" => MobjFlags::DROPPED,
+			"SHADOW" => MobjFlags::SHADOW,
+			"NOBLOOD" => MobjFlags::NOBLOOD,
+			"CORPSE" => MobjFlags::CORPSE,
+			"INFLOAT" => MobjFlags::INFLOAT,
+			"COUNTKILL" => MobjFlags::COUNTKILL,
+			"COUNTITEM" => MobjFlags::COUNTITEM,
+			"SKULLFLY" => MobjFlags::SKULLFLY,
+			"NOTDMATCH" => MobjFlags::NOTDMATCH,
+			"TRANSLATION" => MobjFlags::TRANSLATION,
+			_ => bail!("Unknown thing flag \"{}\"", name),
+		};
+	}
+
+	Ok(flags)
+}
+
+// Code pointer names, with or without the A_ prefix. NULL removes the action.
+fn parse_action(value: &str) -> anyhow::Result<Option<StateAction>> {
+	let name = match value.get(..2) {
+		Some(prefix) if prefix.eq_ignore_ascii_case("a_") => &value[2..],
+		_ => value,
+	};
+
+	if name.eq_ignore_ascii_case("null") {
+		return Ok(None);
+	}
+
+	ACTIONS
+		.iter()
+		.find(|(action_name, _)| action_name.eq_ignore_ascii_case(name))
+		.map(|&(_, action)| Some(action))
+		.ok_or_else(|| anyhow!("Unknown code pointer \"{}\"", value))
+}
+
+// Things and sprites in their original order, which DeHackEd uses to refer to them
+#[rustfmt::skip]
+const MOBJ_NAMES: [&str; 137] = [
+	"PLAYER", "POSSESSED", "SHOTGUY", "VILE", "FIRE", "UNDEAD",
+	"TRACER", "SMOKE", "FATSO", "FATSHOT", "CHAINGUY", "TROOP",
+	"SERGEANT", "SHADOWS", "HEAD", "BRUISER", "BRUISERSHOT", "KNIGHT",
+	"SKULL", "SPIDER", "BABY", "CYBORG", "PAIN", "WOLFSS",
+	"KEEN", "BOSSBRAIN", "BOSSSPIT", "BOSSTARGET", "SPAWNSHOT", "SPAWNFIRE",
+	"BARREL", "TROOPSHOT", "HEADSHOT", "ROCKET", "PLASMA", "BFG",
+	"ARACHPLAZ", "PUFF", "BLOOD", "TFOG", "IFOG", "TELEPORTMAN",
+	"EXTRABFG", "MISC0", "MISC1", "MISC2", "MISC3", "MISC4",
+	"MISC5", "MISC6", "MISC7", "MISC8", "MISC9", "MISC10",
+	"MISC11", "MISC12", "INV", "MISC13", "INS", "MISC14",
+	"MISC15", "MISC16", "MEGA", "CLIP", "MISC17", "MISC18",
+	"MISC19", "MISC20", "MISC21", "MISC22", "MISC23", "MISC24",
+	"MISC25", "CHAINGUN", "MISC26", "MISC27", "MISC28", "SHOTGUN",
+	"SUPERSHOTGUN", "MISC29", "MISC30", "MISC31", "MISC32", "MISC33",
+	"MISC34", "MISC35", "MISC36", "MISC37", "MISC38", "MISC39",
+	"MISC40", "MISC41", "MISC42", "MISC43", "MISC44", "MISC45",
+	"MISC46", "MISC47", "MISC48", "MISC49", "MISC50", "MISC51",
+	"MISC52", "MISC53", "MISC54", "MISC55", "MISC56", "MISC57",
+	"MISC58", "MISC59", "MISC60", "MISC61", "MISC62", "MISC63",
+	"MISC64", "MISC65", "MISC66", "MISC67", "MISC68", "MISC69",
+	"MISC70", "MISC71", "MISC72", "MISC73", "MISC74", "MISC75",
+	"MISC76", "MISC77", "MISC78", "MISC79", "MISC80", "MISC81",
+	"MISC82", "MISC83", "MISC84", "MISC85", "MISC86",
+];
+
+#[rustfmt::skip]
+const SPRITE_NAMES: [&str; 138] = [
+	"TROO", "SHTG", "PUNG", "PISG", "PISF", "SHTF", "SHT2", "CHGG", "CHGF", "MISG",
+	"MISF", "SAWG", "PLSG", "PLSF", "BFGG", "BFGF", "BLUD", "PUFF", "BAL1", "BAL2",
+	"PLSS", "PLSE", "MISL", "BFS1", "BFE1", "BFE2", "TFOG", "IFOG", "PLAY", "POSS",
+	"SPOS", "VILE", "FIRE", "FATB", "FBXP", "SKEL", "MANF", "FATT", "CPOS", "SARG",
+	"HEAD", "BAL7", "BOSS", "BOS2", "SKUL", "SPID", "BSPI", "APLS", "APBX", "CYBR",
+	"PAIN", "SSWV", "KEEN", "BBRN", "BOSF", "ARM1", "ARM2", "BAR1", "BEXP", "FCAN",
+	"BON1", "BON2", "BKEY", "RKEY", "YKEY", "BSKU", "RSKU", "YSKU", "STIM", "MEDI",
+	"SOUL", "PINV", "PSTR", "PINS", "MEGA", "SUIT", "PMAP", "PVIS", "CLIP", "AMMO",
+	"ROCK", "BROK", "CELL", "CELP", "SHEL", "SBOX", "BPAK", "BFUG", "MGUN", "CSAW",
+	"LAUN", "PLAS", "SHOT", "SGN2", "COLU", "SMT2", "GOR1", "POL2", "POL5", "POL4",
+	"POL3", "POL1", "POL6", "GOR2", "GOR3", "GOR4", "GOR5", "SMIT", "COL1", "COL2",
+	"COL3", "COL4", "CAND", "CBRA", "COL6", "TRE1", "TRE2", "ELEC", "CEYE", "FSKU",
+	"COL5", "TBLU", "TGRN", "TRED", "SMBT", "SMGT", "SMRT", "HDB1", "HDB2", "HDB3",
+	"HDB4", "HDB5", "HDB6", "POB1", "POB2", "BRS1", "TLMP", "TLP2",
+];
+
+const ACTIONS: [(&str, StateAction); 74] = [
+	("Light0", StateAction::Light0),
+	("WeaponReady", StateAction::WeaponReady),
+	("Lower", StateAction::Lower),
+	("Raise", StateAction::Raise),
+	("Punch", StateAction::Punch),
+	("ReFire", StateAction::ReFire),
+	("FirePistol", StateAction::FirePistol),
+	("Light1", StateAction::Light1),
+	("FireShotgun", StateAction::FireShotgun),
+	("Light2", StateAction::Light2),
+	("FireShotgun2", StateAction::FireShotgun2),
+	("CheckReload", StateAction::CheckReload),
+	("OpenShotgun2", StateAction::OpenShotgun2),
+	("LoadShotgun2", StateAction::LoadShotgun2),
+	("CloseShotgun2", StateAction::CloseShotgun2),
+	("FireCGun", StateAction::FireCGun),
+	("GunFlash", StateAction::GunFlash),
+	("FireMissile", StateAction::FireMissile),
+	("Saw", StateAction::Saw),
+	("FirePlasma", StateAction::FirePlasma),
+	("BfgSound", StateAction::BfgSound),
+	("FireBfg", StateAction::FireBfg),
+	("BfgSpray", StateAction::BfgSpray),
+	("Explode", StateAction::Explode),
+	("Pain", StateAction::Pain),
+	("PlayerScream", StateAction::PlayerScream),
+	("Fall", StateAction::Fall),
+	("XScream", StateAction::XScream),
+	("Look", StateAction::Look),
+	("Chase", StateAction::Chase),
+	("FaceTarget", StateAction::FaceTarget),
+	("PosAttack", StateAction::PosAttack),
+	("Scream", StateAction::Scream),
+	("SPosAttack", StateAction::SPosAttack),
+	("VileChase", StateAction::VileChase),
+	("VileStart", StateAction::VileStart),
+	("VileTarget", StateAction::VileTarget),
+	("VileAttack", StateAction::VileAttack),
+	("StartFire", StateAction::StartFire),
+	("Fire", StateAction::Fire),
+	("FireCrackle", StateAction::FireCrackle),
+	("Tracer", StateAction::Tracer),
+	("SkelWhoosh", StateAction::SkelWhoosh),
+	("SkelFist", StateAction::SkelFist),
+	("SkelMissile", StateAction::SkelMissile),
+	("FatRaise", StateAction::FatRaise),
+	("FatAttack1", StateAction::FatAttack1),
+	("FatAttack2", StateAction::FatAttack2),
+	("FatAttack3", StateAction::FatAttack3),
+	("BossDeath", StateAction::BossDeath),
+	("CPosAttack", StateAction::CPosAttack),
+	("CPosRefire", StateAction::CPosRefire),
+	("TroopAttack", StateAction::TroopAttack),
+	("SargAttack", StateAction::SargAttack),
+	("HeadAttack", StateAction::HeadAttack),
+	("BruisAttack", StateAction::BruisAttack),
+	("SkullAttack", StateAction::SkullAttack),
+	("Metal", StateAction::Metal),
+	("SpidRefire", StateAction::SpidRefire),
+	("BabyMetal", StateAction::BabyMetal),
+	("BspiAttack", StateAction::BspiAttack),
+	("Hoof", StateAction::Hoof),
+	("CyberAttack", StateAction::CyberAttack),
+	("PainAttack", StateAction::PainAttack),
+	("PainDie", StateAction::PainDie),
+	("KeenDie", StateAction::KeenDie),
+	("BrainPain", StateAction::BrainPain),
+	("BrainScream", StateAction::BrainScream),
+	("BrainDie", StateAction::BrainDie),
+	("BrainAwake", StateAction::BrainAwake),
+	("BrainSpit", StateAction::BrainSpit),
+	("SpawnSound", StateAction::SpawnSound),
+	("SpawnFly", StateAction::SpawnFly),
+	("BrainExplode", StateAction::BrainExplode),
+];
+
+// Ammo types in their original order
+const AMMO_TYPES: [AmmoType; 4] = [
+	AmmoType::Clip,
+	AmmoType::Shell,
+	AmmoType::Cell,
+	AmmoType::Missile,
+];
+
+// BEX string mnemonics, and the original texts they replace
+const STRINGS: [(&str, &str); 42] = [
+	("GOTARMOR", "Picked up the armor."),
+	("GOTMEGA", "Picked up the MegaArmor!"),
+	("GOTHTHBONUS", "Picked up a health bonus."),
+	("GOTARMBONUS", "Picked up an armor bonus."),
+	("GOTSTIM", "Picked up a stimpack."),
+	("GOTMEDIKIT", "Picked up a medikit."),
+	("GOTSUPER", "Supercharge!"),
+	("GOTBLUECARD", "Picked up a blue keycard."),
+	("GOTYELWCARD", "Picked up a yellow keycard."),
+	("GOTREDCARD", "Picked up a red keycard."),
+	("GOTBLUESKUL", "Picked up a blue skull key."),
+	("GOTYELWSKUL", "Picked up a yellow skull key."),
+	("GOTREDSKULL", "Picked up a red skull key."),
+	("GOTINVUL", "Invulnerability!"),
+	("GOTBERSERK", "Berserk!"),
+	("GOTINVIS", "Partial Invisibility"),
+	("GOTSUIT", "Radiation Shielding Suit"),
+	("GOTMAP", "Computer Area Map"),
+	("GOTVISOR", "Light Amplification Visor"),
+	("GOTMSPHERE", "MegaSphere!"),
+	("GOTCLIP", "Picked up a clip."),
+	("GOTCLIPBOX", "Picked up a box of bullets."),
+	("GOTROCKET", "Picked up a rocket."),
+	("GOTROCKBOX", "Picked up a box of rockets."),
+	("GOTCELL", "Picked up an energy cell."),
+	("GOTCELLBOX", "Picked up an energy cell pack."),
+	("GOTSHELLS", "Picked up 4 shotgun shells."),
+	("GOTSHELLBOX", "Picked up a box of shotgun shells."),
+	("GOTBACKPACK", "Picked up a backpack full of ammo!"),
+	("GOTBFG9000", "You got the BFG9000!  Oh, yes."),
+	("GOTCHAINGUN", "You got the chaingun!"),
+	("GOTCHAINSAW", "A chainsaw!  Find some meat!"),
+	("GOTLAUNCHER", "You got the rocket launcher!"),
+	("GOTPLASMA", "You got the plasma gun!"),
+	("GOTSHOTGUN", "You got the shotgun!"),
+	("GOTSHOTGUN2", "You got the super shotgun!"),
+	("PD_BLUEO", "You need a blue key to activate this object"),
+	("PD_REDO", "You need a red key to activate this object"),
+	(
+		"PD_YELLOWO",
+		"You need a yellow key to activate this object",
+	),
+	("PD_BLUEK", "You need a blue key to open this door"),
+	("PD_REDK", "You need a red key to open this door"),
+	("PD_YELLOWK", "You need a yellow key to open this door"),
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PATCH: &str = "Patch File for DeHackEd v3.0\r
+# A comment\r
+Doom version = 21\r
+Patch format = 6\r
+\r
+Thing 12 (Imp)\r
+Hit points = 100\r
+Width = 1310720\r
+Bits = SOLID+SHOOTABLE+NOBLOOD\r
+Initial frame = 34\r
+\r
+Frame 34\r
+Duration = -1\r
+Next frame = 35\r
+\r
+Pointer 12 (Frame 34)\r
+Codep Frame = 56\r
+\r
+Ammo 1 (Shells)\r
+Max ammo = 100\r
+Per ammo = 8\r
+\r
+Weapon 2 (Shotgun)\r
+Ammo type = 5\r
+Shooting frame = 40\r
+\r
+Text 11 14\r
+Hello\r
+worldGoodbye\r
+world!\r
+\r
+[CODEPTR]\r
+Frame 100 = A_Chase\r
+FRAME 101 = NULL\r
+\r
+[STRINGS]\r
+GOTARMOR = Picked up the \\\r
+    shiny armor.\r
+GotMega = Mega\\narmor!\r
+\r
+[PARS]\r
+par 1 2 75\r
+par 14 300\r
+";
+
+	#[test]
+	fn parse_patch() {
+		let mut dehacked = Dehacked::default();
+		dehacked.parse(PATCH).unwrap();
+
+		let thing = &dehacked.things[&12];
+		assert_eq!(thing.hit_points, Some(100));
+		assert_eq!(thing.radius, Some(20.0));
+		assert_eq!(
+			thing.flags,
+			Some(MobjFlags::SOLID | MobjFlags::SHOOTABLE | MobjFlags::NOBLOOD)
+		);
+		assert_eq!(thing.spawn, Some(34));
+		assert_eq!(thing.speed, None);
+
+		let frame = &dehacked.frames[&34];
+		assert_eq!(frame.duration, Some(-1));
+		assert_eq!(frame.next, Some(35));
+		assert_eq!(frame.sprite, None);
+
+		assert_eq!(dehacked.code_pointers[&34], CodePointer::Frame(56));
+		assert_eq!(
+			dehacked.code_pointers[&100],
+			CodePointer::Action(Some(StateAction::Chase))
+		);
+		assert_eq!(dehacked.code_pointers[&101], CodePointer::Action(None));
+
+		let ammo = &dehacked.ammo[&1];
+		assert_eq!(ammo.max, Some(100));
+		assert_eq!(ammo.clip, Some(8));
+
+		let weapon = &dehacked.weapons[&2];
+		assert_eq!(weapon.ammo_type, Some(5));
+		assert_eq!(weapon.attack, Some(40));
+		assert_eq!(weapon.up, None);
+
+		assert_eq!(
+			dehacked.texts,
+			vec![(
+				String::from("Hello\nworld"),
+				String::from("Goodbye\nworld!")
+			)]
+		);
+
+		assert_eq!(dehacked.strings["GOTARMOR"], "Picked up the shiny armor.");
+		assert_eq!(dehacked.strings["GOTMEGA"], "Mega\narmor!");
+
+		assert_eq!(dehacked.par_times["E1M2"], Duration::from_secs(75));
+		assert_eq!(dehacked.par_times["MAP14"], Duration::from_secs(300));
+	}
+
+	#[test]
+	fn later_patches_override() {
+		let mut dehacked = Dehacked::default();
+		dehacked.parse(PATCH).unwrap();
+		dehacked
+			.parse("Thing 12\nHit points = 200\n\n[PARS]\npar 1 2 90\n")
+			.unwrap();
+
+		assert_eq!(dehacked.things[&12].hit_points, Some(200));
+		assert_eq!(dehacked.things[&12].radius, Some(20.0));
+		assert_eq!(dehacked.par_times["E1M2"], Duration::from_secs(90));
+	}
+}
@@ -2,18 +2,24 @@ use crate::{
 	assets::{AssetHandle, AssetStorage},
 	audio::Sound,
 	doom::{
-		client::{UseAction, UseEvent},
+		ceiling::CeilingActive,
+		client::{Client, MessageEvent, ShootAction, ShootEvent, UseAction, UseEvent},
+		floor::FloorActive,
+		inventory::{Inventory, Keys},
 		map::{
 			textures::{TextureType, Wall},
-			LinedefRef, MapDynamic, SectorRef, SidedefSlot,
+			LinedefRef, Map, MapDynamic, Sector, SectorRef, SidedefSlot,
 		},
-		physics::SectorTracer,
+		monster::Monster,
+		physics::{CrossAction, CrossEvent, SectorTracer},
+		plat::PlatActive,
 	},
 	geometry::Side,
 };
 use legion::prelude::{
 	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
 };
+use serde::Deserialize;
 use shrev::EventChannel;
 use std::time::Duration;
 
@@ -24,11 +30,13 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 		.register_reader();
 
 	Box::new(move |world, resources| {
-		let (asset_storage, use_event_channel, mut sound_queue) = <(
-			Read<AssetStorage>,
-			Read<EventChannel<UseEvent>>,
-			Write<Vec<(AssetHandle<Sound>, Entity)>>,
-		)>::fetch_mut(resources);
+		let (asset_storage, use_event_channel, mut message_event_channel, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<EventChannel<UseEvent>>,
+				Write<EventChannel<MessageEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
 
 		let mut command_buffer = CommandBuffer::new(world);
 
@@ -37,6 +45,18 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 				.get_component::<UseAction>(use_event.linedef_entity)
 				.as_deref()
 			{
+				if let Some(lock) = &door_use.lock {
+					if !check_lock(
+						world,
+						use_event.entity,
+						lock,
+						&mut message_event_channel,
+						&mut sound_queue,
+					) {
+						continue;
+					}
+				}
+
 				let linedef_ref = world
 					.get_component::<LinedefRef>(use_event.linedef_entity)
 					.unwrap();
@@ -54,45 +74,44 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 					if let Some(mut door_active) =
 						unsafe { world.get_component_mut_unchecked::<DoorActive>(sector_entity) }
 					{
+						// Doors that stay open can't be closed again
+						if door_use.params.end_state != DoorState::Closed {
+							continue;
+						}
+
 						match door_active.state {
 							DoorState::Closing => {
 								// Re-open the door
 								door_active.state = DoorState::Closed;
+								door_active.time_left = Duration::default();
 							}
 							DoorState::Opening | DoorState::Open => {
+								// Monsters never close doors
+								if world.has_component::<Monster>(use_event.entity) {
+									continue;
+								}
+
 								// Close the door early
 								door_active.state = DoorState::Open;
 								door_active.time_left = Duration::default();
 							}
-							DoorState::Closed => unreachable!(),
+							DoorState::Closed => {
+								// Open the door early
+								door_active.time_left = Duration::default();
+							}
 						}
-					} else {
-						if let Some(open_height) = sector
-							.neighbours
-							.iter()
-							.map(|index| map_dynamic.sectors[*index].interval.max)
-							.min_by(|x, y| x.partial_cmp(y).unwrap())
-						{
-							command_buffer.add_component(
-								sector_entity,
-								DoorActive {
-									open_sound: door_use.open_sound.clone(),
-									open_height: open_height - 4.0,
-
-									close_sound: door_use.close_sound.clone(),
-									close_height: map_dynamic.sectors[sector_index].interval.min,
-
-									state: DoorState::Closed,
-									speed: door_use.speed,
-									time_left: door_use.wait_time,
-									wait_time: door_use.wait_time,
-								},
-							);
-						} else {
-							log::error!(
-								"Used door sector {} has no neighbouring sectors",
-								sector_index
-							);
+					} else if world.has_component::<CeilingActive>(sector_entity)
+						|| world.has_component::<FloorActive>(sector_entity)
+						|| world.has_component::<PlatActive>(sector_entity)
+					{
+						continue;
+					} else if let Some(door_active) =
+						new_door(&door_use.params, sector, sector_index, &map_dynamic)
+					{
+						command_buffer.add_component(sector_entity, door_active);
+
+						if !door_use.retrigger {
+							command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
 						}
 					}
 				} else {
@@ -107,6 +126,18 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 					continue;
 				}
 
+				if let Some(lock) = &door_use.lock {
+					if !check_lock(
+						world,
+						use_event.entity,
+						lock,
+						&mut message_event_channel,
+						&mut sound_queue,
+					) {
+						continue;
+					}
+				}
+
 				let linedef_ref = world
 					.get_component::<LinedefRef>(use_event.linedef_entity)
 					.unwrap();
@@ -130,75 +161,200 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 				{
 					let sector_entity = map_dynamic.sectors[i].entity;
 
-					if world.has_component::<DoorActive>(sector_entity) {
+					if world.has_component::<CeilingActive>(sector_entity)
+						|| world.has_component::<DoorActive>(sector_entity)
+						|| world.has_component::<FloorActive>(sector_entity)
+						|| world.has_component::<PlatActive>(sector_entity)
+					{
 						continue;
-					} else {
+					}
+
+					if let Some(door_active) = new_door(&door_use.params, sector, i, map_dynamic) {
+						command_buffer.add_component(sector_entity, door_active);
 						used = true;
 					}
+				}
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&door_use.switch_sound,
+						if door_use.retrigger {
+							Some(door_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !door_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn door_cross_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, cross_event_channel) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+		)>::fetch(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			if let Some(CrossAction::DoorCross(door_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				// Only some linedefs can be triggered by monsters
+				if !door_cross.monsters && client.entity != Some(cross_event.entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(cross_event.linedef_entity)
+					.unwrap();
+				let map_dynamic = world
+					.get_component::<MapDynamic>(linedef_ref.map_entity)
+					.unwrap();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+				let linedef = &map.linedefs[linedef_ref.index];
+
+				// Activate all the doors with the same tag
+				for (i, sector) in map
+					.sectors
+					.iter()
+					.enumerate()
+					.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+				{
+					let sector_entity = map_dynamic.sectors[i].entity;
+
+					if world.has_component::<CeilingActive>(sector_entity)
+						|| world.has_component::<DoorActive>(sector_entity)
+						|| world.has_component::<FloorActive>(sector_entity)
+						|| world.has_component::<PlatActive>(sector_entity)
+					{
+						continue;
+					}
 
-					if let Some(open_height) = sector
-						.neighbours
-						.iter()
-						.map(|index| map_dynamic.sectors[*index].interval.max)
-						.min_by(|x, y| x.partial_cmp(y).unwrap())
+					if let Some(door_active) = new_door(&door_cross.params, sector, i, &map_dynamic)
 					{
-						command_buffer.add_component(
-							sector_entity,
-							DoorActive {
-								open_sound: door_use.open_sound.clone(),
-								open_height: open_height - 4.0,
-
-								close_sound: door_use.close_sound.clone(),
-								close_height: map_dynamic.sectors[i].interval.min,
-
-								state: DoorState::Closed,
-								speed: door_use.speed,
-								time_left: door_use.wait_time,
-								wait_time: door_use.wait_time,
-							},
-						);
-					} else {
-						log::error!("Used door sector {}, has no neighbouring sectors", i);
+						command_buffer.add_component(sector_entity, door_active);
 					}
 				}
 
-				if used {
-					// Flip the switch texture
-					let sidedef = linedef.sidedefs[0].as_ref().unwrap();
-					let linedef_dynamic = &mut map_dynamic.linedefs[linedef_ref.index];
-					let sidedef_dynamic = linedef_dynamic.sidedefs[0].as_mut().unwrap();
-
-					for slot in [SidedefSlot::Top, SidedefSlot::Middle, SidedefSlot::Bottom]
-						.iter()
-						.copied()
+				if !door_cross.retrigger {
+					command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn door_shoot_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut shoot_event_reader = resources
+		.get_mut::<EventChannel<ShootEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, shoot_event_channel, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Read<EventChannel<ShootEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for shoot_event in shoot_event_channel.read(&mut shoot_event_reader) {
+			if let Some(ShootAction::DoorShoot(door_shoot)) = world
+				.get_component::<ShootAction>(shoot_event.linedef_entity)
+				.as_deref()
+			{
+				// Only some linedefs can be triggered by monsters
+				if !door_shoot.monsters && client.entity != Some(shoot_event.entity) {
+					continue;
+				}
+
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(shoot_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(shoot_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+				let linedef = &map.linedefs[linedef_ref.index];
+
+				// Activate all the doors with the same tag
+				for (i, sector) in map
+					.sectors
+					.iter()
+					.enumerate()
+					.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+				{
+					let sector_entity = map_dynamic.sectors[i].entity;
+
+					if world.has_component::<CeilingActive>(sector_entity)
+						|| world.has_component::<DoorActive>(sector_entity)
+						|| world.has_component::<FloorActive>(sector_entity)
+						|| world.has_component::<PlatActive>(sector_entity)
 					{
-						if let TextureType::Normal(texture) =
-							&mut sidedef_dynamic.textures[slot as usize]
-						{
-							if let Some(new) = map.switches.get(texture) {
-								// Change texture
-								let old = std::mem::replace(texture, new.clone());
-
-								// Play sound
-								let sector_entity =
-									map_dynamic.sectors[sidedef.sector_index].entity;
-								sound_queue.push((door_use.switch_sound.clone(), sector_entity));
-
-								command_buffer.add_component(
-									use_event.linedef_entity,
-									SwitchActive {
-										sound: door_use.switch_sound.clone(),
-										texture: old,
-										texture_slot: slot,
-										time_left: door_use.switch_time,
-									},
-								);
-
-								break;
-							}
-						}
+						continue;
+					}
+
+					if let Some(door_active) = new_door(&door_shoot.params, sector, i, map_dynamic)
+					{
+						command_buffer.add_component(sector_entity, door_active);
 					}
 				}
+
+				// Shot linedefs always flip the switch, even if nothing was activated
+				activate_switch(
+					shoot_event.linedef_entity,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&door_shoot.switch_sound,
+					if door_shoot.retrigger {
+						Some(door_shoot.switch_time)
+					} else {
+						None
+					},
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				if !door_shoot.retrigger {
+					command_buffer.remove_component::<ShootAction>(shoot_event.linedef_entity);
+				}
 			}
 		}
 
@@ -206,22 +362,165 @@ pub fn door_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &
 	})
 }
 
+// Flips the switch texture on the front of a linedef and plays the sound.
+// If a time is given, the switch flips back after that time, otherwise it stays flipped.
+pub fn activate_switch(
+	linedef_entity: Entity,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	sound: &AssetHandle<Sound>,
+	switch_time: Option<Duration>,
+	sound_queue: &mut Vec<(AssetHandle<Sound>, Entity)>,
+	command_buffer: &mut CommandBuffer,
+) {
+	let linedef = &map.linedefs[linedef_index];
+	let sidedef = linedef.sidedefs[0].as_ref().unwrap();
+	let sector_entity = map_dynamic.sectors[sidedef.sector_index].entity;
+	let linedef_dynamic = &mut map_dynamic.linedefs[linedef_index];
+	let sidedef_dynamic = linedef_dynamic.sidedefs[0].as_mut().unwrap();
+
+	for slot in [SidedefSlot::Top, SidedefSlot::Middle, SidedefSlot::Bottom]
+		.iter()
+		.copied()
+	{
+		if let TextureType::Normal(texture) = &mut sidedef_dynamic.textures[slot as usize] {
+			if let Some(new) = map.switches.get(texture) {
+				// Change texture
+				let old = std::mem::replace(texture, new.clone());
+
+				// Play sound
+				sound_queue.push((sound.clone(), sector_entity));
+
+				if let Some(time_left) = switch_time {
+					command_buffer.add_component(
+						linedef_entity,
+						SwitchActive {
+							sound: sound.clone(),
+							texture: old,
+							texture_slot: slot,
+							time_left,
+						},
+					);
+				}
+
+				break;
+			}
+		}
+	}
+}
+
+// Checks whether the entity has the keys to open a lock, and complains if it doesn't
+fn check_lock(
+	world: &World,
+	entity: Entity,
+	lock: &DoorLock,
+	message_event_channel: &mut EventChannel<MessageEvent>,
+	sound_queue: &mut Vec<(AssetHandle<Sound>, Entity)>,
+) -> bool {
+	if let Some(inventory) = world.get_component::<Inventory>(entity) {
+		if inventory.keys.intersects(lock.keys) {
+			return true;
+		}
+	}
+
+	sound_queue.push((lock.sound.clone(), entity));
+	message_event_channel.single_write(MessageEvent {
+		entity,
+		message: lock.message.clone(),
+	});
+
+	false
+}
+
+fn new_door(
+	params: &DoorParams,
+	sector: &Sector,
+	sector_index: usize,
+	map_dynamic: &MapDynamic,
+) -> Option<DoorActive> {
+	let sector_dynamic = &map_dynamic.sectors[sector_index];
+
+	// Doors that start by closing reopen to where they were
+	let open_height = match params.start_state {
+		DoorState::Closed => {
+			if let Some(open_height) = sector
+				.neighbours
+				.iter()
+				.map(|index| map_dynamic.sectors[*index].interval.max)
+				.min_by(|x, y| x.partial_cmp(y).unwrap())
+			{
+				open_height - 4.0
+			} else {
+				log::error!("Door sector {} has no neighbouring sectors", sector_index);
+				return None;
+			}
+		}
+		_ => sector_dynamic.interval.max,
+	};
+
+	Some(DoorActive {
+		open_sound: params.open_sound.clone(),
+		open_height,
+
+		close_sound: params.close_sound.clone(),
+		close_height: sector_dynamic.interval.min,
+
+		state: params.start_state,
+		end_state: params.end_state,
+		speed: params.speed,
+		time_left: Duration::default(),
+		wait_time: params.wait_time,
+	})
+}
+
 #[derive(Clone, Debug)]
-pub struct DoorUse {
-	pub open_sound: AssetHandle<Sound>,
-	pub close_sound: AssetHandle<Sound>,
+pub struct DoorParams {
+	pub start_state: DoorState,
+	pub end_state: DoorState,
 	pub speed: f32,
 	pub wait_time: Duration,
+	pub open_sound: AssetHandle<Sound>,
+	pub close_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DoorLock {
+	pub keys: Keys,
+	pub message: String,
+	pub sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DoorUse {
+	pub params: DoorParams,
+	pub lock: Option<DoorLock>,
+	pub retrigger: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct DoorSwitchUse {
-	pub open_sound: AssetHandle<Sound>,
-	pub close_sound: AssetHandle<Sound>,
+	pub params: DoorParams,
+	pub lock: Option<DoorLock>,
 	pub switch_sound: AssetHandle<Sound>,
 	pub switch_time: Duration,
-	pub speed: f32,
-	pub wait_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DoorShoot {
+	pub params: DoorParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DoorCross {
+	pub params: DoorParams,
+	pub retrigger: bool,
+	pub monsters: bool,
 }
 
 pub fn door_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
@@ -250,10 +549,14 @@ pub fn door_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 
 			match door_active.state {
 				DoorState::Closed => {
-					door_active.state = DoorState::Opening;
+					if let Some(new_time) = door_active.time_left.checked_sub(*delta) {
+						door_active.time_left = new_time;
+					} else {
+						door_active.state = DoorState::Opening;
 
-					// Play sound
-					sound_queue.push((door_active.open_sound.clone(), entity));
+						// Play sound
+						sound_queue.push((door_active.open_sound.clone(), entity));
+					}
 				}
 				DoorState::Opening => {
 					let move_step = door_active.speed * delta.as_secs_f32();
@@ -261,8 +564,13 @@ pub fn door_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 
 					if sector_dynamic.interval.max > door_active.open_height {
 						sector_dynamic.interval.max = door_active.open_height;
-						door_active.state = DoorState::Open;
-						door_active.time_left = door_active.wait_time;
+
+						if door_active.end_state == DoorState::Open {
+							command_buffer.remove_component::<DoorActive>(entity);
+						} else {
+							door_active.state = DoorState::Open;
+							door_active.time_left = door_active.wait_time;
+						}
 					}
 				}
 				DoorState::Open => {
@@ -287,12 +595,26 @@ pub fn door_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 					// TODO use fraction
 					if trace.collision.is_some() {
 						// Hit something on the way down, re-open the door
-						door_active.state = DoorState::Closed;
+						// Doors that only close keep pushing instead
+						let close_only = door_active.end_state == DoorState::Closed
+							&& door_active.wait_time == Duration::default();
+
+						if !close_only {
+							door_active.state = DoorState::Closed;
+							door_active.time_left = Duration::default();
+						}
 					} else {
 						sector_dynamic.interval.max += move_step;
 
 						if sector_dynamic.interval.max < door_active.close_height {
-							command_buffer.remove_component::<DoorActive>(entity);
+							sector_dynamic.interval.max = door_active.close_height;
+
+							if door_active.end_state == DoorState::Closed {
+								command_buffer.remove_component::<DoorActive>(entity);
+							} else {
+								door_active.state = DoorState::Closed;
+								door_active.time_left = door_active.wait_time;
+							}
 						}
 					}
 				}
@@ -312,6 +634,7 @@ pub struct DoorActive {
 	pub close_height: f32,
 
 	pub state: DoorState,
+	pub end_state: DoorState,
 	pub speed: f32,
 	pub time_left: Duration,
 	pub wait_time: Duration,
@@ -365,7 +688,7 @@ pub struct SwitchActive {
 	time_left: Duration,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
 pub enum DoorState {
 	Closed,
 	Opening,
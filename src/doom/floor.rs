@@ -0,0 +1,885 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		ceiling::CeilingActive,
+		client::{Client, ShootAction, ShootEvent, UseAction, UseEvent},
+		damage::{DamageEvent, DamageType},
+		data::{States, FRAME_TIME},
+		door::{activate_switch, DoorActive, SwitchActive},
+		map::{
+			textures::{Flat, TextureType},
+			LinedefRef, Map, MapDynamic, SectorRef, SectorSlot, SidedefSlot,
+		},
+		physics::{move_floor, CrossAction, CrossEvent},
+		plat::PlatActive,
+	},
+	geometry::Side,
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn floor_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut use_event_reader = resources
+		.get_mut::<EventChannel<UseEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, use_event_channel, mut sound_queue) = <(
+			Read<AssetStorage>,
+			Read<EventChannel<UseEvent>>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for use_event in use_event_channel.read(&mut use_event_reader) {
+			if let Some(UseAction::FloorSwitchUse(floor_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				let used = activate_floors(
+					world,
+					&floor_use.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&asset_storage,
+					&mut command_buffer,
+				);
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&floor_use.switch_sound,
+						if floor_use.retrigger {
+							Some(floor_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !floor_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			} else if let Some(UseAction::DonutSwitchUse(donut_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				let used = activate_donuts(
+					world,
+					donut_use,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut command_buffer,
+				);
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&donut_use.switch_sound,
+						if donut_use.retrigger {
+							Some(donut_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !donut_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			} else if let Some(UseAction::StairsSwitchUse(stairs_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				let used = activate_stairs(
+					world,
+					&stairs_use.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut command_buffer,
+				);
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&stairs_use.switch_sound,
+						if stairs_use.retrigger {
+							Some(stairs_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !stairs_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn floor_cross_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, cross_event_channel) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+		)>::fetch(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			if let Some(CrossAction::FloorCross(floor_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				// Only some linedefs can be triggered by monsters
+				if !floor_cross.monsters && client.entity != Some(cross_event.entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(cross_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_floors(
+					world,
+					&floor_cross.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&asset_storage,
+					&mut command_buffer,
+				);
+
+				if !floor_cross.retrigger {
+					command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				}
+			} else if let Some(CrossAction::StairsCross(stairs_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				if client.entity != Some(cross_event.entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(cross_event.linedef_entity)
+					.unwrap();
+				let map_dynamic = world
+					.get_component::<MapDynamic>(linedef_ref.map_entity)
+					.unwrap();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_stairs(
+					world,
+					&stairs_cross.params,
+					linedef_ref.index,
+					map,
+					&map_dynamic,
+					&mut command_buffer,
+				);
+
+				if !stairs_cross.retrigger {
+					command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn floor_shoot_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut shoot_event_reader = resources
+		.get_mut::<EventChannel<ShootEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, shoot_event_channel, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Read<EventChannel<ShootEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for shoot_event in shoot_event_channel.read(&mut shoot_event_reader) {
+			if let Some(ShootAction::FloorShoot(floor_shoot)) = world
+				.get_component::<ShootAction>(shoot_event.linedef_entity)
+				.as_deref()
+			{
+				// Only some linedefs can be triggered by monsters
+				if !floor_shoot.monsters && client.entity != Some(shoot_event.entity) {
+					continue;
+				}
+
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(shoot_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(shoot_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_floors(
+					world,
+					&floor_shoot.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&asset_storage,
+					&mut command_buffer,
+				);
+
+				// Shot linedefs always flip the switch, even if nothing was activated
+				activate_switch(
+					shoot_event.linedef_entity,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&floor_shoot.switch_sound,
+					if floor_shoot.retrigger {
+						Some(floor_shoot.switch_time)
+					} else {
+						None
+					},
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				if !floor_shoot.retrigger {
+					command_buffer.remove_component::<ShootAction>(shoot_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Starts a floor mover in every sector with the same tag as the linedef.
+// Returns whether any floor was started.
+pub fn activate_floors(
+	world: &World,
+	params: &FloorParams,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	asset_storage: &AssetStorage,
+	command_buffer: &mut CommandBuffer,
+) -> bool {
+	let linedef = &map.linedefs[linedef_index];
+	let mut used = false;
+
+	for (i, sector) in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+	{
+		let sector_entity = map_dynamic.sectors[i].entity;
+
+		if world.has_component::<CeilingActive>(sector_entity)
+			|| world.has_component::<DoorActive>(sector_entity)
+			|| world.has_component::<FloorActive>(sector_entity)
+			|| world.has_component::<PlatActive>(sector_entity)
+		{
+			continue;
+		}
+
+		let floor_height = map_dynamic.sectors[i].interval.min;
+		let ceiling_height = map_dynamic.sectors[i].interval.max;
+		let neighbour_floors = sector
+			.neighbours
+			.iter()
+			.map(|index| map_dynamic.sectors[*index].interval.min);
+
+		let target_height = match params.target {
+			FloorTarget::LowestNeighbourFloor => neighbour_floors
+				.clone()
+				.fold(floor_height, |x, y| f32::min(x, y)),
+			FloorTarget::HighestNeighbourFloor => {
+				neighbour_floors.clone().fold(-500.0, |x, y| f32::max(x, y))
+			}
+			FloorTarget::HighestNeighbourFloorPlus(offset) => {
+				let height = neighbour_floors.clone().fold(-500.0, |x, y| f32::max(x, y));
+
+				if height != floor_height {
+					height + offset
+				} else {
+					height
+				}
+			}
+			FloorTarget::NextNeighbourFloor => neighbour_floors
+				.clone()
+				.filter(|h| *h > floor_height)
+				.min_by(|x, y| x.partial_cmp(y).unwrap())
+				.unwrap_or(floor_height),
+			FloorTarget::LowestNeighbourCeiling(offset) => {
+				sector
+					.neighbours
+					.iter()
+					.map(|index| map_dynamic.sectors[*index].interval.max)
+					.fold(ceiling_height, |x, y| f32::min(x, y))
+					+ offset
+			}
+			FloorTarget::Offset(offset) => floor_height + offset,
+			FloorTarget::ShortestLowerTexture => {
+				// Find the height of the shortest lower texture on the sector's two-sided linedefs
+				let height = sector
+					.linedefs
+					.iter()
+					.map(|index| &map_dynamic.linedefs[*index])
+					.filter(|linedef_dynamic| linedef_dynamic.sidedefs[1].is_some())
+					.flat_map(|linedef_dynamic| linedef_dynamic.sidedefs.iter().flatten())
+					.filter_map(|sidedef_dynamic| {
+						match &sidedef_dynamic.textures[SidedefSlot::Bottom as usize] {
+							TextureType::Normal(texture) => {
+								asset_storage.get(texture).map(|image| image.world_size[1])
+							}
+							_ => None,
+						}
+					})
+					.fold(f32::INFINITY, |x, y| f32::min(x, y));
+
+				if height.is_finite() {
+					floor_height + height
+				} else {
+					floor_height
+				}
+			}
+		};
+
+		// Raising floors take on the floor texture of the sector in front of the linedef straight
+		// away, lowering floors take on the texture of the sector they end up level with
+		let change_texture = if params.change_texture {
+			if target_height >= floor_height {
+				let sidedef = linedef.sidedefs[Side::Right as usize].as_ref().unwrap();
+				let floor_texture = map_dynamic.sectors[sidedef.sector_index].textures
+					[SectorSlot::Floor as usize]
+					.clone();
+				map_dynamic.sectors[i].textures[SectorSlot::Floor as usize] = floor_texture;
+				None
+			} else {
+				sector
+					.neighbours
+					.iter()
+					.find(|index| map_dynamic.sectors[**index].interval.min == target_height)
+					.map(|index| {
+						map_dynamic.sectors[*index].textures[SectorSlot::Floor as usize].clone()
+					})
+			}
+		} else {
+			None
+		};
+
+		command_buffer.add_component(
+			sector_entity,
+			FloorActive {
+				speed: params.speed,
+				target_height,
+				crush: params.crush,
+				change_texture,
+
+				move_sound: params.move_sound.clone(),
+				move_sound_time: Duration::default(),
+				stop_sound: params.stop_sound.clone(),
+				crush_time: Duration::default(),
+			},
+		);
+		used = true;
+	}
+
+	used
+}
+
+// Lowers a pillar in every sector with the same tag as the linedef,
+// and raises the surrounding ring of floor to fill the gap.
+fn activate_donuts(
+	world: &World,
+	donut_use: &DonutSwitchUse,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	command_buffer: &mut CommandBuffer,
+) -> bool {
+	let linedef = &map.linedefs[linedef_index];
+	let mut used = false;
+
+	for (i, sector) in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+	{
+		let sector_entity = map_dynamic.sectors[i].entity;
+
+		if world.has_component::<FloorActive>(sector_entity) {
+			continue;
+		}
+
+		// The ring is on the other side of the pillar's first linedef
+		let ring_index = match sector
+			.linedefs
+			.first()
+			.and_then(|index| other_sector(map, *index, i))
+		{
+			Some(index) => index,
+			None => continue,
+		};
+
+		// The pool is on the other side of the ring
+		let pool_index = match map.sectors[ring_index]
+			.linedefs
+			.iter()
+			.filter_map(|index| other_sector(map, *index, ring_index))
+			.find(|index| *index != i)
+		{
+			Some(index) => index,
+			None => continue,
+		};
+
+		let ring_entity = map_dynamic.sectors[ring_index].entity;
+		let pool_height = map_dynamic.sectors[pool_index].interval.min;
+		let pool_texture =
+			map_dynamic.sectors[pool_index].textures[SectorSlot::Floor as usize].clone();
+
+		command_buffer.add_component(
+			ring_entity,
+			FloorActive {
+				speed: donut_use.speed,
+				target_height: pool_height,
+				crush: false,
+				change_texture: Some(pool_texture),
+
+				move_sound: donut_use.move_sound.clone(),
+				move_sound_time: Duration::default(),
+				stop_sound: donut_use.stop_sound.clone(),
+				crush_time: Duration::default(),
+			},
+		);
+		command_buffer.add_component(
+			sector_entity,
+			FloorActive {
+				speed: donut_use.speed,
+				target_height: pool_height,
+				crush: false,
+				change_texture: None,
+
+				move_sound: donut_use.move_sound.clone(),
+				move_sound_time: Duration::default(),
+				stop_sound: donut_use.stop_sound.clone(),
+				crush_time: Duration::default(),
+			},
+		);
+		used = true;
+	}
+
+	used
+}
+
+// Builds a staircase starting in every sector with the same tag as the linedef.
+// Each step is the sector behind a linedef of the previous step, as long as it has the same floor
+// texture, and rises one step height higher than the previous step.
+fn activate_stairs(
+	world: &World,
+	params: &StairsParams,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &MapDynamic,
+	command_buffer: &mut CommandBuffer,
+) -> bool {
+	let linedef = &map.linedefs[linedef_index];
+	let mut activated: Vec<usize> = Vec::new();
+
+	let is_active = |index: usize, activated: &[usize]| {
+		let sector_entity = map_dynamic.sectors[index].entity;
+
+		activated.contains(&index)
+			|| world.has_component::<CeilingActive>(sector_entity)
+			|| world.has_component::<DoorActive>(sector_entity)
+			|| world.has_component::<FloorActive>(sector_entity)
+			|| world.has_component::<PlatActive>(sector_entity)
+	};
+
+	for start_index in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+		.map(|(i, _)| i)
+	{
+		if is_active(start_index, &activated) {
+			continue;
+		}
+
+		let floor_texture = &map_dynamic.sectors[start_index].textures[SectorSlot::Floor as usize];
+		let mut sector_index = start_index;
+		let mut target_height = map_dynamic.sectors[start_index].interval.min + params.step_height;
+
+		loop {
+			command_buffer.add_component(
+				map_dynamic.sectors[sector_index].entity,
+				FloorActive {
+					speed: params.speed,
+					target_height,
+					crush: params.crush,
+					change_texture: None,
+
+					move_sound: params.move_sound.clone(),
+					move_sound_time: Duration::default(),
+					stop_sound: params.stop_sound.clone(),
+					crush_time: Duration::default(),
+				},
+			);
+			activated.push(sector_index);
+
+			// Find the next step behind one of this step's linedefs
+			let mut next_index = None;
+
+			for sidedefs in map.sectors[sector_index]
+				.linedefs
+				.iter()
+				.map(|index| &map.linedefs[*index].sidedefs)
+			{
+				if let [Some(front_sidedef), Some(back_sidedef)] = sidedefs {
+					if front_sidedef.sector_index != sector_index
+						|| map_dynamic.sectors[back_sidedef.sector_index].textures
+							[SectorSlot::Floor as usize]
+							!= *floor_texture
+					{
+						continue;
+					}
+
+					// Steps that are already moving are skipped, but still count towards the height
+					target_height += params.step_height;
+
+					if is_active(back_sidedef.sector_index, &activated) {
+						continue;
+					}
+
+					next_index = Some(back_sidedef.sector_index);
+					break;
+				}
+			}
+
+			if let Some(index) = next_index {
+				sector_index = index;
+			} else {
+				break;
+			}
+		}
+	}
+
+	!activated.is_empty()
+}
+
+fn other_sector(map: &Map, linedef_index: usize, sector_index: usize) -> Option<usize> {
+	if let [Some(front_sidedef), Some(back_sidedef)] = &map.linedefs[linedef_index].sidedefs {
+		if front_sidedef.sector_index == sector_index {
+			Some(back_sidedef.sector_index)
+		} else {
+			Some(front_sidedef.sector_index)
+		}
+	} else {
+		None
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum FloorTarget {
+	LowestNeighbourFloor,
+	HighestNeighbourFloor,
+	HighestNeighbourFloorPlus(f32),
+	NextNeighbourFloor,
+	LowestNeighbourCeiling(f32),
+	Offset(f32),
+	ShortestLowerTexture,
+}
+
+#[derive(Clone, Debug)]
+pub struct FloorParams {
+	pub target: FloorTarget,
+	pub speed: f32,
+	pub crush: bool,
+	pub change_texture: bool,
+	pub move_sound: AssetHandle<Sound>,
+	pub stop_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FloorSwitchUse {
+	pub params: FloorParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FloorCross {
+	pub params: FloorParams,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FloorShoot {
+	pub params: FloorParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DonutSwitchUse {
+	pub speed: f32,
+	pub move_sound: AssetHandle<Sound>,
+	pub stop_sound: AssetHandle<Sound>,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct StairsParams {
+	pub step_height: f32,
+	pub speed: f32,
+	pub crush: bool,
+	pub move_sound: AssetHandle<Sound>,
+	pub stop_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StairsSwitchUse {
+	pub params: StairsParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct StairsCross {
+	pub params: StairsParams,
+	pub retrigger: bool,
+}
+
+pub fn floor_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(move |world, resources| {
+		let (asset_storage, delta, mut damage_event_channel, mut sound_queue, states) =
+			<(
+				Read<AssetStorage>,
+				Read<Duration>,
+				Write<EventChannel<DamageEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+				Read<States>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for (entity, (sector_ref, mut floor_active)) in unsafe {
+			<(Read<SectorRef>, Write<FloorActive>)>::query().iter_entities_unchecked(world)
+		} {
+			let mut map_dynamic = unsafe {
+				world
+					.get_component_mut_unchecked::<MapDynamic>(sector_ref.map_entity)
+					.unwrap()
+			};
+			let map_dynamic = map_dynamic.as_mut();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+			let floor_height = map_dynamic.sectors[sector_ref.index].interval.min;
+
+			let move_step = if floor_active.target_height > floor_height {
+				f32::min(
+					floor_active.speed * delta.as_secs_f32(),
+					floor_active.target_height - floor_height,
+				)
+			} else {
+				f32::max(
+					-floor_active.speed * delta.as_secs_f32(),
+					floor_active.target_height - floor_height,
+				)
+			};
+
+			// Play the moving sound every 8 tics
+			if let Some(new_time) = floor_active.move_sound_time.checked_sub(*delta) {
+				floor_active.move_sound_time = new_time;
+			} else {
+				floor_active.move_sound_time = 8 * FRAME_TIME;
+				sound_queue.push((floor_active.move_sound.clone(), entity));
+			}
+
+			let blocked = move_floor(
+				world,
+				map,
+				map_dynamic,
+				&states,
+				sector_ref.index,
+				move_step,
+				floor_active.crush,
+			);
+
+			if !blocked.is_empty() {
+				// Crushing floors keep moving, others wait until the way is clear
+				if floor_active.crush {
+					crush_entities(
+						&blocked,
+						&mut floor_active.crush_time,
+						*delta,
+						&mut damage_event_channel,
+					);
+				} else {
+					continue;
+				}
+			}
+
+			if move_step == floor_active.target_height - floor_height {
+				map_dynamic.sectors[sector_ref.index].interval.min = floor_active.target_height;
+
+				if let Some(texture) = floor_active.change_texture.take() {
+					map_dynamic.sectors[sector_ref.index].textures[SectorSlot::Floor as usize] =
+						texture;
+				}
+
+				sound_queue.push((floor_active.stop_sound.clone(), entity));
+				command_buffer.remove_component::<FloorActive>(entity);
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Damages entities that are being crushed, every 4 tics
+pub fn crush_entities(
+	entities: &[Entity],
+	crush_time: &mut Duration,
+	delta: Duration,
+	damage_event_channel: &mut EventChannel<DamageEvent>,
+) {
+	if let Some(new_time) = crush_time.checked_sub(delta) {
+		*crush_time = new_time;
+	} else {
+		*crush_time = 4 * FRAME_TIME;
+		damage_event_channel.iter_write(entities.iter().map(|entity| DamageEvent {
+			source: None,
+			inflictor: None,
+			target: *entity,
+			amount: 10,
+			damage_type: DamageType::Crush,
+		}));
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct FloorActive {
+	pub speed: f32,
+	pub target_height: f32,
+	pub crush: bool,
+	pub change_texture: Option<TextureType<Flat>>,
+
+	pub move_sound: AssetHandle<Sound>,
+	pub move_sound_time: Duration,
+	pub stop_sound: AssetHandle<Sound>,
+	pub crush_time: Duration,
+}
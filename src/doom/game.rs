@@ -0,0 +1,297 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage, DataSource},
+	audio::Sound,
+	doom::{
+		client::{Client, UseAction, UseEvent},
+		components::{Armor, Health},
+		dehacked::Dehacked,
+		door::activate_switch,
+		inventory::{Inventory, Keys},
+		map::{LinedefRef, MapDynamic},
+		physics::{CrossAction, CrossEvent},
+		weapon::{WeaponState, WeaponType},
+	},
+};
+use legion::prelude::{CommandBuffer, Entity, Read, ResourceSet, Resources, World, Write};
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default)]
+pub struct GameSession {
+	pub map: String,
+	pub exit: Option<ExitType>,
+	pub secrets_found: usize,
+	pub secrets_total: usize,
+	pub items_found: usize,
+	pub items_total: usize,
+	pub level_time: Duration,
+	pub par_time: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ExitType {
+	Normal,
+	Secret,
+}
+
+pub fn exit_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut use_event_reader = resources
+		.get_mut::<EventChannel<UseEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, mut game_session, use_event_channel, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Write<GameSession>,
+				Read<EventChannel<UseEvent>>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for use_event in use_event_channel.read(&mut use_event_reader) {
+			if let Some(UseAction::ExitSwitchUse(exit_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Only players can exit the level
+				if client.entity != Some(use_event.entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_switch(
+					use_event.linedef_entity,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&exit_use.switch_sound,
+					None,
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+				command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+
+				game_session.exit = Some(exit_use.exit_type);
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn exit_cross_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (client, cross_event_channel, mut game_session) = <(
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+			Write<GameSession>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			if let Some(CrossAction::ExitCross(exit_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				// Only players can exit the level
+				if client.entity != Some(cross_event.entity) {
+					continue;
+				}
+
+				command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				game_session.exit = Some(exit_cross.exit_type);
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Picks the map that follows the given one, following the vanilla rules.
+// Returns None if the exit ends the episode or the next map doesn't exist.
+pub fn next_map(map: &str, exit_type: ExitType, source: &impl DataSource) -> Option<String> {
+	let map = map.to_ascii_uppercase();
+	let secret = exit_type == ExitType::Secret;
+
+	let next = if let Some((episode, number)) = parse_episode_map(&map) {
+		let number = match (number, secret) {
+			(9, _) => match episode {
+				1 => 4,
+				2 => 6,
+				3 => 7,
+				4 => 3,
+				_ => return None,
+			},
+			(_, true) => 9,
+			(8, false) => return None,
+			(_, false) => number + 1,
+		};
+
+		format!("E{}M{}", episode, number)
+	} else if map.len() == 5 && map.starts_with("MAP") {
+		let number: u32 = map[3..].parse().ok()?;
+		let has_map = |number: u32| {
+			let name = format!("MAP{:02}", number);
+			source.names().any(|n| n == name)
+		};
+
+		let number = match (number, secret) {
+			(15, true) if has_map(31) => 31,
+			(31, true) if has_map(32) => 32,
+			(31, _) | (32, _) => 16,
+			(30, _) => return None,
+			_ => number + 1,
+		};
+
+		format!("MAP{:02}", number)
+	} else {
+		return None;
+	};
+
+	if source.names().any(|n| n == next) {
+		Some(next)
+	} else {
+		None
+	}
+}
+
+fn parse_episode_map(map: &str) -> Option<(u32, u32)> {
+	let bytes = map.as_bytes();
+
+	if bytes.len() == 4 && bytes[0] == b'E' && bytes[2] == b'M' {
+		let episode = (bytes[1] as char).to_digit(10)?;
+		let number = (bytes[3] as char).to_digit(10)?;
+		Some((episode, number))
+	} else {
+		None
+	}
+}
+
+// Par times from the original games, which DeHackEd patches can override
+pub fn par_time(map: &str, dehacked: &Dehacked) -> Option<Duration> {
+	let map = map.to_ascii_uppercase();
+
+	if let Some(&par_time) = dehacked.par_times.get(&map) {
+		return Some(par_time);
+	}
+
+	let seconds = if let Some((episode, number)) = parse_episode_map(&map) {
+		*PARS
+			.get(episode.checked_sub(1)? as usize)?
+			.get(number.checked_sub(1)? as usize)?
+	} else if map.len() == 5 && map.starts_with("MAP") {
+		let number: usize = map[3..].parse().ok()?;
+		*CPARS.get(number.checked_sub(1)?)?
+	} else {
+		return None;
+	};
+
+	Some(Duration::from_secs(seconds))
+}
+
+pub fn format_time(time: Duration) -> String {
+	let seconds = time.as_secs();
+	format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[rustfmt::skip]
+const PARS: [[u64; 9]; 3] = [
+	[30, 75, 120, 90, 165, 180, 180, 30, 165],
+	[90, 90, 90, 120, 90, 360, 240, 30, 170],
+	[90, 45, 90, 150, 90, 90, 165, 30, 135],
+];
+
+#[rustfmt::skip]
+const CPARS: [u64; 32] = [
+	30, 90, 120, 120, 90, 150, 120, 120, 270, 90,
+	210, 150, 150, 150, 210, 150, 420, 150, 210, 150,
+	240, 150, 180, 150, 150, 300, 330, 420, 300, 180,
+	120, 30,
+];
+
+// Player state that survives going to the next map
+#[derive(Clone, Debug)]
+pub struct PlayerCarry {
+	pub armor: Option<Armor>,
+	pub health: Option<Health>,
+	pub inventory: Option<Inventory>,
+	pub weapon: Option<WeaponType>,
+}
+
+impl PlayerCarry {
+	pub fn from_entity(world: &World, entity: Entity) -> PlayerCarry {
+		let mut inventory = world.get_component::<Inventory>(entity).as_deref().copied();
+
+		// Keys only work in the map they were found in
+		if let Some(inventory) = &mut inventory {
+			inventory.keys = Keys::empty();
+		}
+
+		PlayerCarry {
+			armor: world.get_component::<Armor>(entity).as_deref().copied(),
+			health: world.get_component::<Health>(entity).as_deref().copied(),
+			inventory,
+			weapon: world
+				.get_component::<WeaponState>(entity)
+				.map(|weapon_state| weapon_state.current),
+		}
+	}
+
+	pub fn apply(self, world: &mut World, entity: Entity) {
+		if let Some(armor) = self.armor {
+			if let Some(mut component) = world.get_component_mut::<Armor>(entity) {
+				*component = armor;
+			}
+		}
+
+		if let Some(health) = self.health {
+			if let Some(mut component) = world.get_component_mut::<Health>(entity) {
+				*component = health;
+			}
+		}
+
+		if let Some(inventory) = self.inventory {
+			if let Some(mut component) = world.get_component_mut::<Inventory>(entity) {
+				*component = inventory;
+			}
+		}
+
+		if let Some(weapon) = self.weapon {
+			if let Some(mut component) = world.get_component_mut::<WeaponState>(entity) {
+				*component = WeaponState::new(weapon);
+			}
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct ExitSwitchUse {
+	pub exit_type: ExitType,
+	pub switch_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExitCross {
+	pub exit_type: ExitType,
+}
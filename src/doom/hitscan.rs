@@ -0,0 +1,1011 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		client::{ShootAction, ShootEvent},
+		components::{Health, Transform},
+		damage::{DamageEvent, DamageType},
+		data::{MobjTypes, States, WeaponTypes, FRAME_TIME},
+		inventory::Powers,
+		map::{MapDynamic, NodeChild, SectorSlot},
+		monster,
+		physics::{BoxCollider, EntityTracer},
+		state::{StateAction, StateActionEvent, StateMachine},
+		weapon::WeaponState,
+	},
+	geometry::{Angle, Interval, Line2, AABB2, AABB3},
+	quadtree::Quadtree,
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use nalgebra::{Vector2, Vector3};
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use shrev::EventChannel;
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+
+pub const MELEE_RANGE: f32 = 64.0;
+pub const MISSILE_RANGE: f32 = 2048.0;
+
+// Range of the player's autoaim
+pub const AUTOAIM_RANGE: f32 = 1024.0;
+
+// Vanilla's vertical autoaim limit, as a slope
+const AIM_SLOPE: f32 = 100.0 / 160.0;
+
+pub fn hitscan_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut state_action_event_reader = resources
+		.get_mut::<EventChannel<StateActionEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (
+			asset_storage,
+			mut damage_event_channel,
+			entity_types,
+			quadtree,
+			mut rng,
+			mut shoot_event_channel,
+			mut sound_queue,
+			state_action_event_channel,
+			states,
+			weapon_types,
+		) = <(
+			Read<AssetStorage>,
+			Write<EventChannel<DamageEvent>>,
+			Read<MobjTypes>,
+			Read<Quadtree>,
+			Write<Pcg64Mcg>,
+			Write<EventChannel<ShootEvent>>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Read<EventChannel<StateActionEvent>>,
+			Read<States>,
+			Read<WeaponTypes>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		{
+			let map_dynamic = <Read<MapDynamic>>::query().iter(world).next().unwrap();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+			let mut context = HitscanContext {
+				tracer: EntityTracer {
+					map,
+					map_dynamic: map_dynamic.as_ref(),
+					quadtree: &quadtree,
+					world,
+				},
+				asset_storage: &asset_storage,
+				command_buffer: &mut command_buffer,
+				damage_event_channel: &mut damage_event_channel,
+				entity_types: &entity_types,
+				rng: &mut rng,
+				shoot_event_channel: &mut shoot_event_channel,
+				states: &states,
+			};
+
+			for state_action_event in
+				state_action_event_channel.read(&mut state_action_event_reader)
+			{
+				let entity = state_action_event.entity;
+				let (start, rotation) = match shoot_origin(world, entity) {
+					Some(x) => x,
+					None => continue,
+				};
+				let yaw = rotation[2];
+
+				match state_action_event.action {
+					StateAction::Punch => {
+						let mut damage = (context.rng.gen::<u8>() % 10 + 1) as i32 * 2;
+
+						if world
+							.get_component::<Powers>(entity)
+							.map_or(false, |powers| powers.strength.is_some())
+						{
+							damage *= 10;
+						}
+
+						let yaw = yaw + spread(context.rng, 18);
+						let slope = aim(&context.tracer, entity, start, yaw, MELEE_RANGE)
+							.map_or(0.0, |(_, slope)| slope);
+
+						if let Some(target) =
+							context.line_attack(entity, start, yaw, slope, MELEE_RANGE, damage)
+						{
+							sound_queue.push((weapon_types.sounds["DSPUNCH"].clone(), entity));
+							turn_towards(world, entity, target, None);
+						}
+					}
+					StateAction::Saw => {
+						let damage = (context.rng.gen::<u8>() % 10 + 1) as i32 * 2;
+						let yaw = yaw + spread(context.rng, 18);
+
+						// Reaches slightly further than the fist, so that puffs still spark
+						let range = MELEE_RANGE + 1.0;
+						let slope = aim(&context.tracer, entity, start, yaw, range)
+							.map_or(0.0, |(_, slope)| slope);
+
+						if let Some(target) =
+							context.line_attack(entity, start, yaw, slope, range, damage)
+						{
+							sound_queue.push((weapon_types.sounds["DSSAWHIT"].clone(), entity));
+							turn_towards(
+								world,
+								entity,
+								target,
+								Some(Angle::from_degrees(90.0 / 20.0)),
+							);
+						} else {
+							sound_queue.push((weapon_types.sounds["DSSAWFUL"].clone(), entity));
+						}
+					}
+					StateAction::FirePistol | StateAction::FireCGun => {
+						let slope = autoaim_slope(&context.tracer, entity, start, rotation);
+
+						// The first shot is accurate, held fire spreads out
+						let accurate = world
+							.get_component::<WeaponState>(entity)
+							.map_or(true, |weapon_state| weapon_state.refire == 0);
+						context.gun_shot(entity, start, yaw, slope, accurate);
+					}
+					StateAction::FireShotgun => {
+						let slope = autoaim_slope(&context.tracer, entity, start, rotation);
+
+						for _ in 0..7 {
+							context.gun_shot(entity, start, yaw, slope, false);
+						}
+					}
+					StateAction::FireShotgun2 => {
+						let slope = autoaim_slope(&context.tracer, entity, start, rotation);
+
+						for _ in 0..20 {
+							let damage = 5 * (context.rng.gen::<u8>() % 3 + 1) as i32;
+							let yaw = yaw + spread(context.rng, 19);
+							let slope = slope + spread(context.rng, 5) as f32 / 65536.0;
+							context.line_attack(entity, start, yaw, slope, MISSILE_RANGE, damage);
+						}
+					}
+					StateAction::PosAttack | StateAction::SPosAttack | StateAction::CPosAttack => {
+						let (sound, count) = match state_action_event.action {
+							StateAction::PosAttack => ("DSPISTOL", 1),
+							StateAction::SPosAttack => ("DSSHOTGN", 3),
+							_ => ("DSSHOTGN", 1),
+						};
+						sound_queue.push((weapon_types.sounds[sound].clone(), entity));
+
+						monster::face_target(world, context.rng, entity);
+						let yaw = world.get_component::<Transform>(entity).unwrap().rotation[2];
+						let slope = aim(&context.tracer, entity, start, yaw, MISSILE_RANGE)
+							.map_or(0.0, |(_, slope)| slope);
+
+						for _ in 0..count {
+							let damage = 3 * (context.rng.gen::<u8>() % 5 + 1) as i32;
+							let yaw = yaw + spread(context.rng, 20);
+							context.line_attack(entity, start, yaw, slope, MISSILE_RANGE, damage);
+						}
+					}
+					_ => {}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+struct HitscanContext<'a> {
+	tracer: EntityTracer<'a>,
+	asset_storage: &'a AssetStorage,
+	command_buffer: &'a mut CommandBuffer,
+	damage_event_channel: &'a mut EventChannel<DamageEvent>,
+	entity_types: &'a MobjTypes,
+	rng: &'a mut Pcg64Mcg,
+	shoot_event_channel: &'a mut EventChannel<ShootEvent>,
+	states: &'a States,
+}
+
+impl<'a> HitscanContext<'a> {
+	fn gun_shot(
+		&mut self,
+		shooter: Entity,
+		start: Vector3<f32>,
+		yaw: Angle,
+		slope: f32,
+		accurate: bool,
+	) {
+		let damage = 5 * (self.rng.gen::<u8>() % 3 + 1) as i32;
+		let yaw = if accurate {
+			yaw
+		} else {
+			yaw + spread(self.rng, 18)
+		};
+
+		self.line_attack(shooter, start, yaw, slope, MISSILE_RANGE, damage);
+	}
+
+	// Fires a single hitscan and applies its effects. Returns the entity that was hit, if any.
+	fn line_attack(
+		&mut self,
+		shooter: Entity,
+		start: Vector3<f32>,
+		yaw: Angle,
+		slope: f32,
+		range: f32,
+		damage: i32,
+	) -> Option<Entity> {
+		let trace = trace_hitscan(&self.tracer, shooter, start, yaw, slope, range);
+
+		// Shooting a linedef can trigger it, even if the shot passes through
+		for linedef_index in trace.linedefs.iter().copied() {
+			let linedef_entity = self.tracer.map_dynamic.linedefs[linedef_index].entity;
+
+			if self
+				.tracer
+				.world
+				.has_component::<ShootAction>(linedef_entity)
+			{
+				self.shoot_event_channel.single_write(ShootEvent {
+					entity: shooter,
+					linedef_entity,
+				});
+			}
+		}
+
+		match trace.collision {
+			Some(HitscanCollision::Entity(target)) => {
+				let no_blood = self
+					.tracer
+					.world
+					.get_component::<Health>(target)
+					.map_or(true, |health| health.no_blood);
+
+				if no_blood {
+					self.spawn_puff(trace.position, range <= MELEE_RANGE);
+				} else {
+					self.spawn_blood(trace.position, damage);
+				}
+
+				self.damage_event_channel.single_write(DamageEvent {
+					source: Some(shooter),
+					inflictor: Some(shooter),
+					target,
+					amount: damage,
+					damage_type: DamageType::Hitscan,
+				});
+
+				Some(target)
+			}
+			Some(HitscanCollision::World) => {
+				self.spawn_puff(trace.position, range <= MELEE_RANGE);
+				None
+			}
+			Some(HitscanCollision::Sky) | None => None,
+		}
+	}
+
+	fn spawn_puff(&mut self, position: Vector3<f32>, melee: bool) {
+		// Melee puffs don't spark
+		self.spawn("PUFF", position, if melee { Some("PUFF3") } else { None });
+	}
+
+	fn spawn_blood(&mut self, position: Vector3<f32>, damage: i32) {
+		// Less damage makes less blood
+		let state = if damage < 9 {
+			Some("BLOOD3")
+		} else if damage <= 12 {
+			Some("BLOOD2")
+		} else {
+			None
+		};
+
+		self.spawn("BLOOD", position, state);
+	}
+
+	fn spawn(&mut self, name: &str, mut position: Vector3<f32>, state: Option<&str>) {
+		let asset_storage = self.asset_storage;
+		let template = asset_storage.get(&self.entity_types.names[name]).unwrap();
+
+		let entity = self.command_buffer.insert((), vec![()])[0];
+		template.add_to_entity(entity, self.command_buffer);
+
+		position[2] += (self.rng.gen::<u8>() as f32 - self.rng.gen::<u8>() as f32) / 64.0;
+		self.command_buffer.add_component(
+			entity,
+			Transform {
+				position,
+				rotation: Vector3::zeros(),
+			},
+		);
+
+		if let Some(state_machine) = template.get_component::<StateMachine>() {
+			let mut state_machine = *state_machine;
+
+			// Vary the length of the first frame a little
+			let random_time = FRAME_TIME * (self.rng.gen::<u8>() & 3) as u32;
+			state_machine.time_left = state_machine.time_left.map(|time| {
+				time.checked_sub(random_time)
+					.unwrap_or(FRAME_TIME)
+					.max(FRAME_TIME)
+			});
+
+			if let Some(state) = state {
+				state_machine.next = Some(self.states.names[state]);
+			}
+
+			self.command_buffer.add_component(entity, state_machine);
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct HitscanTrace {
+	// Where the shot hit, or the end of the line if it hit nothing
+	pub position: Vector3<f32>,
+	pub collision: Option<HitscanCollision>,
+	// Linedefs that the shot crossed or hit, in order
+	pub linedefs: SmallVec<[usize; 4]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitscanCollision {
+	Entity(Entity),
+	// A wall, floor or ceiling
+	World,
+	// Shots into the sky disappear without a puff
+	Sky,
+}
+
+// Attacks start a little above the middle of the attacker
+pub fn shoot_origin(world: &World, entity: Entity) -> Option<(Vector3<f32>, Vector3<Angle>)> {
+	let transform = world.get_component::<Transform>(entity)?;
+	let box_collider = world.get_component::<BoxCollider>(entity)?;
+	let start = transform.position + Vector3::new(0.0, 0.0, box_collider.height * 0.5 + 8.0);
+
+	Some((start, transform.rotation))
+}
+
+// A random angle, biased towards zero, with a magnitude set by shifting
+fn spread(rng: &mut Pcg64Mcg, shift: u32) -> i32 {
+	(rng.gen::<u8>() as i32 - rng.gen::<u8>() as i32) << shift
+}
+
+// Finds the slope for a player to fire at, aiming at anything slightly to either side too
+pub fn autoaim_slope(
+	tracer: &EntityTracer,
+	shooter: Entity,
+	start: Vector3<f32>,
+	rotation: Vector3<Angle>,
+) -> f32 {
+	for &offset in [0, 1 << 26, -(1 << 26)].iter() {
+		if let Some((_, slope)) = aim(tracer, shooter, start, rotation[2] + offset, AUTOAIM_RANGE) {
+			return slope;
+		}
+	}
+
+	// Nothing to aim at, so shoot where the player is looking
+	-rotation[1].tan() as f32
+}
+
+// Turns an attacker to face its target, optionally turning no more than a given angle
+fn turn_towards(world: &World, entity: Entity, target: Entity, max_turn: Option<Angle>) {
+	let target_position = match world.get_component::<Transform>(target) {
+		Some(transform) => transform.position,
+		None => return,
+	};
+	let mut transform = match unsafe { world.get_component_mut_unchecked::<Transform>(entity) } {
+		Some(x) => x,
+		None => return,
+	};
+
+	let delta = target_position - transform.position;
+	let angle = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64));
+
+	transform.rotation[2] = match max_turn {
+		Some(max_turn) => {
+			let turn = angle - transform.rotation[2];
+
+			if turn.0 > max_turn.0 {
+				transform.rotation[2] + max_turn
+			} else if turn.0 < -max_turn.0 {
+				transform.rotation[2] - max_turn
+			} else {
+				angle
+			}
+		}
+		None => angle,
+	};
+}
+
+// Searches for a shootable entity in front of the shooter, within vanilla's vertical autoaim
+// range. Returns the entity and the slope to aim at it.
+pub fn aim(
+	tracer: &EntityTracer,
+	shooter: Entity,
+	start: Vector3<f32>,
+	yaw: Angle,
+	range: f32,
+) -> Option<(Entity, f32)> {
+	let start2 = Vector2::new(start[0], start[1]);
+	let move_step = Vector2::new(yaw.cos() as f32, yaw.sin() as f32) * range;
+	let mut slopes = Interval::new(-AIM_SLOPE, AIM_SLOPE);
+
+	for (fraction, intercept) in find_intercepts(tracer, shooter, start2, move_step) {
+		match intercept {
+			Intercept::Linedef(linedef_index) => {
+				// Can't aim through one-sided linedefs
+				let opening = linedef_opening(tracer, linedef_index)?;
+
+				if opening.is_empty_or_point() {
+					return None;
+				}
+
+				// Narrow the range to what can be seen through the opening
+				let distance = fraction * range;
+				slopes = slopes.intersection(Interval::new(
+					(opening.min - start[2]) / distance,
+					(opening.max - start[2]) / distance,
+				));
+
+				if slopes.is_empty_or_point() {
+					return None;
+				}
+			}
+			Intercept::Entity(entity) => {
+				if !is_shootable(tracer.world, entity) {
+					continue;
+				}
+
+				let entity_bbox = match entity_bbox(tracer.world, entity) {
+					Some(x) => x,
+					None => continue,
+				};
+
+				let middle = entity_bbox.middle();
+				let distance = (Vector2::new(middle[0], middle[1]) - start2).norm();
+				let entity_slopes = Interval::new(
+					(entity_bbox[2].min - start[2]) / distance,
+					(entity_bbox[2].max - start[2]) / distance,
+				);
+
+				// Over or under the line of sight
+				if !entity_slopes.overlaps(slopes) {
+					continue;
+				}
+
+				return Some((entity, entity_slopes.intersection(slopes).middle()));
+			}
+		}
+	}
+
+	None
+}
+
+// Checks whether any part of an entity's box can be seen from a point,
+// looking through the openings of two-sided linedefs in between
+pub fn check_sight(
+	tracer: &EntityTracer,
+	viewer: Entity,
+	start: Vector3<f32>,
+	target_bbox: &AABB3,
+) -> bool {
+	let start2 = Vector2::new(start[0], start[1]);
+	let middle = target_bbox.middle();
+	let move_step = Vector2::new(middle[0], middle[1]) - start2;
+	let distance = move_step.norm();
+
+	if distance == 0.0 {
+		return true;
+	}
+
+	let mut slopes = Interval::new(
+		(target_bbox[2].min - start[2]) / distance,
+		(target_bbox[2].max - start[2]) / distance,
+	);
+
+	for (fraction, intercept) in find_intercepts(tracer, viewer, start2, move_step) {
+		let linedef_index = match intercept {
+			Intercept::Linedef(x) => x,
+			Intercept::Entity(_) => continue,
+		};
+
+		let opening = match linedef_opening(tracer, linedef_index) {
+			Some(x) => x,
+			None => return false,
+		};
+
+		if opening.is_empty_or_point() {
+			return false;
+		}
+
+		let distance = fraction * distance;
+		slopes = slopes.intersection(Interval::new(
+			(opening.min - start[2]) / distance,
+			(opening.max - start[2]) / distance,
+		));
+
+		if slopes.is_empty() {
+			return false;
+		}
+	}
+
+	true
+}
+
+// Traces a hitscan attack in a straight line, until it hits a wall, a floor or ceiling,
+// or a shootable entity
+pub fn trace_hitscan(
+	tracer: &EntityTracer,
+	shooter: Entity,
+	start: Vector3<f32>,
+	yaw: Angle,
+	slope: f32,
+	range: f32,
+) -> HitscanTrace {
+	let start2 = Vector2::new(start[0], start[1]);
+	let move_step2 = Vector2::new(yaw.cos() as f32, yaw.sin() as f32) * range;
+	let move_step = Vector3::new(move_step2[0], move_step2[1], slope * range);
+	let mut sector_index = tracer.map.find_subsector(start2).sector_index;
+	let mut linedefs = SmallVec::new();
+
+	// Entities are hit where the line enters their box
+	let mut intercepts: Vec<(f32, Intercept)> =
+		find_intercepts(tracer, shooter, start2, move_step2)
+			.into_iter()
+			.filter_map(|(fraction, intercept)| match intercept {
+				Intercept::Entity(entity) => {
+					if !is_shootable(tracer.world, entity) {
+						return None;
+					}
+
+					let entity_bbox = entity_bbox(tracer.world, entity)?;
+					let fractions = clip_line(
+						entity_bbox.vector().as_slice(),
+						start.as_slice(),
+						move_step.as_slice(),
+					);
+
+					if fractions.is_empty() {
+						None
+					} else {
+						Some((fractions.min, intercept))
+					}
+				}
+				Intercept::Linedef(_) => Some((fraction, intercept)),
+			})
+			.collect();
+	intercepts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+	for (fraction, intercept) in intercepts {
+		// Did the shot leave through the floor or ceiling before getting here?
+		if let Some((fraction, collision)) =
+			plane_collision(tracer, sector_index, start, move_step, fraction)
+		{
+			return HitscanTrace {
+				position: start + move_step * fraction,
+				collision: Some(collision),
+				linedefs,
+			};
+		}
+
+		match intercept {
+			Intercept::Linedef(linedef_index) => {
+				let linedef = &tracer.map.linedefs[linedef_index];
+				let z = start[2] + move_step[2] * fraction;
+				linedefs.push(linedef_index);
+
+				// Puffs are moved back a little so they're in front of the wall
+				let position = start + move_step * (fraction - 4.0 / range).max(0.0);
+
+				if let [Some(front_sidedef), Some(back_sidedef)] = &linedef.sidedefs {
+					let front_sector = &tracer.map_dynamic.sectors[front_sidedef.sector_index];
+					let back_sector = &tracer.map_dynamic.sectors[back_sidedef.sector_index];
+					let opening = front_sector.interval.intersection(back_sector.interval);
+
+					// Through the opening, into the sector on the other side
+					if z >= opening.min && z <= opening.max {
+						sector_index = if (start2 - linedef.line.point).dot(&linedef.normal) > 0.0 {
+							back_sidedef.sector_index
+						} else {
+							front_sidedef.sector_index
+						};
+						continue;
+					}
+
+					// Shots hitting the upper part between two skies go into the sky
+					let sky = z > opening.max
+						&& front_sector.textures[SectorSlot::Ceiling as usize].is_sky()
+						&& back_sector.textures[SectorSlot::Ceiling as usize].is_sky();
+
+					return HitscanTrace {
+						position,
+						collision: Some(if sky {
+							HitscanCollision::Sky
+						} else {
+							HitscanCollision::World
+						}),
+						linedefs,
+					};
+				}
+
+				return HitscanTrace {
+					position,
+					collision: Some(HitscanCollision::World),
+					linedefs,
+				};
+			}
+			Intercept::Entity(entity) => {
+				return HitscanTrace {
+					position: start + move_step * fraction,
+					collision: Some(HitscanCollision::Entity(entity)),
+					linedefs,
+				};
+			}
+		}
+	}
+
+	if let Some((fraction, collision)) =
+		plane_collision(tracer, sector_index, start, move_step, 1.0)
+	{
+		return HitscanTrace {
+			position: start + move_step * fraction,
+			collision: Some(collision),
+			linedefs,
+		};
+	}
+
+	HitscanTrace {
+		position: start + move_step,
+		collision: None,
+		linedefs,
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Intercept {
+	Linedef(usize),
+	Entity(Entity),
+}
+
+// Finds all linedefs and entities that a horizontal line passes, sorted by distance
+fn find_intercepts(
+	tracer: &EntityTracer,
+	shooter: Entity,
+	start: Vector2<f32>,
+	move_step: Vector2<f32>,
+) -> Vec<(f32, Intercept)> {
+	let line = Line2::new(start, move_step);
+	let end = start + move_step;
+	let bbox = AABB2::from_extents(
+		f32::max(start[1], end[1]),
+		f32::min(start[1], end[1]),
+		f32::min(start[0], end[0]),
+		f32::max(start[0], end[0]),
+	);
+	let mut intercepts = Vec::new();
+
+	tracer
+		.map
+		.traverse_nodes(NodeChild::Node(0), &bbox, &mut |node: NodeChild| {
+			let linedefs = match node {
+				NodeChild::Subsector(index) => &tracer.map.subsectors[index].linedefs,
+				NodeChild::Node(index) => &tracer.map.nodes[index].linedefs,
+			};
+
+			for linedef_index in linedefs.iter().copied() {
+				let linedef = &tracer.map.linedefs[linedef_index];
+
+				if !bbox.overlaps(&linedef.bbox) {
+					continue;
+				}
+
+				if let Some((linedef_p, line_p)) = linedef.line.intersect(&line) {
+					if linedef_p >= 0.0 && linedef_p <= 1.0 && line_p >= 0.0 && line_p <= 1.0 {
+						intercepts.push((line_p, Intercept::Linedef(linedef_index)));
+					}
+				}
+			}
+		});
+
+	tracer
+		.quadtree
+		.traverse_nodes(&bbox, &mut |entities: &[Entity]| {
+			for entity in entities.iter().copied() {
+				if entity == shooter {
+					continue;
+				}
+
+				let entity_bbox = match entity_bbox(tracer.world, entity) {
+					Some(x) => AABB2::from(&x),
+					None => continue,
+				};
+
+				let fractions = clip_line(
+					entity_bbox.vector().as_slice(),
+					start.as_slice(),
+					move_step.as_slice(),
+				);
+
+				if !fractions.is_empty() {
+					intercepts.push((fractions.min, Intercept::Entity(entity)));
+				}
+			}
+		});
+
+	intercepts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+	intercepts
+}
+
+// The vertical range that can be passed through a linedef, or None if it's one-sided
+fn linedef_opening(tracer: &EntityTracer, linedef_index: usize) -> Option<Interval> {
+	match &tracer.map.linedefs[linedef_index].sidedefs {
+		[Some(front_sidedef), Some(back_sidedef)] => {
+			let front_interval = tracer.map_dynamic.sectors[front_sidedef.sector_index].interval;
+			let back_interval = tracer.map_dynamic.sectors[back_sidedef.sector_index].interval;
+			Some(front_interval.intersection(back_interval))
+		}
+		_ => None,
+	}
+}
+
+// Checks whether a line leaves a sector through its floor or ceiling before reaching the given
+// fraction, and if so, where
+fn plane_collision(
+	tracer: &EntityTracer,
+	sector_index: usize,
+	start: Vector3<f32>,
+	move_step: Vector3<f32>,
+	fraction: f32,
+) -> Option<(f32, HitscanCollision)> {
+	let sector_dynamic = &tracer.map_dynamic.sectors[sector_index];
+	let z = start[2] + move_step[2] * fraction;
+
+	if z < sector_dynamic.interval.min {
+		Some((
+			(sector_dynamic.interval.min - start[2]) / move_step[2],
+			HitscanCollision::World,
+		))
+	} else if z > sector_dynamic.interval.max {
+		let collision = if sector_dynamic.textures[SectorSlot::Ceiling as usize].is_sky() {
+			HitscanCollision::Sky
+		} else {
+			HitscanCollision::World
+		};
+
+		Some((
+			(sector_dynamic.interval.max - start[2]) / move_step[2],
+			collision,
+		))
+	} else {
+		None
+	}
+}
+
+// Returns the range of fractions along a line that are inside a box
+fn clip_line(bbox: &[Interval], start: &[f32], move_step: &[f32]) -> Interval {
+	let mut fractions = Interval::new(0.0, 1.0);
+
+	for ((interval, start), move_step) in bbox.iter().zip(start).zip(move_step) {
+		if *move_step == 0.0 {
+			if *start < interval.min || *start > interval.max {
+				return Interval::empty();
+			}
+		} else {
+			let min = (interval.min - start) / move_step;
+			let max = (interval.max - start) / move_step;
+			fractions = fractions.intersection(Interval::new(min.min(max), min.max(max)));
+		}
+	}
+
+	fractions
+}
+
+// Only living things can be shot
+pub fn is_shootable(world: &World, entity: Entity) -> bool {
+	world
+		.get_component::<Health>(entity)
+		.map_or(false, |health| health.current > 0)
+}
+
+pub fn entity_bbox(world: &World, entity: Entity) -> Option<AABB3> {
+	let transform = world.get_component::<Transform>(entity)?;
+	let box_collider = world.get_component::<BoxCollider>(entity)?;
+
+	Some(
+		AABB3::from_radius_height(box_collider.radius, box_collider.height)
+			.offset(transform.position),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::doom::{
+		map::test_map::{TestMap, TestSector},
+		physics::SolidMask,
+	};
+
+	fn collider() -> BoxCollider {
+		BoxCollider {
+			height: 56.0,
+			radius: 16.0,
+			solid_mask: SolidMask::all(),
+		}
+	}
+
+	// Returns the shooter and where it shoots from
+	fn spawn_shooter(test_map: &mut TestMap, position: Vector3<f32>) -> (Entity, Vector3<f32>) {
+		let shooter = test_map.spawn(position, collider(), None);
+		(shooter, position + Vector3::new(0.0, 0.0, 36.0))
+	}
+
+	#[test]
+	fn hits_wall() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+
+		let trace = test_map.with_tracer(|tracer| {
+			trace_hitscan(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				0.0,
+				MISSILE_RANGE,
+			)
+		});
+
+		assert_eq!(trace.collision, Some(HitscanCollision::World));
+		assert!((trace.position[0] - 508.0).abs() < 0.01);
+		assert!((trace.position[2] - 36.0).abs() < 0.01);
+		assert_eq!(trace.linedefs.len(), 2);
+	}
+
+	#[test]
+	fn hits_lower_wall() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 64.0, 128.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+
+		let trace = test_map.with_tracer(|tracer| {
+			trace_hitscan(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				0.0,
+				MISSILE_RANGE,
+			)
+		});
+
+		assert_eq!(trace.collision, Some(HitscanCollision::World));
+		assert!((trace.position[0] - 252.0).abs() < 0.01);
+		assert_eq!(trace.linedefs.len(), 1);
+	}
+
+	#[test]
+	fn hits_floor() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+
+		let trace = test_map.with_tracer(|tracer| {
+			trace_hitscan(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				-0.25,
+				MISSILE_RANGE,
+			)
+		});
+
+		assert_eq!(trace.collision, Some(HitscanCollision::World));
+		assert!((trace.position[0] - 208.0).abs() < 0.01);
+		assert!(trace.position[2].abs() < 0.01);
+	}
+
+	#[test]
+	fn disappears_into_sky() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0).with_sky(),
+			TestSector::new(256.0, 0.0, 128.0).with_sky(),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+
+		let trace = test_map.with_tracer(|tracer| {
+			trace_hitscan(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				0.5,
+				MISSILE_RANGE,
+			)
+		});
+
+		assert_eq!(trace.collision, Some(HitscanCollision::Sky));
+	}
+
+	#[test]
+	fn hits_shootable_entities_only() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+		let _corpse = test_map.spawn(Vector3::new(160.0, 0.0, 0.0), collider(), Some(0));
+		let target = test_map.spawn(Vector3::new(320.0, 0.0, 0.0), collider(), Some(20));
+
+		let trace = test_map.with_tracer(|tracer| {
+			trace_hitscan(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				0.0,
+				MISSILE_RANGE,
+			)
+		});
+
+		assert_eq!(trace.collision, Some(HitscanCollision::Entity(target)));
+		assert!((trace.position[0] - 304.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn autoaims_at_target_on_ledge() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 64.0, 192.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+		let target = test_map.spawn(Vector3::new(400.0, 0.0, 64.0), collider(), Some(20));
+		let yaw = Angle::from_degrees(0.0);
+
+		let (aimed, slope) = test_map
+			.with_tracer(|tracer| aim(tracer, shooter, start, yaw, AUTOAIM_RANGE))
+			.unwrap();
+		assert_eq!(aimed, target);
+
+		// Shooting at the found slope hits the target, straight ahead hits the ledge
+		let trace = test_map
+			.with_tracer(|tracer| trace_hitscan(tracer, shooter, start, yaw, slope, MISSILE_RANGE));
+		assert_eq!(trace.collision, Some(HitscanCollision::Entity(target)));
+
+		let trace = test_map
+			.with_tracer(|tracer| trace_hitscan(tracer, shooter, start, yaw, 0.0, MISSILE_RANGE));
+		assert_eq!(trace.collision, Some(HitscanCollision::World));
+	}
+
+	#[test]
+	fn cannot_aim_through_closed_sector() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(64.0, 0.0, 0.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let (shooter, start) = spawn_shooter(&mut test_map, Vector3::new(64.0, 0.0, 0.0));
+		test_map.spawn(Vector3::new(480.0, 0.0, 0.0), collider(), Some(20));
+
+		let aimed = test_map.with_tracer(|tracer| {
+			aim(
+				tracer,
+				shooter,
+				start,
+				Angle::from_degrees(0.0),
+				AUTOAIM_RANGE,
+			)
+		});
+		assert!(aimed.is_none());
+	}
+}
@@ -1,8 +1,9 @@
 use crate::assets::{Asset, AssetFormat, DataSource};
+use anyhow::ensure;
 use byteorder::{ReadBytesExt, LE};
 use std::{
 	io::{Cursor, Read, Seek, SeekFrom},
-	ops::Deref,
+	ops::{Deref, RangeInclusive},
 };
 
 #[derive(Copy, Clone, Default)]
@@ -31,28 +32,165 @@ impl Deref for Palette {
 	}
 }
 
-impl Asset for Palette {
+pub struct Palettes {
+	palettes: Vec<Palette>,
+	tints: Vec<[f32; 4]>,
+}
+
+impl Palettes {
+	pub fn tint(&self, index: usize) -> [f32; 4] {
+		self.tints[index]
+	}
+}
+
+impl Deref for Palettes {
+	type Target = [Palette];
+
+	fn deref(&self) -> &Self::Target {
+		&self.palettes
+	}
+}
+
+impl Asset for Palettes {
 	type Data = Self;
 	type Intermediate = Self;
-	const NAME: &'static str = "Palette";
+	const NAME: &'static str = "Palettes";
 
 	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
-		let mut reader = Cursor::new(source.load(name)?);
-		let mut palette = [RGBAColor {
-			r: 0,
-			g: 0,
-			b: 0,
-			a: 0,
-		}; 256];
-
-		for color in palette.iter_mut() {
-			let r = reader.read_u8()?;
-			let g = reader.read_u8()?;
-			let b = reader.read_u8()?;
-			*color = RGBAColor { r, g, b, a: 0xFF };
+		let data = source.load(name)?;
+		ensure!(
+			data.len() >= 768 && data.len() % 768 == 0,
+			"Palette lump has invalid size {}",
+			data.len()
+		);
+
+		let mut reader = Cursor::new(data);
+		let count = reader.get_ref().len() / 768;
+		let mut palettes = Vec::with_capacity(count);
+
+		for _ in 0..count {
+			let mut palette = [RGBAColor {
+				r: 0,
+				g: 0,
+				b: 0,
+				a: 0,
+			}; 256];
+
+			for color in palette.iter_mut() {
+				let r = reader.read_u8()?;
+				let g = reader.read_u8()?;
+				let b = reader.read_u8()?;
+				*color = RGBAColor { r, g, b, a: 0xFF };
+			}
+
+			palettes.push(Palette(palette));
 		}
 
-		Ok(Palette(palette))
+		let tints = palettes
+			.iter()
+			.map(|palette| palette_tint(&palettes[0], palette))
+			.collect();
+
+		Ok(Palettes { palettes, tints })
+	}
+}
+
+// Doom's extra palettes are palette 0 blended towards a single colour.
+// Recover that colour and the blend factor, so that the same effect can be applied
+// as a colour overlay on top of the true-colour image.
+fn palette_tint(base: &Palette, palette: &Palette) -> [f32; 4] {
+	let channels = |color: &RGBAColor| [color.r as f32, color.g as f32, color.b as f32];
+	let mut scale = [0.0; 3];
+	let mut offset = [0.0; 3];
+
+	// Least-squares fit of palette = base * scale + offset, for each channel
+	for c in 0..3 {
+		let xs = base.iter().map(|color| channels(color)[c]);
+		let ys = palette.iter().map(|color| channels(color)[c]);
+		let mean_x = xs.clone().sum::<f32>() / 256.0;
+		let mean_y = ys.clone().sum::<f32>() / 256.0;
+
+		let (cov, var) = xs.zip(ys).fold((0.0, 0.0), |(cov, var), (x, y)| {
+			(
+				cov + (x - mean_x) * (y - mean_y),
+				var + (x - mean_x) * (x - mean_x),
+			)
+		});
+
+		scale[c] = if var > 0.0 { cov / var } else { 1.0 };
+		offset[c] = mean_y - scale[c] * mean_x;
+	}
+
+	let factor = num_traits::clamp(1.0 - (scale[0] + scale[1] + scale[2]) / 3.0, 0.0, 1.0);
+
+	if factor <= 0.001 {
+		return [0.0; 4];
+	}
+
+	[
+		num_traits::clamp(offset[0] / factor / 255.0, 0.0, 1.0),
+		num_traits::clamp(offset[1] / factor / 255.0, 0.0, 1.0),
+		num_traits::clamp(offset[2] / factor / 255.0, 0.0, 1.0),
+		factor,
+	]
+}
+
+#[derive(Clone)]
+pub struct Translation([u8; 256]);
+
+impl Translation {
+	pub fn from_ranges(ranges: &[(RangeInclusive<u8>, RangeInclusive<u8>)]) -> Translation {
+		let mut table = [0u8; 256];
+
+		for (i, entry) in table.iter_mut().enumerate() {
+			*entry = i as u8;
+		}
+
+		// Map each source range linearly onto its destination range
+		for (source, dest) in ranges {
+			let source_start = *source.start() as f32;
+			let source_len = *source.end() as f32 - source_start;
+			let dest_start = *dest.start() as f32;
+			let dest_len = *dest.end() as f32 - dest_start;
+
+			for i in source.clone() {
+				let fraction = if source_len > 0.0 {
+					(i as f32 - source_start) / source_len
+				} else {
+					0.0
+				};
+				table[i as usize] = (dest_start + dest_len * fraction).round() as u8;
+			}
+		}
+
+		Translation(table)
+	}
+}
+
+impl Deref for Translation {
+	type Target = [u8; 256];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl Asset for Translation {
+	type Data = Self;
+	type Intermediate = Self;
+	const NAME: &'static str = "Translation";
+
+	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
+		let data = source.load(name)?;
+		ensure!(
+			data.len() == 256,
+			"Translation lump has invalid size {}",
+			data.len()
+		);
+
+		let mut table = [0u8; 256];
+		table.copy_from_slice(&data);
+		Ok(Translation(table))
 	}
 }
 
@@ -62,6 +200,88 @@ pub struct Image {
 	pub offset: [isize; 2],
 }
 
+// A true colour replacement for an image, with the original kept so that the
+// replacement can be scaled to the same size in the world
+pub struct ReplaceableImage {
+	pub name: String,
+	pub image: Image,
+	pub hires: Option<HiresImage>,
+}
+
+impl ReplaceableImage {
+	pub fn import(image: Image, name: &str, source: &impl DataSource) -> ReplaceableImage {
+		let hires = match HiresImageFormat.import(name, source) {
+			Ok(hires) => hires,
+			Err(e) => {
+				log::warn!(
+					"Hires replacement for '{}' could not be loaded: {}",
+					name,
+					e
+				);
+				None
+			}
+		};
+
+		ReplaceableImage {
+			name: name.to_owned(),
+			image,
+			hires,
+		}
+	}
+}
+
+pub struct HiresImage {
+	pub data: Vec<RGBAColor>,
+	pub size: [usize; 2],
+}
+
+#[derive(Clone, Copy)]
+pub struct HiresImageFormat;
+
+impl AssetFormat for HiresImageFormat {
+	type Asset = Option<HiresImage>;
+
+	fn import(&self, name: &str, source: &impl DataSource) -> anyhow::Result<Self::Asset> {
+		let data = match source.load(&format!("HIRES/{}", name)) {
+			Ok(data) => data,
+			Err(_) => return Ok(None),
+		};
+
+		let mut decoder = png::Decoder::new(Cursor::new(data));
+		decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+		let (info, mut reader) = decoder.read_info()?;
+		let mut buf = vec![0u8; info.buffer_size()];
+		reader.next_frame(&mut buf)?;
+
+		let channels = info.color_type.samples();
+		let data = buf
+			.chunks_exact(channels)
+			.map(|pixel| match pixel {
+				&[i] => RGBAColor {
+					r: i,
+					g: i,
+					b: i,
+					a: 0xFF,
+				},
+				&[i, a] => RGBAColor {
+					r: i,
+					g: i,
+					b: i,
+					a,
+				},
+				&[r, g, b] => RGBAColor { r, g, b, a: 0xFF },
+				&[r, g, b, a] => RGBAColor { r, g, b, a },
+				_ => unreachable!(),
+			})
+			.collect();
+
+		Ok(Some(HiresImage {
+			data,
+			size: [info.width as usize, info.height as usize],
+		}))
+	}
+}
+
 #[derive(Clone, Copy)]
 pub struct ImageFormat;
 
@@ -19,7 +19,7 @@ pub enum Axis {
 #[derive(Clone, Copy, Debug, Default)]
 pub struct UserCommand {
 	pub action_attack: bool,
-	//pub action_switch_weapon: Option<u8>,
+	pub action_switch_weapon: Option<u8>,
 	pub action_use: bool,
 	pub axis_forward: f32,
 	pub axis_pitch: f32,
@@ -0,0 +1,514 @@
+use crate::{
+	assets::AssetHandle,
+	audio::Sound,
+	doom::{
+		client::MessageEvent,
+		components::{Armor, Health, Transform},
+		data::WeaponTypes,
+		game::GameSession,
+		palette::PaletteFlash,
+		physics::{BoxCollider, TouchEvent},
+		render::sprite::{RenderStyle, SpriteRender},
+		weapon::{AmmoType, WeaponState, WeaponType},
+	},
+	quadtree::Quadtree,
+};
+use bitflags::bitflags;
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn item_pickup_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut touch_event_reader = resources
+		.get_mut::<EventChannel<TouchEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (
+			mut game_session,
+			touch_event_channel,
+			mut message_event_channel,
+			mut quadtree,
+			mut sound_queue,
+			weapon_types,
+		) = <(
+			Write<GameSession>,
+			Read<EventChannel<TouchEvent>>,
+			Write<EventChannel<MessageEvent>>,
+			Write<Quadtree>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Read<WeaponTypes>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for touch_event in touch_event_channel.read(&mut touch_event_reader) {
+			let item_pickup = match world.get_component::<ItemPickup>(touch_event.touched_entity) {
+				Some(val) => ItemPickup::clone(&val),
+				None => continue,
+			};
+
+			// Dead things can't pick anything up
+			match world.get_component::<Health>(touch_event.entity) {
+				Some(health) if health.current > 0 => (),
+				_ => continue,
+			}
+
+			if !world.has_component::<Inventory>(touch_event.entity) {
+				continue;
+			}
+
+			// The item must be within reach vertically
+			if let (Some(transform), Some(item_transform), Some(box_collider)) = (
+				world.get_component::<Transform>(touch_event.entity),
+				world.get_component::<Transform>(touch_event.touched_entity),
+				world.get_component::<BoxCollider>(touch_event.entity),
+			) {
+				let delta = item_transform.position[2] - transform.position[2];
+
+				if delta > box_collider.height || delta < -8.0 {
+					continue;
+				}
+			}
+
+			let mut context = ItemContext {
+				world,
+				weapon_types: &weapon_types,
+				entity: touch_event.entity,
+			};
+
+			let show_message = match context.give(&item_pickup.item) {
+				Some(show_message) => show_message,
+				None => continue,
+			};
+
+			if item_pickup.count_item {
+				game_session.items_found += 1;
+			}
+
+			if show_message {
+				message_event_channel.single_write(MessageEvent {
+					entity: touch_event.entity,
+					message: item_pickup.message.clone(),
+				});
+			}
+
+			if let Some(mut palette_flash) =
+				unsafe { world.get_component_mut_unchecked::<PaletteFlash>(touch_event.entity) }
+			{
+				palette_flash.add_bonus();
+			}
+
+			sound_queue.push((item_pickup.sound.clone(), touch_event.entity));
+
+			// Remove the item from the world
+			quadtree.remove(touch_event.touched_entity);
+			command_buffer.delete(touch_event.touched_entity);
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn powers_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(|world, resources| {
+		let delta = <Read<Duration>>::fetch(resources);
+		let mut visible = Vec::new();
+
+		for (entity, mut powers) in <Write<Powers>>::query().iter_entities_mut(world) {
+			powers.invulnerability = powers
+				.invulnerability
+				.checked_sub(*delta)
+				.unwrap_or_default();
+			powers.iron_feet = powers.iron_feet.checked_sub(*delta).unwrap_or_default();
+			powers.infrared = powers.infrared.checked_sub(*delta).unwrap_or_default();
+
+			// Berserk never runs out, it only counts up to fade the red palette
+			if let Some(strength) = &mut powers.strength {
+				*strength += *delta;
+			}
+
+			if powers.invisibility > Duration::default() {
+				powers.invisibility = powers.invisibility.checked_sub(*delta).unwrap_or_default();
+
+				if powers.invisibility == Duration::default() {
+					visible.push(entity);
+				}
+			}
+		}
+
+		for entity in visible {
+			if let Some(mut sprite_render) = world.get_component_mut::<SpriteRender>(entity) {
+				sprite_render.render_style = RenderStyle::Normal;
+			}
+		}
+	})
+}
+
+bitflags! {
+	pub struct Keys: u8 {
+		const BLUECARD = 0b00_0001;
+		const YELLOWCARD = 0b00_0010;
+		const REDCARD = 0b00_0100;
+		const BLUESKULL = 0b00_1000;
+		const YELLOWSKULL = 0b01_0000;
+		const REDSKULL = 0b10_0000;
+
+		const BLUE = Self::BLUECARD.bits | Self::BLUESKULL.bits;
+		const YELLOW = Self::YELLOWCARD.bits | Self::YELLOWSKULL.bits;
+		const RED = Self::REDCARD.bits | Self::REDSKULL.bits;
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Inventory {
+	pub keys: Keys,
+	// Indexed by WeaponType and AmmoType
+	pub weapons: [bool; 9],
+	pub ammo: [i32; 4],
+	// Doubles the maximum ammo
+	pub backpack: bool,
+}
+
+impl Default for Inventory {
+	fn default() -> Inventory {
+		Inventory {
+			keys: Keys::empty(),
+			weapons: [false; 9],
+			ammo: [0; 4],
+			backpack: false,
+		}
+	}
+}
+
+const INVULNERABILITY_TIME: Duration = Duration::from_secs(30);
+const INVISIBILITY_TIME: Duration = Duration::from_secs(60);
+const IRON_FEET_TIME: Duration = Duration::from_secs(60);
+const INFRARED_TIME: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Powers {
+	pub invulnerability: Duration,
+	// Time since berserk was picked up
+	pub strength: Option<Duration>,
+	pub invisibility: Duration,
+	pub iron_feet: Duration,
+	pub all_map: bool,
+	pub infrared: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct ItemPickup {
+	pub item: Item,
+	pub sound: AssetHandle<Sound>,
+	pub message: String,
+	// Counts towards the item total in the level stats
+	pub count_item: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Item {
+	// Health can go up to max, items with always set are picked up even at max
+	Health { amount: i32, max: i32, always: bool },
+	// Replaces the current armor if it is better
+	Armor { class: u8, amount: i32 },
+	ArmorBonus { amount: i32, max: i32 },
+	Megasphere,
+	// A clips value of 0 gives half a clip
+	Ammo { ammo_type: AmmoType, clips: i32 },
+	Weapon { weapon: WeaponType, clips: i32 },
+	Backpack,
+	Key(Keys),
+	Power(Power),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Power {
+	Invulnerability,
+	Strength,
+	Invisibility,
+	IronFeet,
+	AllMap,
+	Infrared,
+}
+
+struct ItemContext<'a> {
+	world: &'a World,
+	weapon_types: &'a WeaponTypes,
+	entity: Entity,
+}
+
+impl<'a> ItemContext<'a> {
+	// Returns None if the item can't be picked up, otherwise whether to show the pickup message
+	fn give(&mut self, item: &Item) -> Option<bool> {
+		match *item {
+			Item::Health {
+				amount,
+				max,
+				always,
+			} => {
+				if !self.give_health(amount, max) && !always {
+					return None;
+				}
+			}
+			Item::Armor { class, amount } => {
+				let mut armor = unsafe {
+					self.world
+						.get_component_mut_unchecked::<Armor>(self.entity)?
+				};
+
+				if armor.current >= amount {
+					return None;
+				}
+
+				armor.class = class;
+				armor.current = amount;
+			}
+			Item::ArmorBonus { amount, max } => {
+				let mut armor = unsafe {
+					self.world
+						.get_component_mut_unchecked::<Armor>(self.entity)?
+				};
+				armor.current = i32::min(armor.current + amount, max);
+
+				if armor.class == 0 {
+					armor.class = 1;
+				}
+			}
+			Item::Megasphere => {
+				let mut health = unsafe {
+					self.world
+						.get_component_mut_unchecked::<Health>(self.entity)?
+				};
+				health.current = 200;
+
+				if let Some(mut armor) =
+					unsafe { self.world.get_component_mut_unchecked::<Armor>(self.entity) }
+				{
+					armor.class = 2;
+					armor.current = 200;
+				}
+			}
+			Item::Ammo { ammo_type, clips } => {
+				if !self.give_ammo(ammo_type, clips) {
+					return None;
+				}
+			}
+			Item::Weapon { weapon, clips } => {
+				if !self.give_weapon(weapon, clips) {
+					return None;
+				}
+			}
+			Item::Backpack => {
+				let mut inventory = unsafe {
+					self.world
+						.get_component_mut_unchecked::<Inventory>(self.entity)?
+				};
+				inventory.backpack = true;
+				drop(inventory);
+
+				for &ammo_type in &[
+					AmmoType::Clip,
+					AmmoType::Shell,
+					AmmoType::Cell,
+					AmmoType::Missile,
+				] {
+					self.give_ammo(ammo_type, 1);
+				}
+			}
+			Item::Key(keys) => {
+				let mut inventory = unsafe {
+					self.world
+						.get_component_mut_unchecked::<Inventory>(self.entity)?
+				};
+
+				// Keys are always picked up, but the message is only shown for new ones
+				if inventory.keys.contains(keys) {
+					return Some(false);
+				}
+
+				inventory.keys.insert(keys);
+			}
+			Item::Power(power) => {
+				if !self.give_power(power) {
+					return None;
+				}
+			}
+		}
+
+		Some(true)
+	}
+
+	fn give_health(&mut self, amount: i32, max: i32) -> bool {
+		let mut health = match unsafe {
+			self.world
+				.get_component_mut_unchecked::<Health>(self.entity)
+		} {
+			Some(val) => val,
+			None => return false,
+		};
+
+		if health.current >= max {
+			return false;
+		}
+
+		health.current = i32::min(health.current + amount, max);
+		true
+	}
+
+	fn give_ammo(&mut self, ammo_type: AmmoType, clips: i32) -> bool {
+		let mut inventory = match unsafe {
+			self.world
+				.get_component_mut_unchecked::<Inventory>(self.entity)
+		} {
+			Some(val) => val,
+			None => return false,
+		};
+		let ammo_info = &self.weapon_types.ammo[ammo_type as usize];
+		let max = if inventory.backpack {
+			ammo_info.max * 2
+		} else {
+			ammo_info.max
+		};
+		let old_ammo = inventory.ammo[ammo_type as usize];
+
+		if old_ammo >= max {
+			return false;
+		}
+
+		let amount = if clips == 0 {
+			ammo_info.clip / 2
+		} else {
+			ammo_info.clip * clips
+		};
+		inventory.ammo[ammo_type as usize] = i32::min(old_ammo + amount, max);
+
+		// Switch to a better weapon if the player was out of this ammo
+		if old_ammo == 0 {
+			if let Some(mut weapon_state) = unsafe {
+				self.world
+					.get_component_mut_unchecked::<WeaponState>(self.entity)
+			} {
+				let current = weapon_state.current;
+				let has = |weapon: WeaponType| inventory.weapons[weapon as usize];
+
+				let pending = match ammo_type {
+					AmmoType::Clip if current == WeaponType::Fist => {
+						if has(WeaponType::Chaingun) {
+							Some(WeaponType::Chaingun)
+						} else {
+							Some(WeaponType::Pistol)
+						}
+					}
+					AmmoType::Shell
+						if (current == WeaponType::Fist || current == WeaponType::Pistol)
+							&& has(WeaponType::Shotgun) =>
+					{
+						Some(WeaponType::Shotgun)
+					}
+					AmmoType::Cell
+						if (current == WeaponType::Fist || current == WeaponType::Pistol)
+							&& has(WeaponType::Plasma) =>
+					{
+						Some(WeaponType::Plasma)
+					}
+					AmmoType::Missile
+						if current == WeaponType::Fist && has(WeaponType::Missile) =>
+					{
+						Some(WeaponType::Missile)
+					}
+					_ => None,
+				};
+
+				if pending.is_some() {
+					weapon_state.pending = pending;
+				}
+			}
+		}
+
+		true
+	}
+
+	fn give_weapon(&mut self, weapon: WeaponType, clips: i32) -> bool {
+		let gave_ammo = match self.weapon_types.weapons[weapon as usize].ammo_type {
+			Some(ammo_type) => self.give_ammo(ammo_type, clips),
+			None => false,
+		};
+
+		let mut inventory = match unsafe {
+			self.world
+				.get_component_mut_unchecked::<Inventory>(self.entity)
+		} {
+			Some(val) => val,
+			None => return gave_ammo,
+		};
+
+		if inventory.weapons[weapon as usize] {
+			return gave_ammo;
+		}
+
+		inventory.weapons[weapon as usize] = true;
+
+		if let Some(mut weapon_state) = unsafe {
+			self.world
+				.get_component_mut_unchecked::<WeaponState>(self.entity)
+		} {
+			weapon_state.pending = Some(weapon);
+		}
+
+		true
+	}
+
+	fn give_power(&mut self, power: Power) -> bool {
+		let mut powers = match unsafe {
+			self.world
+				.get_component_mut_unchecked::<Powers>(self.entity)
+		} {
+			Some(val) => val,
+			None => return false,
+		};
+
+		match power {
+			Power::Invulnerability => powers.invulnerability = INVULNERABILITY_TIME,
+			Power::Strength => {
+				powers.strength = Some(Duration::default());
+				drop(powers);
+				self.give_health(100, 100);
+
+				if let Some(mut weapon_state) = unsafe {
+					self.world
+						.get_component_mut_unchecked::<WeaponState>(self.entity)
+				} {
+					if weapon_state.current != WeaponType::Fist {
+						weapon_state.pending = Some(WeaponType::Fist);
+					}
+				}
+			}
+			Power::Invisibility => {
+				powers.invisibility = INVISIBILITY_TIME;
+
+				if let Some(mut sprite_render) = unsafe {
+					self.world
+						.get_component_mut_unchecked::<SpriteRender>(self.entity)
+				} {
+					sprite_render.render_style = RenderStyle::Fuzz;
+				}
+			}
+			Power::IronFeet => powers.iron_feet = IRON_FEET_TIME,
+			Power::AllMap => {
+				if powers.all_map {
+					return false;
+				}
+
+				powers.all_map = true;
+			}
+			Power::Infrared => powers.infrared = INFRARED_TIME,
+		}
+
+		true
+	}
+}
@@ -1,15 +1,190 @@
 use crate::{
-	assets::AssetStorage,
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
 	doom::{
+		client::{Client, UseAction, UseEvent},
 		data::FRAME_TIME,
-		map::{MapDynamic, SectorRef},
+		door::{activate_switch, SwitchActive},
+		map::{LinedefRef, Map, MapDynamic, SectorRef},
+		physics::{CrossAction, CrossEvent},
 	},
 };
-use legion::prelude::{IntoQuery, Read, ResourceSet, Resources, World, Write};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
 use rand::Rng;
 use rand_pcg::Pcg64Mcg;
+use shrev::EventChannel;
 use std::time::Duration;
 
+pub fn light_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut use_event_reader = resources
+		.get_mut::<EventChannel<UseEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, use_event_channel, mut sound_queue) = <(
+			Read<AssetStorage>,
+			Read<EventChannel<UseEvent>>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for use_event in use_event_channel.read(&mut use_event_reader) {
+			if let Some(UseAction::LightSwitchUse(light_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_lights(
+					world,
+					light_use.change,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut command_buffer,
+				);
+
+				activate_switch(
+					use_event.linedef_entity,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&light_use.switch_sound,
+					if light_use.retrigger {
+						Some(light_use.switch_time)
+					} else {
+						None
+					},
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				if !light_use.retrigger {
+					command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn light_cross_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, cross_event_channel) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+		)>::fetch(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			if let Some(CrossAction::LightCross(light_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				// Only players can change lights
+				if client.entity != Some(cross_event.entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(cross_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_lights(
+					world,
+					light_cross.change,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut command_buffer,
+				);
+
+				if !light_cross.retrigger {
+					command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+fn activate_lights(
+	world: &World,
+	change: LightChange,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	command_buffer: &mut CommandBuffer,
+) {
+	let linedef = &map.linedefs[linedef_index];
+
+	for (i, sector) in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+	{
+		// Neighbouring light levels as they are right now, not at the start of the map
+		let neighbour_lights = sector
+			.neighbours
+			.iter()
+			.map(|index| map_dynamic.sectors[*index].light_level);
+
+		let light_level = match change {
+			LightChange::Set(light_level) => light_level,
+			LightChange::BrightestNeighbour => neighbour_lights.fold(0.0, f32::max),
+			LightChange::DarkestNeighbour => {
+				neighbour_lights.fold(map_dynamic.sectors[i].light_level, f32::min)
+			}
+			LightChange::Flash(light_flash) => {
+				let sector_entity = map_dynamic.sectors[i].entity;
+
+				if !world.has_component::<LightFlash>(sector_entity) {
+					command_buffer.add_component(sector_entity, light_flash);
+				}
+
+				continue;
+			}
+		};
+
+		map_dynamic.sectors[i].light_level = light_level;
+	}
+}
+
 pub fn light_update_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 	Box::new(|world, resources| {
 		let (asset_storage, delta, mut rng) =
@@ -142,3 +317,25 @@ pub struct LightGlow {
 	pub speed: f32,
 	pub state: bool,
 }
+
+#[derive(Clone, Copy, Debug)]
+pub enum LightChange {
+	Set(f32),
+	BrightestNeighbour,
+	DarkestNeighbour,
+	Flash(LightFlash),
+}
+
+#[derive(Clone, Debug)]
+pub struct LightSwitchUse {
+	pub change: LightChange,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LightCross {
+	pub change: LightChange,
+	pub retrigger: bool,
+}
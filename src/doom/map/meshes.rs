@@ -8,7 +8,7 @@ use crate::{
 use fnv::FnvHashMap;
 use legion::prelude::{Read, ResourceSet, Resources};
 use nalgebra::Vector2;
-use vulkano::{image::Dimensions, impl_vertex};
+use vulkano::impl_vertex;
 
 #[derive(Clone, Debug, Default)]
 pub struct VertexData {
@@ -41,7 +41,7 @@ pub fn make_meshes(
 		vert_v: [f32; 2],
 		tex_v: [f32; 2],
 		offset: Vector2<f32>,
-		dimensions: Dimensions,
+		world_size: [f32; 2],
 		light_level: f32,
 	) {
 		let width = (vert_h[1] - vert_h[0]).norm();
@@ -52,8 +52,8 @@ pub fn make_meshes(
 			vertices.push(VertexData {
 				in_position: [vert_h[h][0], vert_h[h][1], vert_v[v]],
 				in_texture_coord: [
-					(offset[0] + width * h as f32) / dimensions.width() as f32,
-					(offset[1] + tex_v[v]) / dimensions.height() as f32,
+					(offset[0] + width * h as f32) / world_size[0],
+					(offset[1] + tex_v[v]) / world_size[1],
 				],
 				in_light_level: light_level,
 			});
@@ -83,7 +83,7 @@ pub fn make_meshes(
 		indices: &mut Vec<u32>,
 		iter: impl Iterator<Item = &'a Vector2<f32>>,
 		vert_z: f32,
-		dimensions: Dimensions,
+		world_size: [f32; 2],
 		light_level: f32,
 	) {
 		indices.push(u32::max_value());
@@ -92,10 +92,7 @@ pub fn make_meshes(
 			indices.push(vertices.len() as u32);
 			vertices.push(VertexData {
 				in_position: [vert[0], vert[1], vert_z],
-				in_texture_coord: [
-					vert[0] / dimensions.width() as f32,
-					-vert[1] / dimensions.height() as f32,
-				],
+				in_texture_coord: [vert[0] / world_size[0], -vert[1] / world_size[1]],
 				in_light_level: light_level,
 			});
 		}
@@ -176,7 +173,7 @@ pub fn make_meshes(
 						);
 					}
 					TextureType::Normal(handle) => {
-						let dimensions = asset_storage.get(handle).unwrap().dimensions();
+						let world_size = asset_storage.get(handle).unwrap().world_size;
 						let (ref mut vertices, ref mut indices) = wall_meshes
 							.entry(handle.clone())
 							.or_insert((vec![], vec![]));
@@ -194,7 +191,7 @@ pub fn make_meshes(
 							[spans[0], spans[1]],
 							tex_v,
 							texture_offset,
-							dimensions,
+							world_size,
 							front_sector_dynamic.light_level,
 						);
 					}
@@ -205,7 +202,7 @@ pub fn make_meshes(
 					TextureType::None => (),
 					TextureType::Sky => unimplemented!(),
 					TextureType::Normal(handle) => {
-						let dimensions = asset_storage.get(handle).unwrap().dimensions();
+						let world_size = asset_storage.get(handle).unwrap().world_size;
 						let (ref mut vertices, ref mut indices) = wall_meshes
 							.entry(handle.clone())
 							.or_insert((vec![], vec![]));
@@ -226,7 +223,7 @@ pub fn make_meshes(
 							[spans[2], spans[3]],
 							tex_v,
 							texture_offset,
-							dimensions,
+							world_size,
 							front_sector_dynamic.light_level,
 						);
 					}
@@ -237,7 +234,7 @@ pub fn make_meshes(
 					TextureType::None => (),
 					TextureType::Sky => unimplemented!(),
 					TextureType::Normal(handle) => {
-						let dimensions = asset_storage.get(handle).unwrap().dimensions();
+						let world_size = asset_storage.get(handle).unwrap().world_size;
 						let (ref mut vertices, ref mut indices) = wall_meshes
 							.entry(handle.clone())
 							.or_insert((vec![], vec![]));
@@ -255,7 +252,7 @@ pub fn make_meshes(
 							[spans[1], spans[2]],
 							tex_v,
 							texture_offset,
-							dimensions,
+							world_size,
 							front_sector_dynamic.light_level,
 						);
 					}
@@ -265,7 +262,7 @@ pub fn make_meshes(
 					TextureType::None => (),
 					TextureType::Sky => unimplemented!(),
 					TextureType::Normal(handle) => {
-						let dimensions = asset_storage.get(handle).unwrap().dimensions();
+						let world_size = asset_storage.get(handle).unwrap().world_size;
 						let (ref mut vertices, ref mut indices) = wall_meshes
 							.entry(handle.clone())
 							.or_insert((vec![], vec![]));
@@ -286,7 +283,7 @@ pub fn make_meshes(
 							],
 							tex_v,
 							texture_offset,
-							dimensions,
+							world_size,
 							front_sector_dynamic.light_level,
 						);
 					}
@@ -303,7 +300,7 @@ pub fn make_meshes(
 			// Floor
 			let iter = segs.iter().map(|seg| &seg.line.point).rev();
 
-			match &sector.textures[SectorSlot::Floor as usize] {
+			match &sector_dynamic.textures[SectorSlot::Floor as usize] {
 				TextureType::None => (),
 				TextureType::Sky => push_sky_flat(
 					&mut sky_mesh.0,
@@ -312,7 +309,7 @@ pub fn make_meshes(
 					sector_dynamic.interval.min,
 				),
 				TextureType::Normal(handle) => {
-					let dimensions = asset_storage.get(handle).unwrap().dimensions();
+					let world_size = asset_storage.get(handle).unwrap().world_size;
 					let (ref mut vertices, ref mut indices) = flat_meshes
 						.entry(handle.clone())
 						.or_insert((vec![], vec![]));
@@ -322,7 +319,7 @@ pub fn make_meshes(
 						indices,
 						iter,
 						sector_dynamic.interval.min,
-						dimensions,
+						world_size,
 						sector_dynamic.light_level,
 					);
 				}
@@ -331,7 +328,7 @@ pub fn make_meshes(
 			// Ceiling
 			let iter = segs.iter().map(|seg| &seg.line.point);
 
-			match &sector.textures[SectorSlot::Ceiling as usize] {
+			match &sector_dynamic.textures[SectorSlot::Ceiling as usize] {
 				TextureType::None => (),
 				TextureType::Sky => push_sky_flat(
 					&mut sky_mesh.0,
@@ -340,7 +337,7 @@ pub fn make_meshes(
 					sector_dynamic.interval.max,
 				),
 				TextureType::Normal(handle) => {
-					let dimensions = asset_storage.get(handle).unwrap().dimensions();
+					let world_size = asset_storage.get(handle).unwrap().world_size;
 					let (ref mut vertices, ref mut indices) = flat_meshes
 						.entry(handle.clone())
 						.or_insert((vec![], vec![]));
@@ -350,7 +347,7 @@ pub fn make_meshes(
 						indices,
 						iter,
 						sector_dynamic.interval.max,
-						dimensions,
+						world_size,
 						sector_dynamic.light_level,
 					);
 				}
@@ -2,6 +2,9 @@ pub mod load;
 pub mod meshes;
 pub mod textures;
 
+#[cfg(test)]
+pub mod test_map;
+
 use crate::{
 	assets::{AssetHandle, AssetStorage},
 	doom::{
@@ -11,7 +14,9 @@ use crate::{
 			load::LinedefFlags,
 			textures::{Flat, TextureType, Wall},
 		},
+		monster::Monster,
 		physics::SolidMask,
+		render::sprite::SpriteRender,
 	},
 	geometry::{Angle, Interval, Line2, Plane2, Plane3, Side, AABB2},
 };
@@ -72,7 +77,8 @@ bitflags! {
 		const EASY = 0b00000000_00000001;
 		const NORMAL = 0b00000000_00000010;
 		const HARD = 0b00000000_00000100;
-		const MPONLY = 0b00000000_00001000;
+		const AMBUSH = 0b00000000_00001000;
+		const MPONLY = 0b00000000_00010000;
 	}
 }
 
@@ -174,6 +180,9 @@ pub struct SectorDynamic {
 	pub entity: Entity,
 	pub light_level: f32,
 	pub interval: Interval,
+	pub textures: [TextureType<Flat>; 2],
+	// The last entity that made a noise which reached this sector
+	pub sound_target: Option<Entity>,
 }
 
 #[derive(Clone, Debug)]
@@ -248,6 +257,19 @@ pub fn spawn_things(
 		let entity = command_buffer.insert((), vec![()])[0];
 		template.add_to_entity(entity, &mut command_buffer);
 
+		// Ambush monsters only wake up to noises if they can see where they came from
+		if thing.flags.contains(ThingFlags::AMBUSH) {
+			if let Some(monster) = template.get_component::<Monster>() {
+				command_buffer.add_component(
+					entity,
+					Monster {
+						ambush: true,
+						..monster.clone()
+					},
+				);
+			}
+		}
+
 		// Set entity transform
 		let z = {
 			let map = asset_storage.get(&map_handle).unwrap();
@@ -283,14 +305,24 @@ pub fn spawn_things(
 	Ok(())
 }
 
-pub fn spawn_player(world: &mut World, resources: &mut Resources) -> anyhow::Result<Entity> {
+pub fn spawn_player(
+	world: &mut World,
+	resources: &mut Resources,
+	player_num: usize,
+) -> anyhow::Result<Entity> {
 	let mut command_buffer = CommandBuffer::new(world);
 
 	// Get spawn point transform
 	let transform = <(Read<Transform>, Read<SpawnPoint>)>::query()
 		.iter(world)
-		.find_map(|(t, s)| if s.player_num == 1 { Some(*t) } else { None })
-		.unwrap();
+		.find_map(|(t, s)| {
+			if s.player_num == player_num {
+				Some(*t)
+			} else {
+				None
+			}
+		})
+		.ok_or(anyhow!("Spawn point not found for player {}", player_num))?;
 
 	// Fetch entity template
 	let (asset_storage, entity_types) =
@@ -308,6 +340,20 @@ pub fn spawn_player(world: &mut World, resources: &mut Resources) -> anyhow::Res
 
 	command_buffer.write(world);
 
+	// Players other than the first get their colours remapped
+	let translation = match player_num {
+		2 => entity_types.translations.get("GRAY"),
+		3 => entity_types.translations.get("BROWN"),
+		4 => entity_types.translations.get("RED"),
+		_ => None,
+	};
+
+	if let Some(translation) = translation {
+		if let Some(mut sprite_render) = world.get_component_mut::<SpriteRender>(entity) {
+			sprite_render.translation = Some(translation.clone());
+		}
+	}
+
 	Ok(entity)
 }
 
@@ -407,6 +453,8 @@ pub fn spawn_map_entities(
 			entity,
 			light_level: sector.light_level,
 			interval: sector.interval,
+			textures: sector.textures.clone(),
+			sound_target: None,
 		});
 		command_buffer.add_component(
 			entity,
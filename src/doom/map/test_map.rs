@@ -0,0 +1,415 @@
+// Builds small maps for tests, without needing any WAD data.
+use crate::{
+	assets::AssetStorage,
+	doom::{
+		components::{Health, Transform},
+		map::{
+			load::LinedefFlags,
+			textures::{TextureType, Wall},
+			Linedef, LinedefDynamic, LinedefRef, Map, MapDynamic, Node, NodeChild, Sector,
+			SectorDynamic, SectorRef, SectorSlot, Seg, Sidedef, SidedefDynamic, Subsector,
+		},
+		physics::{BoxCollider, EntityTracer, SolidMask},
+		wad::WadLoader,
+	},
+	geometry::{Interval, Line2, Plane2, Side, AABB2, AABB3},
+	quadtree::Quadtree,
+};
+use fnv::FnvHashMap;
+use legion::prelude::{CommandBuffer, Entity, World};
+use nalgebra::{Vector2, Vector3};
+
+// Half of the width of a corridor map
+pub const HALF_WIDTH: f32 = 128.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct TestSector {
+	pub length: f32,
+	pub interval: Interval,
+	pub sky: bool,
+}
+
+impl TestSector {
+	pub fn new(length: f32, floor: f32, ceiling: f32) -> TestSector {
+		TestSector {
+			length,
+			interval: Interval::new(floor, ceiling),
+			sky: false,
+		}
+	}
+
+	pub fn with_sky(self) -> TestSector {
+		TestSector { sky: true, ..self }
+	}
+}
+
+pub struct TestMap {
+	pub asset_storage: AssetStorage,
+	pub map_entity: Entity,
+	pub quadtree: Quadtree,
+	pub world: World,
+}
+
+impl TestMap {
+	// A straight corridor along the x axis, starting at x = 0 and running between
+	// y = -HALF_WIDTH and HALF_WIDTH. It's made of at least two rectangular sectors side by side,
+	// with a BSP node splitting off each of them in turn.
+	pub fn corridor(test_sectors: &[TestSector]) -> TestMap {
+		assert!(test_sectors.len() >= 2);
+
+		let mut asset_storage = AssetStorage::default();
+		let mut world = World::new();
+
+		let mut boundaries = vec![0.0];
+
+		for test_sector in test_sectors {
+			boundaries.push(boundaries.last().unwrap() + test_sector.length);
+		}
+
+		let mut sectors: Vec<Sector> = test_sectors
+			.iter()
+			.map(|test_sector| Sector {
+				interval: test_sector.interval,
+				textures: [
+					TextureType::None,
+					if test_sector.sky {
+						TextureType::Sky
+					} else {
+						TextureType::None
+					},
+				],
+				light_level: 1.0,
+				special_type: 0,
+				sector_tag: 0,
+				linedefs: Vec::new(),
+				subsectors: Vec::new(),
+				neighbours: Vec::new(),
+			})
+			.collect();
+		let mut linedefs = Vec::new();
+		let mut subsectors = Vec::new();
+
+		let mut add_linedef = |sectors: &mut [Sector],
+		                       start: Vector2<f32>,
+		                       end: Vector2<f32>,
+		                       sector_indices: [Option<usize>; 2]|
+		 -> usize {
+			let index = linedefs.len();
+			let dir = end - start;
+			let mut bbox = AABB2::empty();
+			bbox.add_point(start);
+			bbox.add_point(end);
+
+			let [front, back] = sector_indices;
+			let two_sided = front.is_some() && back.is_some();
+			let sky = sector_indices.iter().all(|index| {
+				index.map_or(false, |index| {
+					sectors[index].textures[SectorSlot::Ceiling as usize].is_sky()
+				})
+			});
+
+			for sector_index in sector_indices.iter().flatten() {
+				sectors[*sector_index].linedefs.push(index);
+			}
+
+			if let [Some(front), Some(back)] = sector_indices {
+				sectors[front].neighbours.push(back);
+				sectors[back].neighbours.push(front);
+			}
+
+			let sidedef = |sector_index: Option<usize>| {
+				sector_index.map(|sector_index| Sidedef {
+					texture_offset: Vector2::zeros(),
+					textures: [
+						if sky {
+							TextureType::Sky
+						} else {
+							TextureType::None
+						},
+						TextureType::None,
+						TextureType::None,
+					],
+					sector_index,
+				})
+			};
+
+			linedefs.push(Linedef {
+				line: Line2::new(start, dir),
+				normal: Vector2::new(dir[1], -dir[0]).normalize(),
+				planes: Vec::from(&bbox.planes()[..]),
+				bbox,
+				flags: if two_sided {
+					LinedefFlags::TWOSIDED
+				} else {
+					LinedefFlags::BLOCKING
+				},
+				solid_mask: if two_sided {
+					SolidMask::empty()
+				} else {
+					SolidMask::all()
+				},
+				special_type: 0,
+				sector_tag: 0,
+				sidedefs: [sidedef(front), sidedef(back)],
+			});
+
+			index
+		};
+
+		// Walls along the sides, and the two-sided linedefs between sectors
+		let mut bottom_walls = Vec::new();
+		let mut top_walls = Vec::new();
+		let mut between = Vec::new();
+
+		for i in 0..sectors.len() {
+			let (x0, x1) = (boundaries[i], boundaries[i + 1]);
+			bottom_walls.push(add_linedef(
+				&mut sectors,
+				Vector2::new(x1, -HALF_WIDTH),
+				Vector2::new(x0, -HALF_WIDTH),
+				[Some(i), None],
+			));
+			top_walls.push(add_linedef(
+				&mut sectors,
+				Vector2::new(x0, HALF_WIDTH),
+				Vector2::new(x1, HALF_WIDTH),
+				[Some(i), None],
+			));
+
+			if i > 0 {
+				between.push(add_linedef(
+					&mut sectors,
+					Vector2::new(x0, HALF_WIDTH),
+					Vector2::new(x0, -HALF_WIDTH),
+					[Some(i - 1), Some(i)],
+				));
+			}
+		}
+
+		let start_wall = add_linedef(
+			&mut sectors,
+			Vector2::new(0.0, -HALF_WIDTH),
+			Vector2::new(0.0, HALF_WIDTH),
+			[Some(0), None],
+		);
+		let x_end = *boundaries.last().unwrap();
+		let last = sectors.len() - 1;
+		let end_wall = add_linedef(
+			&mut sectors,
+			Vector2::new(x_end, HALF_WIDTH),
+			Vector2::new(x_end, -HALF_WIDTH),
+			[Some(last), None],
+		);
+
+		// One subsector per sector
+		for i in 0..sectors.len() {
+			let (x0, x1) = (boundaries[i], boundaries[i + 1]);
+			let corners = [
+				Vector2::new(x1, -HALF_WIDTH),
+				Vector2::new(x0, -HALF_WIDTH),
+				Vector2::new(x0, HALF_WIDTH),
+				Vector2::new(x1, HALF_WIDTH),
+			];
+			let seg_linedefs = [
+				(bottom_walls[i], Side::Right),
+				if i == 0 {
+					(start_wall, Side::Right)
+				} else {
+					(between[i - 1], Side::Left)
+				},
+				(top_walls[i], Side::Right),
+				if i == last {
+					(end_wall, Side::Right)
+				} else {
+					(between[i], Side::Right)
+				},
+			];
+
+			let segs = (0..4)
+				.map(|j| {
+					let dir = corners[(j + 1) % 4] - corners[j];
+
+					Seg {
+						line: Line2::new(corners[j], dir),
+						normal: Vector2::new(dir[1], -dir[0]).normalize(),
+						linedef: Some(seg_linedefs[j]),
+					}
+				})
+				.collect();
+
+			let mut bbox = AABB2::empty();
+			bbox.add_point(corners[1]);
+			bbox.add_point(corners[3]);
+
+			let mut subsector_linedefs = vec![bottom_walls[i], top_walls[i]];
+
+			if i == 0 {
+				subsector_linedefs.push(start_wall);
+			}
+
+			if i == last {
+				subsector_linedefs.push(end_wall);
+			}
+
+			sectors[i].subsectors.push(i);
+			subsectors.push(Subsector {
+				segs,
+				planes: Vec::from(&bbox.planes()[..]),
+				bbox,
+				linedefs: subsector_linedefs,
+				sector_index: i,
+			});
+		}
+
+		// Each node splits off the sector before one of the boundaries
+		let nodes = (1..sectors.len())
+			.map(|i| {
+				let x = boundaries[i];
+				let right = if i == last {
+					NodeChild::Subsector(i)
+				} else {
+					NodeChild::Node(i)
+				};
+
+				Node {
+					plane: Plane2::new(x, Vector2::new(1.0, 0.0)),
+					linedefs: vec![between[i - 1]],
+					child_bboxes: [
+						AABB2::from_extents(HALF_WIDTH, -HALF_WIDTH, x, x_end),
+						AABB2::from_extents(HALF_WIDTH, -HALF_WIDTH, boundaries[i - 1], x),
+					],
+					child_indices: [right, NodeChild::Subsector(i - 1)],
+				}
+			})
+			.collect();
+
+		let bbox = AABB2::from_extents(HALF_WIDTH, -HALF_WIDTH, 0.0, x_end);
+		let map = Map {
+			anims_flat: FnvHashMap::default(),
+			anims_wall: FnvHashMap::default(),
+			bbox: bbox.clone(),
+			linedefs,
+			nodes,
+			sectors,
+			subsectors,
+			sky: asset_storage.load::<Wall>("SKY1", &mut WadLoader::new()),
+			switches: FnvHashMap::default(),
+		};
+
+		// Create the map entities, like spawn_map_entities does
+		let mut command_buffer = CommandBuffer::new(&world);
+		let map_entity = command_buffer.insert((), vec![()])[0];
+
+		let linedefs = map
+			.linedefs
+			.iter()
+			.enumerate()
+			.map(|(index, linedef)| {
+				let entity = command_buffer.insert((), vec![()])[0];
+				command_buffer.add_component(entity, LinedefRef { map_entity, index });
+
+				LinedefDynamic {
+					entity,
+					sidedefs: [
+						linedef.sidedefs[0].as_ref().map(|sidedef| SidedefDynamic {
+							textures: sidedef.textures.clone(),
+						}),
+						linedef.sidedefs[1].as_ref().map(|sidedef| SidedefDynamic {
+							textures: sidedef.textures.clone(),
+						}),
+					],
+					texture_offset: Vector2::zeros(),
+				}
+			})
+			.collect();
+
+		let sectors = map
+			.sectors
+			.iter()
+			.enumerate()
+			.map(|(index, sector)| {
+				let entity = command_buffer.insert((), vec![()])[0];
+				command_buffer.add_component(entity, SectorRef { map_entity, index });
+
+				SectorDynamic {
+					entity,
+					light_level: sector.light_level,
+					interval: sector.interval,
+					textures: sector.textures.clone(),
+					sound_target: None,
+				}
+			})
+			.collect();
+
+		let map_dynamic = MapDynamic {
+			anim_states_flat: FnvHashMap::default(),
+			anim_states_wall: FnvHashMap::default(),
+			map: asset_storage.insert(map),
+			linedefs,
+			sectors,
+		};
+
+		command_buffer.add_component(map_entity, map_dynamic);
+		command_buffer.write(&mut world);
+
+		TestMap {
+			asset_storage,
+			map_entity,
+			quadtree: Quadtree::new(bbox),
+			world,
+		}
+	}
+
+	// Adds an entity with a box collider standing at the given position
+	pub fn spawn(
+		&mut self,
+		position: Vector3<f32>,
+		box_collider: BoxCollider,
+		health: Option<i32>,
+	) -> Entity {
+		let transform = Transform {
+			position,
+			..Transform::default()
+		};
+
+		let entity = match health {
+			Some(health) => self.world.insert(
+				(),
+				vec![(
+					transform,
+					box_collider,
+					Health {
+						current: health,
+						max: health,
+						pain_chance: 0,
+						no_blood: false,
+						no_radius_damage: false,
+						killer: None,
+					},
+				)],
+			)[0],
+			None => self.world.insert((), vec![(transform, box_collider)])[0],
+		};
+
+		let bbox = AABB3::from_radius_height(box_collider.radius, box_collider.height);
+		self.quadtree
+			.insert(entity, &AABB2::from(&bbox.offset(position)));
+
+		entity
+	}
+
+	pub fn with_tracer<R>(&self, func: impl FnOnce(&EntityTracer) -> R) -> R {
+		let map_dynamic = self
+			.world
+			.get_component::<MapDynamic>(self.map_entity)
+			.unwrap();
+		let map = self.asset_storage.get(&map_dynamic.map).unwrap();
+
+		func(&EntityTracer {
+			map,
+			map_dynamic: map_dynamic.as_ref(),
+			quadtree: &self.quadtree,
+			world: &self.world,
+		})
+	}
+}
@@ -1,6 +1,6 @@
 use crate::{
 	assets::{Asset, AssetFormat, AssetHandle, DataSource},
-	doom::image::{IAColor, Image, ImageFormat},
+	doom::image::{IAColor, Image, ImageFormat, ReplaceableImage},
 };
 use anyhow::anyhow;
 use byteorder::{ReadBytesExt, LE};
@@ -13,12 +13,18 @@ use std::{
 };
 use vulkano::image::ImageViewAccess;
 
+#[derive(Clone)]
+pub struct TextureImage {
+	pub image: Arc<dyn ImageViewAccess + Send + Sync>,
+	pub world_size: [f32; 2],
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Flat;
 
 impl Asset for Flat {
-	type Data = Arc<dyn ImageViewAccess + Send + Sync>;
-	type Intermediate = Image;
+	type Data = TextureImage;
+	type Intermediate = ReplaceableImage;
 	const NAME: &'static str = "Flat";
 
 	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
@@ -26,11 +32,13 @@ impl Asset for Flat {
 		let mut pixels = [0u8; 64 * 64];
 		reader.read_exact(&mut pixels)?;
 
-		Ok(Image {
+		let image = Image {
 			data: pixels.iter().map(|&i| IAColor { i, a: 0xFF }).collect(),
 			size: [64, 64],
 			offset: [0, 0],
-		})
+		};
+
+		Ok(ReplaceableImage::import(image, name, source))
 	}
 }
 
@@ -59,8 +67,8 @@ impl AssetFormat for PNamesFormat {
 pub struct Wall;
 
 impl Asset for Wall {
-	type Data = Arc<dyn ImageViewAccess + Send + Sync>;
-	type Intermediate = Image;
+	type Data = TextureImage;
+	type Intermediate = ReplaceableImage;
 	const NAME: &'static str = "Wall";
 
 	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
@@ -122,11 +130,13 @@ impl Asset for Wall {
 				Ok(())
 			})?;
 
-		Ok(Image {
+		let image = Image {
 			data,
 			size: texture_info.size,
 			offset: [0, 0],
-		})
+		};
+
+		Ok(ReplaceableImage::import(image, &name, source))
 	}
 }
 
@@ -240,7 +250,7 @@ impl AssetFormat for RawTexturesFormat {
 }
 
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""), Debug(bound = ""))]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
 pub enum TextureType<T> {
 	Normal(AssetHandle<T>),
 	Sky,
@@ -1,14 +1,30 @@
+pub mod ceiling;
 pub mod client;
 pub mod components;
+pub mod damage;
 pub mod data;
+pub mod dehacked;
 pub mod door;
+pub mod floor;
+pub mod game;
+pub mod hitscan;
 pub mod image;
 pub mod input;
+pub mod inventory;
 pub mod light;
 pub mod map;
+pub mod monster;
+pub mod palette;
 pub mod physics;
+pub mod plat;
+pub mod projectile;
 pub mod render;
+pub mod sector;
 pub mod sound;
 pub mod sprite;
+pub mod state;
+pub mod teleport;
+pub mod template;
 pub mod update;
 pub mod wad;
+pub mod weapon;
@@ -0,0 +1,1965 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		client::{Client, UseAction, UseEvent},
+		components::{Health, Transform, Velocity},
+		damage::{DamageEvent, DamageType},
+		data::{MobjTypes, States, FRAME_RATE},
+		hitscan::{check_sight, entity_bbox, is_shootable, MELEE_RANGE},
+		map::{load::LinedefFlags, LinedefRef, Map, MapDynamic},
+		physics::{find_crossed_linedefs, BoxCollider, CrossEvent, EntityTracer, SolidMask},
+		projectile::{radius_attack, spawn_projectile},
+		render::sprite::{RenderStyle, SpriteRender},
+		state::{StateAction, StateActionEvent, StateMachine},
+	},
+	geometry::{Angle, Side, AABB2, AABB3},
+	quadtree::Quadtree,
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use nalgebra::{Vector2, Vector3};
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use shrev::EventChannel;
+use smallvec::SmallVec;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct Monster {
+	// Distance moved with each step while chasing
+	pub speed: f32,
+	// Number of chase steps after waking up before the monster can fire missiles
+	pub reaction_time: u32,
+	pub target: Option<Entity>,
+	// One of the 8 directions, counting anticlockwise from east, or None if not moving
+	pub move_dir: Option<usize>,
+	// Steps left to take before choosing a new direction
+	pub move_count: i32,
+	// Set after firing a missile, to move a bit before attacking again
+	pub just_attacked: bool,
+	// Set after flinching in pain, to fire back at the attacker straight away
+	pub just_hit: bool,
+	// Only wakes up to noises when it can see where they came from
+	pub ambush: bool,
+	// Velocity of a lost soul's charge, which carries on until it hits something
+	pub charge: Option<Vector3<f32>>,
+
+	// Only fires missiles at targets within this range, measured like the distance below
+	pub min_missile_range: f32,
+	pub max_missile_range: Option<f32>,
+	// Scales the distance to the target when deciding whether to fire, lower fires more often
+	pub missile_distance_scale: f32,
+	// Caps the scaled distance, lower fires more often at far away targets
+	pub missile_distance_cap: f32,
+
+	pub see_sound: Option<AssetHandle<Sound>>,
+	pub attack_sound: Option<AssetHandle<Sound>>,
+	pub pain_sound: Option<AssetHandle<Sound>>,
+	pub death_sound: Option<AssetHandle<Sound>>,
+	pub active_sound: Option<AssetHandle<Sound>>,
+}
+
+// Highest ledge a monster can walk up or down
+pub const STEP_HEIGHT: f32 = 24.0;
+
+// Angle between the fireballs of a mancubus volley
+const FAT_SPREAD: i32 = 1 << 27;
+
+// Speed of a lost soul's charge, per frame
+const SKULL_SPEED: f32 = 20.0;
+
+// Damage of a lost soul's charge is this multiplied by a random number from 1 to 8
+const SKULL_DAMAGE: i32 = 3;
+
+// Pain elementals stop spitting out lost souls when there are more than this many
+const MAX_SKULLS: usize = 20;
+
+// Speed that an arch-vile's blast throws its target upwards, as for a player
+const VILE_THRUST: f32 = 10.0 * FRAME_RATE;
+
+// The fire that an arch-vile conjures up in front of its target
+#[derive(Clone, Copy, Debug)]
+pub struct VileFire {
+	pub vile: Entity,
+	pub target: Entity,
+}
+
+pub fn monster_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut state_action_event_reader = resources
+		.get_mut::<EventChannel<StateActionEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (
+			asset_storage,
+			client,
+			mut cross_event_channel,
+			mut damage_event_channel,
+			delta,
+			entity_types,
+			mut quadtree,
+			mut rng,
+			mut sound_queue,
+			state_action_event_channel,
+			states,
+			mut use_event_channel,
+		) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Write<EventChannel<CrossEvent>>,
+			Write<EventChannel<DamageEvent>>,
+			Read<Duration>,
+			Read<MobjTypes>,
+			Write<Quadtree>,
+			Write<Pcg64Mcg>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Read<EventChannel<StateActionEvent>>,
+			Read<States>,
+			Write<EventChannel<UseEvent>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+		let players: SmallVec<[Entity; 1]> = client.entity.into_iter().collect();
+
+		{
+			let map_dynamic = <Read<MapDynamic>>::query().iter(world).next().unwrap();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+			// Charging lost souls carry on moving after any actions
+			let charging = <Read<Monster>>::query()
+				.iter_entities(world)
+				.filter(|(_, monster)| monster.charge.is_some())
+				.map(|(entity, _)| (entity, None));
+			let updates: Vec<(Entity, Option<StateAction>)> = state_action_event_channel
+				.read(&mut state_action_event_reader)
+				.map(|state_action_event| {
+					(state_action_event.entity, Some(state_action_event.action))
+				})
+				.chain(charging)
+				.collect();
+
+			for (entity, action) in updates {
+				// Gibbing sounds the same for everything
+				if action == Some(StateAction::XScream) {
+					sound_queue.push((entity_types.sounds["DSSLOP"].clone(), entity));
+					continue;
+				}
+
+				// Arch-vile fire isn't a monster, but keeps up with the arch-vile's target
+				let fire_sound = match action {
+					Some(StateAction::StartFire) => Some(Some("DSFLAMST")),
+					Some(StateAction::FireCrackle) => Some(Some("DSFLAME")),
+					Some(StateAction::Fire) => Some(None),
+					_ => None,
+				};
+
+				if let Some(fire_sound) = fire_sound {
+					if let Some(name) = fire_sound {
+						sound_queue.push((entity_types.sounds[name].clone(), entity));
+					}
+
+					follow_target(
+						&EntityTracer {
+							map,
+							map_dynamic: map_dynamic.as_ref(),
+							quadtree: &quadtree,
+							world,
+						},
+						entity,
+					);
+					continue;
+				}
+
+				let mut monster = match world.get_component::<Monster>(entity) {
+					Some(monster) => Monster::clone(&monster),
+					None => continue,
+				};
+
+				let mut context = MonsterContext {
+					tracer: EntityTracer {
+						map,
+						map_dynamic: map_dynamic.as_ref(),
+						quadtree: &quadtree,
+						world,
+					},
+					asset_storage: &asset_storage,
+					command_buffer: &mut command_buffer,
+					damage_event_channel: &mut damage_event_channel,
+					entity_types: &entity_types,
+					players: &players,
+					rng: &mut rng,
+					sound_queue: &mut sound_queue,
+					states: &states,
+					use_event_channel: &mut use_event_channel,
+				};
+				let moved = match action {
+					Some(action) => context.action(entity, &mut monster, action),
+					None => context.charge(entity, &mut monster, *delta),
+				};
+
+				*unsafe { world.get_component_mut_unchecked::<Monster>(entity) }.unwrap() = monster;
+
+				if let Some((position, crossed)) = moved {
+					let mut transform =
+						unsafe { world.get_component_mut_unchecked::<Transform>(entity) }.unwrap();
+					transform.position = position;
+
+					let box_collider = world.get_component::<BoxCollider>(entity).unwrap();
+					let bbox = AABB3::from_radius_height(box_collider.radius, box_collider.height);
+					quadtree.remove(entity);
+					quadtree.insert(entity, &AABB2::from(&bbox.offset(position)));
+
+					cross_event_channel.iter_write(crossed.into_iter().map(
+						|(linedef_entity, side)| CrossEvent {
+							entity,
+							linedef_entity,
+							side,
+						},
+					));
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+struct MonsterContext<'a> {
+	tracer: EntityTracer<'a>,
+	asset_storage: &'a AssetStorage,
+	command_buffer: &'a mut CommandBuffer,
+	damage_event_channel: &'a mut EventChannel<DamageEvent>,
+	entity_types: &'a MobjTypes,
+	players: &'a [Entity],
+	rng: &'a mut Pcg64Mcg,
+	sound_queue: &'a mut Vec<(AssetHandle<Sound>, Entity)>,
+	states: &'a States,
+	use_event_channel: &'a mut EventChannel<UseEvent>,
+}
+
+impl<'a> MonsterContext<'a> {
+	// Carries out an action, returning the monster's new position and the linedefs it crossed
+	// if it moved
+	fn action(
+		&mut self,
+		entity: Entity,
+		monster: &mut Monster,
+		action: StateAction,
+	) -> Option<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>)> {
+		match action {
+			// Lost souls keep going until they hit something
+			StateAction::Look | StateAction::Chase if monster.charge.is_some() => {}
+			StateAction::Look => self.look(entity, monster),
+			StateAction::Chase => return self.chase(entity, monster),
+			StateAction::VileChase => return self.vile_chase(entity, monster),
+			StateAction::FaceTarget => self.face_target(entity, monster),
+			StateAction::Pain => self.sound(monster.pain_sound.clone(), entity),
+			StateAction::Scream => self.sound(monster.death_sound.clone(), entity),
+			StateAction::Hoof | StateAction::Metal | StateAction::BabyMetal => {
+				let name = match action {
+					StateAction::Hoof => "DSHOOF",
+					StateAction::Metal => "DSMETAL",
+					_ => "DSBSPWLK",
+				};
+				self.sound(Some(self.entity_types.sounds[name].clone()), entity);
+				return self.chase(entity, monster);
+			}
+			StateAction::SkelWhoosh | StateAction::FatRaise => {
+				self.face_target(entity, monster);
+
+				let name = match action {
+					StateAction::SkelWhoosh => "DSSKESWG",
+					_ => "DSMANATK",
+				};
+				self.sound(Some(self.entity_types.sounds[name].clone()), entity);
+			}
+			StateAction::SargAttack => {
+				self.face_target(entity, monster);
+
+				if let Some(target) = self.melee_target(entity, monster) {
+					let damage = (self.rng.gen::<u8>() % 10 + 1) as i32 * 4;
+					self.melee(entity, target, damage);
+				}
+			}
+			StateAction::SkelFist => {
+				self.face_target(entity, monster);
+
+				if let Some(target) = self.melee_target(entity, monster) {
+					let damage = (self.rng.gen::<u8>() % 10 + 1) as i32 * 6;
+					self.sound(Some(self.entity_types.sounds["DSSKEPCH"].clone()), entity);
+					self.melee(entity, target, damage);
+				}
+			}
+			StateAction::TroopAttack | StateAction::HeadAttack | StateAction::BruisAttack => {
+				self.face_target(entity, monster);
+
+				// Claw or bite when close, throw a fireball otherwise
+				if let Some(target) = self.melee_target(entity, monster) {
+					let (damage, sound) = match action {
+						StateAction::TroopAttack => {
+							((self.rng.gen::<u8>() % 8 + 1) as i32 * 3, Some("DSCLAW"))
+						}
+						StateAction::HeadAttack => {
+							((self.rng.gen::<u8>() % 6 + 1) as i32 * 10, None)
+						}
+						_ => ((self.rng.gen::<u8>() % 8 + 1) as i32 * 10, Some("DSCLAW")),
+					};
+
+					if let Some(sound) = sound {
+						self.sound(Some(self.entity_types.sounds[sound].clone()), entity);
+					}
+
+					self.melee(entity, target, damage);
+				} else {
+					let name = match action {
+						StateAction::TroopAttack => "TROOPSHOT",
+						StateAction::HeadAttack => "HEADSHOT",
+						_ => "BRUISERSHOT",
+					};
+					self.missile(entity, monster, name, 0.0, &[0]);
+				}
+			}
+			StateAction::SkelMissile => {
+				self.face_target(entity, monster);
+				self.missile(entity, monster, "TRACER", 16.0, &[0]);
+			}
+			StateAction::FatAttack1 | StateAction::FatAttack2 | StateAction::FatAttack3 => {
+				self.face_target(entity, monster);
+
+				// Each volley turns the mancubus a little, so that the next is aimed differently
+				let (turn, offsets) = match action {
+					StateAction::FatAttack1 => (FAT_SPREAD, [0, FAT_SPREAD]),
+					StateAction::FatAttack2 => (-FAT_SPREAD, [0, -2 * FAT_SPREAD]),
+					_ => (0, [-FAT_SPREAD / 2, FAT_SPREAD / 2]),
+				};
+
+				if let Some(mut transform) = unsafe {
+					self.tracer
+						.world
+						.get_component_mut_unchecked::<Transform>(entity)
+				} {
+					transform.rotation[2] += turn;
+				}
+
+				self.missile(entity, monster, "FATSHOT", 0.0, &offsets);
+			}
+			StateAction::CyberAttack => {
+				self.face_target(entity, monster);
+				self.missile(entity, monster, "ROCKET", 0.0, &[0]);
+			}
+			StateAction::BspiAttack => {
+				self.face_target(entity, monster);
+				self.missile(entity, monster, "ARACHPLAZ", 0.0, &[0]);
+			}
+			StateAction::SkullAttack => {
+				self.face_target(entity, monster);
+				self.sound(monster.attack_sound.clone(), entity);
+
+				let world = self.tracer.world;
+				let (position, yaw) = {
+					let transform = world.get_component::<Transform>(entity).unwrap();
+					(transform.position, transform.rotation[2])
+				};
+				monster.charge = monster
+					.target
+					.and_then(|target| charge_velocity(world, position, yaw, target));
+			}
+			StateAction::PainAttack => {
+				self.face_target(entity, monster);
+
+				let yaw = self
+					.tracer
+					.world
+					.get_component::<Transform>(entity)
+					.unwrap()
+					.rotation[2];
+				self.shoot_skull(entity, monster, yaw);
+			}
+			StateAction::PainDie => {
+				// One to each side and one behind
+				let yaw = self
+					.tracer
+					.world
+					.get_component::<Transform>(entity)
+					.unwrap()
+					.rotation[2];
+
+				for &offset in &[1 << 30, std::i32::MIN, -(1 << 30)] {
+					self.shoot_skull(entity, monster, yaw + offset);
+				}
+			}
+			StateAction::VileStart => {
+				self.sound(Some(self.entity_types.sounds["DSVILATK"].clone()), entity);
+			}
+			StateAction::VileTarget => {
+				self.face_target(entity, monster);
+
+				if let Some(target) = monster.target {
+					self.spawn_fire(entity, target);
+				}
+			}
+			StateAction::VileAttack => {
+				self.face_target(entity, monster);
+
+				if let Some(target) = monster.target {
+					self.vile_attack(entity, target);
+				}
+			}
+			StateAction::CPosRefire => self.refire(entity, monster, 40),
+			StateAction::SpidRefire => self.refire(entity, monster, 10),
+			_ => {}
+		}
+
+		None
+	}
+
+	// A_Look
+	fn look(&mut self, entity: Entity, monster: &mut Monster) {
+		// Wake up to noises, unless lying in ambush and unable to see where they came from
+		let heard = sound_target(&self.tracer, entity).filter(|&target| {
+			is_shootable(self.tracer.world, target)
+				&& (!monster.ambush || can_see(&self.tracer, entity, target))
+		});
+
+		if let Some(target) =
+			heard.or_else(|| look_for_players(&self.tracer, entity, self.players, false))
+		{
+			monster.target = Some(target);
+			self.sound(monster.see_sound.clone(), entity);
+			set_state(self.tracer.world, entity, |state_machine| state_machine.see);
+		}
+	}
+
+	// A_Chase
+	fn chase(
+		&mut self,
+		entity: Entity,
+		monster: &mut Monster,
+	) -> Option<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>)> {
+		let world = self.tracer.world;
+
+		if monster.reaction_time > 0 {
+			monster.reaction_time -= 1;
+		}
+
+		// Turn towards the direction of movement, 45 degrees at a time
+		if let Some(move_dir) = monster.move_dir {
+			let mut transform =
+				unsafe { world.get_component_mut_unchecked::<Transform>(entity) }.unwrap();
+			let angle = Angle((transform.rotation[2].0 as u32 & (7 << 29)) as i32);
+			let delta = angle - move_dir_angle(move_dir);
+
+			transform.rotation[2] = if delta.0 > 0 {
+				angle - (1 << 29)
+			} else if delta.0 < 0 {
+				angle + (1 << 29)
+			} else {
+				angle
+			};
+		}
+
+		// Look for someone else if the target is gone, or go back to sleep
+		let target = match monster.target.filter(|&target| is_shootable(world, target)) {
+			Some(x) => x,
+			None => {
+				match look_for_players(&self.tracer, entity, self.players, true) {
+					Some(target) => monster.target = Some(target),
+					None => {
+						monster.target = None;
+						set_state(world, entity, |state_machine| Some(state_machine.spawn));
+					}
+				}
+
+				return None;
+			}
+		};
+
+		let position = world.get_component::<Transform>(entity).unwrap().position;
+		let target_position = world.get_component::<Transform>(target).unwrap().position;
+		let bbox = {
+			let box_collider = world.get_component::<BoxCollider>(entity).unwrap();
+			AABB3::from_radius_height(box_collider.radius, box_collider.height)
+		};
+
+		// Move after attacking, instead of attacking again straight away
+		if monster.just_attacked {
+			monster.just_attacked = false;
+			let walk = new_chase_dir(
+				&self.tracer,
+				self.rng,
+				monster,
+				&bbox,
+				position,
+				target_position,
+			);
+			return self.walk(entity, walk);
+		}
+
+		let (melee, missile) = {
+			let state_machine = world.get_component::<StateMachine>(entity).unwrap();
+			(state_machine.melee, state_machine.missile)
+		};
+
+		if melee.is_some() && check_melee_range(&self.tracer, entity, target) {
+			self.sound(monster.attack_sound.clone(), entity);
+			set_state(world, entity, |_| melee);
+			return None;
+		}
+
+		if missile.is_some()
+			&& monster.move_count == 0
+			&& self.check_missile_range(entity, monster, target, melee.is_some())
+		{
+			monster.just_attacked = true;
+			set_state(world, entity, |_| missile);
+			return None;
+		}
+
+		let walk = chase_move(
+			&self.tracer,
+			self.rng,
+			monster,
+			&bbox,
+			position,
+			target_position,
+		);
+
+		if self.rng.gen::<u8>() < 3 {
+			self.sound(monster.active_sound.clone(), entity);
+		}
+
+		self.walk(entity, walk)
+	}
+
+	// A_VileChase: raises a corpse lying where the arch-vile is about to step, otherwise chases
+	// like any other monster
+	fn vile_chase(
+		&mut self,
+		entity: Entity,
+		monster: &mut Monster,
+	) -> Option<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>)> {
+		let world = self.tracer.world;
+		let corpse = match find_corpse(&self.tracer, entity, monster) {
+			Some(x) => x,
+			None => return self.chase(entity, monster),
+		};
+
+		// Face the corpse while healing it
+		let corpse_position = world.get_component::<Transform>(corpse).unwrap().position;
+		let mut transform =
+			unsafe { world.get_component_mut_unchecked::<Transform>(entity) }.unwrap();
+		let delta = corpse_position - transform.position;
+		transform.rotation[2] = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64));
+
+		let heal = self.states.names["VILE_HEAL1"];
+		set_state(world, entity, |_| Some(heal));
+		self.sound(Some(self.entity_types.sounds["DSSLOP"].clone()), corpse);
+		raise(world, corpse);
+
+		None
+	}
+
+	fn walk(
+		&mut self,
+		entity: Entity,
+		walk: Walk,
+	) -> Option<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>)> {
+		match walk {
+			Walk::Moved(position, crossed) => Some((position, crossed)),
+			Walk::Door(linedef_entity) => {
+				self.use_event_channel.single_write(UseEvent {
+					entity,
+					linedef_entity,
+				});
+				None
+			}
+			Walk::Blocked => None,
+		}
+	}
+
+	// Decides whether to fire a missile, with less chance the further away the target is
+	fn check_missile_range(
+		&mut self,
+		entity: Entity,
+		monster: &mut Monster,
+		target: Entity,
+		has_melee: bool,
+	) -> bool {
+		if !can_see(&self.tracer, entity, target) {
+			return false;
+		}
+
+		// Fight back straight away after being hurt
+		if monster.just_hit {
+			monster.just_hit = false;
+			return true;
+		}
+
+		if monster.reaction_time > 0 {
+			return false;
+		}
+
+		let world = self.tracer.world;
+		let position = world.get_component::<Transform>(entity).unwrap().position;
+		let target_position = world.get_component::<Transform>(target).unwrap().position;
+		let mut distance = (Vector2::new(target_position[0], target_position[1])
+			- Vector2::new(position[0], position[1]))
+		.norm() - MELEE_RANGE;
+
+		// Monsters that can't fight up close prefer to keep their distance
+		if !has_melee {
+			distance -= 128.0;
+		}
+
+		if distance < monster.min_missile_range
+			|| monster
+				.max_missile_range
+				.map_or(false, |range| distance > range)
+		{
+			return false;
+		}
+
+		distance = (distance * monster.missile_distance_scale).min(monster.missile_distance_cap);
+		(self.rng.gen::<u8>() as f32) >= distance
+	}
+
+	// The target, if it's close enough to hit up close
+	fn melee_target(&self, entity: Entity, monster: &Monster) -> Option<Entity> {
+		monster
+			.target
+			.filter(|&target| check_melee_range(&self.tracer, entity, target))
+	}
+
+	fn melee(&mut self, entity: Entity, target: Entity, damage: i32) {
+		self.damage_event_channel.single_write(DamageEvent {
+			source: Some(entity),
+			inflictor: Some(entity),
+			target,
+			amount: damage,
+			damage_type: DamageType::Melee,
+		});
+	}
+
+	// Fires missiles at the target, turned by each of the offsets
+	fn missile(
+		&mut self,
+		entity: Entity,
+		monster: &Monster,
+		name: &str,
+		height: f32,
+		offsets: &[i32],
+	) {
+		let world = self.tracer.world;
+		let target = match monster.target {
+			Some(x) => x,
+			None => return,
+		};
+		let position = world.get_component::<Transform>(entity).unwrap().position
+			+ Vector3::new(0.0, 0.0, height);
+		let target_position = match world.get_component::<Transform>(target) {
+			Some(transform) => transform.position,
+			None => return,
+		};
+
+		let delta = target_position - position;
+		let distance = Vector2::new(delta[0], delta[1]).norm();
+		let mut yaw = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64));
+		let slope = if distance > 0.0 {
+			delta[2] / distance
+		} else {
+			0.0
+		};
+
+		if is_fuzzy(world, target) {
+			yaw += (self.rng.gen::<u8>() as i32 - self.rng.gen::<u8>() as i32) << 20;
+		}
+
+		for &offset in offsets {
+			if let Some(sound) = spawn_projectile(
+				self.asset_storage,
+				self.entity_types,
+				self.command_buffer,
+				name,
+				entity,
+				position,
+				yaw + offset,
+				slope,
+			) {
+				self.sound_queue.push(sound);
+			}
+		}
+	}
+
+	// Carries a lost soul's charge on for a frame, returning its new position and the linedefs it
+	// crossed. It bounces off floors and ceilings, and stops when it hits anything else.
+	fn charge(
+		&mut self,
+		entity: Entity,
+		monster: &mut Monster,
+		delta: Duration,
+	) -> Option<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>)> {
+		let world = self.tracer.world;
+		let mut velocity = monster.charge?;
+
+		// Dead lost souls drop out of the charge
+		if !is_shootable(world, entity) {
+			monster.charge = None;
+			return None;
+		}
+
+		let position = world.get_component::<Transform>(entity).unwrap().position;
+		let bbox = {
+			let box_collider = world.get_component::<BoxCollider>(entity).unwrap();
+			AABB3::from_radius_height(box_collider.radius, box_collider.height)
+		};
+		let trace = self.tracer.trace(
+			&bbox.offset(position),
+			velocity * delta.as_secs_f32(),
+			SolidMask::MONSTER,
+		);
+
+		let mut crossed = SmallVec::new();
+		find_crossed_linedefs(&self.tracer, position, trace.move_step, &mut crossed);
+
+		if let Some(collision) = trace.collision {
+			if collision.normal[2] != 0.0 {
+				velocity[2] = -velocity[2];
+				monster.charge = Some(velocity);
+			} else {
+				if is_shootable(world, collision.entity) {
+					let damage = (self.rng.gen::<u8>() % 8 + 1) as i32 * SKULL_DAMAGE;
+					self.melee(entity, collision.entity, damage);
+				}
+
+				monster.charge = None;
+				set_state(world, entity, |state_machine| Some(state_machine.spawn));
+			}
+		}
+
+		Some((position + trace.move_step, crossed))
+	}
+
+	// A_PainShootSkull: spits out a lost soul in front, which charges straight at the target
+	fn shoot_skull(&mut self, entity: Entity, monster: &Monster, yaw: Angle) {
+		let world = self.tracer.world;
+		let asset_storage = self.asset_storage;
+		let template = asset_storage
+			.get(&self.entity_types.names["SKULL"])
+			.unwrap();
+
+		// Lost souls are recognised by their spawn state
+		let skull_spawn = template.get_component::<StateMachine>().unwrap().spawn;
+		let count = <Read<StateMachine>>::query()
+			.iter(world)
+			.filter(|state_machine| state_machine.spawn == skull_spawn)
+			.count();
+
+		if count > MAX_SKULLS {
+			return;
+		}
+
+		let (position, radius) = match (
+			world.get_component::<Transform>(entity),
+			world.get_component::<BoxCollider>(entity),
+		) {
+			(Some(transform), Some(box_collider)) => (transform.position, box_collider.radius),
+			_ => return,
+		};
+		let skull_collider = *template.get_component::<BoxCollider>().unwrap();
+		let bbox = AABB3::from_radius_height(skull_collider.radius, skull_collider.height);
+
+		// Far enough in front that the two don't overlap, checking that the way there is clear
+		let start = position + Vector3::new(0.0, 0.0, 8.0);
+		let prestep = 4.0 + 3.0 * (radius + skull_collider.radius) / 2.0;
+		let move_step = Vector3::new(yaw.cos() as f32, yaw.sin() as f32, 0.0) * prestep;
+		let trace = self
+			.tracer
+			.trace(&bbox.offset(start), move_step, SolidMask::MONSTER);
+
+		let skull = self.command_buffer.insert((), vec![()])[0];
+		template.add_to_entity(skull, self.command_buffer);
+		self.command_buffer.add_component(
+			skull,
+			Transform {
+				position: start + move_step,
+				rotation: Vector3::new(0.into(), 0.into(), yaw),
+			},
+		);
+
+		// Spat out into a wall or another monster, so it dies straight away
+		if trace.collision.is_some() {
+			self.damage_event_channel.single_write(DamageEvent {
+				source: Some(entity),
+				inflictor: Some(entity),
+				target: skull,
+				amount: 10000,
+				damage_type: DamageType::Telefrag,
+			});
+			return;
+		}
+
+		let target = match monster.target {
+			Some(x) => x,
+			None => return,
+		};
+		let skull_monster = template.get_component::<Monster>().unwrap();
+		let charge_yaw = match world.get_component::<Transform>(target) {
+			Some(transform) => {
+				let delta = transform.position - (start + move_step);
+				Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64))
+			}
+			None => return,
+		};
+
+		self.command_buffer.add_component(
+			skull,
+			Monster {
+				target: Some(target),
+				charge: charge_velocity(world, start + move_step, charge_yaw, target),
+				..skull_monster.clone()
+			},
+		);
+
+		if let Some(sound) = skull_monster.attack_sound.clone() {
+			self.sound_queue.push((sound, skull));
+		}
+	}
+
+	// A_VileTarget: conjures up fire in front of the target, which follows it around until the
+	// arch-vile attacks
+	fn spawn_fire(&mut self, entity: Entity, target: Entity) {
+		let (position, yaw) = match self.tracer.world.get_component::<Transform>(target) {
+			Some(transform) => (transform.position, transform.rotation[2]),
+			None => return,
+		};
+		let asset_storage = self.asset_storage;
+		let template = asset_storage.get(&self.entity_types.names["FIRE"]).unwrap();
+
+		let fire = self.command_buffer.insert((), vec![()])[0];
+		template.add_to_entity(fire, self.command_buffer);
+		self.command_buffer.add_component(
+			fire,
+			Transform {
+				position: position + Vector3::new(yaw.cos() as f32, yaw.sin() as f32, 0.0) * 24.0,
+				rotation: Vector3::new(0.into(), 0.into(), 0.into()),
+			},
+		);
+		self.command_buffer.add_component(
+			fire,
+			VileFire {
+				vile: entity,
+				target,
+			},
+		);
+	}
+
+	// A_VileAttack: blasts the target into the air, and sets off the fire between the two
+	fn vile_attack(&mut self, entity: Entity, target: Entity) {
+		let world = self.tracer.world;
+
+		if !can_see(&self.tracer, entity, target) {
+			return;
+		}
+
+		self.sound(Some(self.entity_types.sounds["DSBAREXP"].clone()), entity);
+		self.damage_event_channel.single_write(DamageEvent {
+			source: Some(entity),
+			inflictor: Some(entity),
+			target,
+			amount: 20,
+			damage_type: DamageType::Explosion,
+		});
+
+		if let Some(mut velocity) = unsafe { world.get_component_mut_unchecked::<Velocity>(target) }
+		{
+			velocity.velocity[2] = VILE_THRUST;
+		}
+
+		let fire = match <Read<VileFire>>::query()
+			.iter_entities(world)
+			.find(|(_, vile_fire)| vile_fire.vile == entity)
+		{
+			Some((fire, _)) => fire,
+			None => return,
+		};
+		let yaw = world.get_component::<Transform>(entity).unwrap().rotation[2];
+		let position = world.get_component::<Transform>(target).unwrap().position
+			- Vector3::new(yaw.cos() as f32, yaw.sin() as f32, 0.0) * 24.0;
+
+		if let Some(mut transform) = unsafe { world.get_component_mut_unchecked::<Transform>(fire) }
+		{
+			transform.position = position;
+		}
+
+		radius_attack(
+			&self.tracer,
+			self.damage_event_channel,
+			fire,
+			position,
+			Some(entity),
+			70,
+		);
+	}
+
+	// A_CPosRefire and A_SpidRefire: keep firing unless the target is out of sight
+	fn refire(&mut self, entity: Entity, monster: &mut Monster, chance: u8) {
+		let world = self.tracer.world;
+		self.face_target(entity, monster);
+
+		if self.rng.gen::<u8>() < chance {
+			return;
+		}
+
+		let lost = match monster.target {
+			Some(target) => !is_shootable(world, target) || !can_see(&self.tracer, entity, target),
+			None => true,
+		};
+
+		if lost {
+			set_state(world, entity, |state_machine| state_machine.see);
+		}
+	}
+
+	// Faces the target, keeping the monster's own copy of the ambush flag in step
+	fn face_target(&mut self, entity: Entity, monster: &mut Monster) {
+		face_target(self.tracer.world, self.rng, entity);
+		monster.ambush = false;
+	}
+
+	fn sound(&mut self, sound: Option<AssetHandle<Sound>>, entity: Entity) {
+		if let Some(sound) = sound {
+			self.sound_queue.push((sound, entity));
+		}
+	}
+}
+
+// A_FaceTarget
+pub fn face_target(world: &World, rng: &mut Pcg64Mcg, entity: Entity) {
+	let target = {
+		let mut monster = match unsafe { world.get_component_mut_unchecked::<Monster>(entity) } {
+			Some(x) => x,
+			None => return,
+		};
+
+		// A monster that has started attacking is no longer lying in ambush
+		monster.ambush = false;
+
+		match monster.target {
+			Some(x) => x,
+			None => return,
+		}
+	};
+	let target_position = match world.get_component::<Transform>(target) {
+		Some(transform) => transform.position,
+		None => return,
+	};
+	let mut transform = match unsafe { world.get_component_mut_unchecked::<Transform>(entity) } {
+		Some(x) => x,
+		None => return,
+	};
+
+	let delta = target_position - transform.position;
+	transform.rotation[2] = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64));
+
+	// Partially invisible targets are harder to aim at
+	if is_fuzzy(world, target) {
+		transform.rotation[2] += (rng.gen::<u8>() as i32 - rng.gen::<u8>() as i32) << 21;
+	}
+}
+
+// A_SkullAttack: the velocity of a lost soul charging in a direction, climbing or diving to meet
+// the middle of the target by the time it gets there
+fn charge_velocity(
+	world: &World,
+	position: Vector3<f32>,
+	yaw: Angle,
+	target: Entity,
+) -> Option<Vector3<f32>> {
+	let target_middle = entity_bbox(world, target)?.middle();
+	let distance = (Vector2::new(target_middle[0], target_middle[1])
+		- Vector2::new(position[0], position[1]))
+	.norm();
+	let frames = (distance / SKULL_SPEED).max(1.0);
+
+	Some(
+		Vector3::new(
+			yaw.cos() as f32 * SKULL_SPEED,
+			yaw.sin() as f32 * SKULL_SPEED,
+			(target_middle[2] - position[2]) / frames,
+		) * FRAME_RATE,
+	)
+}
+
+// A_Fire: keeps an arch-vile's fire in front of its target, as long as the arch-vile can see it
+fn follow_target(tracer: &EntityTracer, fire: Entity) {
+	let world = tracer.world;
+	let vile_fire = match world.get_component::<VileFire>(fire) {
+		Some(x) => *x,
+		None => return,
+	};
+
+	if !can_see(tracer, vile_fire.vile, vile_fire.target) {
+		return;
+	}
+
+	let (position, yaw) = match world.get_component::<Transform>(vile_fire.target) {
+		Some(transform) => (transform.position, transform.rotation[2]),
+		None => return,
+	};
+
+	if let Some(mut transform) = unsafe { world.get_component_mut_unchecked::<Transform>(fire) } {
+		transform.position =
+			position + Vector3::new(yaw.cos() as f32, yaw.sin() as f32, 0.0) * 24.0;
+	}
+}
+
+// PIT_VileCheck: finds a corpse that has finished dying next to where the arch-vile is about to
+// step, and that has room to get back up
+fn find_corpse(tracer: &EntityTracer, entity: Entity, monster: &Monster) -> Option<Entity> {
+	let world = tracer.world;
+	let angle = move_dir_angle(monster.move_dir?);
+	let (position, radius) = {
+		let transform = world.get_component::<Transform>(entity)?;
+		let box_collider = world.get_component::<BoxCollider>(entity)?;
+		(transform.position, box_collider.radius)
+	};
+	let ahead = Vector2::new(position[0], position[1])
+		+ Vector2::new(angle.cos() as f32, angle.sin() as f32) * monster.speed;
+
+	// Allow for the radius of the corpses
+	let range = radius + 64.0;
+	let bbox = AABB2::from_extents(
+		ahead[1] + range,
+		ahead[1] - range,
+		ahead[0] - range,
+		ahead[0] + range,
+	);
+	let mut found = None;
+
+	tracer
+		.quadtree
+		.traverse_nodes(&bbox, &mut |entities: &[Entity]| {
+			for &corpse in entities {
+				if found.is_some() {
+					return;
+				}
+
+				if world
+					.get_component::<Health>(corpse)
+					.map_or(true, |health| health.current > 0)
+				{
+					continue;
+				}
+
+				// Still falling over, or can't be raised
+				match world.get_component::<StateMachine>(corpse) {
+					Some(state_machine)
+						if state_machine.time_left.is_none() && state_machine.raise.is_some() => {}
+					_ => continue,
+				}
+
+				let (corpse_position, box_collider) = match (
+					world.get_component::<Transform>(corpse),
+					world.get_component::<BoxCollider>(corpse),
+				) {
+					(Some(transform), Some(box_collider)) => (transform.position, *box_collider),
+					_ => continue,
+				};
+
+				// Crushed corpses are gone for good
+				if box_collider.radius == 0.0 {
+					continue;
+				}
+
+				let max_distance = box_collider.radius + radius;
+
+				if (corpse_position[0] - ahead[0]).abs() > max_distance
+					|| (corpse_position[1] - ahead[1]).abs() > max_distance
+				{
+					continue;
+				}
+
+				// Corpses are a quarter of their height
+				let raised_bbox =
+					AABB3::from_radius_height(box_collider.radius, box_collider.height * 4.0)
+						.offset(corpse_position);
+
+				if has_room(tracer, corpse, &raised_bbox) {
+					found = Some(corpse);
+				}
+			}
+		});
+
+	found
+}
+
+// Checks that nothing solid is in the way of an entity standing up in the given box
+fn has_room(tracer: &EntityTracer, entity: Entity, bbox: &AABB3) -> bool {
+	let world = tracer.world;
+	let middle = bbox.middle();
+	let sector_index = tracer
+		.map
+		.find_subsector(Vector2::new(middle[0], middle[1]))
+		.sector_index;
+
+	if tracer.map_dynamic.sectors[sector_index].interval.max < bbox[2].max {
+		return false;
+	}
+
+	let mut room = true;
+
+	tracer
+		.quadtree
+		.traverse_nodes(&AABB2::from(bbox), &mut |entities: &[Entity]| {
+			for &other in entities {
+				if other == entity {
+					continue;
+				}
+
+				let box_collider = match world.get_component::<BoxCollider>(other) {
+					Some(x) => *x,
+					None => continue,
+				};
+
+				if !box_collider.solid_mask.intersects(SolidMask::MONSTER) {
+					continue;
+				}
+
+				if let Some(other_bbox) = entity_bbox(world, other) {
+					if bbox.overlaps(&other_bbox) {
+						room = false;
+					}
+				}
+			}
+		});
+
+	room
+}
+
+// Brings a corpse back to life, as it was when it first spawned
+fn raise(world: &World, corpse: Entity) {
+	if let Some(mut health) = unsafe { world.get_component_mut_unchecked::<Health>(corpse) } {
+		health.current = health.max;
+		health.killer = None;
+	}
+
+	if let Some(mut box_collider) =
+		unsafe { world.get_component_mut_unchecked::<BoxCollider>(corpse) }
+	{
+		box_collider.height *= 4.0;
+		box_collider.solid_mask = SolidMask::all();
+	}
+
+	if let Some(mut monster) = unsafe { world.get_component_mut_unchecked::<Monster>(corpse) } {
+		monster.target = None;
+	}
+
+	set_state(world, corpse, |state_machine| state_machine.raise);
+}
+
+// The last entity whose noise reached the sector the monster is in
+fn sound_target(tracer: &EntityTracer, entity: Entity) -> Option<Entity> {
+	let position = tracer.world.get_component::<Transform>(entity)?.position;
+	let subsector = tracer
+		.map
+		.find_subsector(Vector2::new(position[0], position[1]));
+	tracer.map_dynamic.sectors[subsector.sector_index].sound_target
+}
+
+// P_NoiseAlert: alerts monsters in all the sectors that a noise made by an entity can reach
+pub fn noise_alert(world: &World, asset_storage: &AssetStorage, source: Entity) {
+	let position = match world.get_component::<Transform>(source) {
+		Some(transform) => transform.position,
+		None => return,
+	};
+	let map_entity = match <Read<MapDynamic>>::query().iter_entities(world).next() {
+		Some((entity, _)) => entity,
+		None => return,
+	};
+	let mut map_dynamic =
+		unsafe { world.get_component_mut_unchecked::<MapDynamic>(map_entity) }.unwrap();
+	let map = asset_storage.get(&map_dynamic.map).unwrap();
+	let sector_index = map
+		.find_subsector(Vector2::new(position[0], position[1]))
+		.sector_index;
+
+	noise_flood(map, &mut map_dynamic, sector_index, source);
+}
+
+// P_RecursiveSound: floods a noise out from a sector through the open two-sided linedefs,
+// marking each sector it reaches. Sound-blocking linedefs muffle the noise, so that it stops at
+// the second one it crosses.
+pub fn noise_flood(map: &Map, map_dynamic: &mut MapDynamic, sector_index: usize, source: Entity) {
+	fn flood(
+		map: &Map,
+		map_dynamic: &mut MapDynamic,
+		traversed: &mut [Option<u32>],
+		sector_index: usize,
+		sound_blocks: u32,
+		source: Entity,
+	) {
+		// Already reached without being muffled more
+		if traversed[sector_index].map_or(false, |blocks| blocks <= sound_blocks) {
+			return;
+		}
+
+		traversed[sector_index] = Some(sound_blocks);
+		map_dynamic.sectors[sector_index].sound_target = Some(source);
+
+		for &neighbour in map.sectors[sector_index].neighbours.iter() {
+			for &linedef_index in map.sectors[sector_index].linedefs.iter() {
+				let linedef = &map.linedefs[linedef_index];
+
+				let sector_indices = match &linedef.sidedefs {
+					[Some(front), Some(back)] => [front.sector_index, back.sector_index],
+					_ => continue,
+				};
+
+				if sector_indices != [sector_index, neighbour]
+					&& sector_indices != [neighbour, sector_index]
+				{
+					continue;
+				}
+
+				// Closed doors stop noises
+				let opening = map_dynamic.sectors[sector_index]
+					.interval
+					.intersection(map_dynamic.sectors[neighbour].interval);
+
+				if opening.is_empty_or_point() {
+					continue;
+				}
+
+				if linedef.flags.contains(LinedefFlags::BLOCKSOUND) {
+					if sound_blocks == 0 {
+						flood(map, map_dynamic, traversed, neighbour, 1, source);
+					}
+				} else {
+					flood(map, map_dynamic, traversed, neighbour, sound_blocks, source);
+				}
+			}
+		}
+	}
+
+	let mut traversed = vec![None; map.sectors.len()];
+	flood(map, map_dynamic, &mut traversed, sector_index, 0, source);
+}
+
+// Finds a player that the monster can see. Unless it looks all around,
+// players behind it are only noticed when they're close.
+fn look_for_players(
+	tracer: &EntityTracer,
+	entity: Entity,
+	players: &[Entity],
+	all_around: bool,
+) -> Option<Entity> {
+	let (position, yaw) = {
+		let transform = tracer.world.get_component::<Transform>(entity)?;
+		(transform.position, transform.rotation[2])
+	};
+
+	players.iter().copied().find(|&player| {
+		if !is_shootable(tracer.world, player) || !can_see(tracer, entity, player) {
+			return false;
+		}
+
+		if !all_around {
+			let player_position = tracer
+				.world
+				.get_component::<Transform>(player)
+				.unwrap()
+				.position;
+			let delta = Vector2::new(player_position[0], player_position[1])
+				- Vector2::new(position[0], position[1]);
+			let angle = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64)) - yaw;
+			let behind = angle.0 > 1 << 30 || angle.0 < -(1 << 30);
+
+			if behind && delta.norm() > MELEE_RANGE {
+				return false;
+			}
+		}
+
+		true
+	})
+}
+
+// Monsters look from three quarters of their height
+fn can_see(tracer: &EntityTracer, entity: Entity, target: Entity) -> bool {
+	let start = match tracer.world.get_component::<Transform>(entity) {
+		Some(transform) => {
+			let height = tracer
+				.world
+				.get_component::<BoxCollider>(entity)
+				.map_or(0.0, |box_collider| box_collider.height);
+			transform.position + Vector3::new(0.0, 0.0, height * 0.75)
+		}
+		None => return false,
+	};
+
+	match entity_bbox(tracer.world, target) {
+		Some(target_bbox) => check_sight(tracer, entity, start, &target_bbox),
+		None => false,
+	}
+}
+
+fn check_melee_range(tracer: &EntityTracer, entity: Entity, target: Entity) -> bool {
+	let (bbox, target_bbox) = match (
+		entity_bbox(tracer.world, entity),
+		entity_bbox(tracer.world, target),
+	) {
+		(Some(bbox), Some(target_bbox)) => (bbox, target_bbox),
+		_ => return false,
+	};
+
+	let (middle, target_middle) = (bbox.middle(), target_bbox.middle());
+	let distance = (Vector2::new(target_middle[0], target_middle[1])
+		- Vector2::new(middle[0], middle[1]))
+	.norm();
+	let target_radius = (target_bbox[0].max - target_bbox[0].min) * 0.5;
+
+	distance < MELEE_RANGE - 20.0 + target_radius && can_see(tracer, entity, target)
+}
+
+fn is_fuzzy(world: &World, entity: Entity) -> bool {
+	world
+		.get_component::<SpriteRender>(entity)
+		.map_or(false, |sprite_render| {
+			sprite_render.render_style == RenderStyle::Fuzz
+		})
+}
+
+fn set_state(world: &World, entity: Entity, state: impl FnOnce(&StateMachine) -> Option<usize>) {
+	if let Some(mut state_machine) =
+		unsafe { world.get_component_mut_unchecked::<StateMachine>(entity) }
+	{
+		if let Some(state) = state(&state_machine) {
+			state_machine.next = Some(state);
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Walk {
+	// The new position, and the linedefs crossed on the way there
+	Moved(Vector3<f32>, SmallVec<[(Entity, Side); 4]>),
+	// Blocked by a door that the monster can open
+	Door(Entity),
+	Blocked,
+}
+
+const EAST: usize = 0;
+const NORTH: usize = 2;
+const WEST: usize = 4;
+const SOUTH: usize = 6;
+
+// Indexed by whether the target is south, and whether it is east
+const DIAGONALS: [usize; 4] = [3, 1, 5, 7];
+
+fn move_dir_angle(move_dir: usize) -> Angle {
+	Angle((move_dir as u32).wrapping_mul(1 << 29) as i32)
+}
+
+// Keeps walking in the current direction, choosing a new one every so often or when blocked
+fn chase_move(
+	tracer: &EntityTracer,
+	rng: &mut Pcg64Mcg,
+	monster: &mut Monster,
+	bbox: &AABB3,
+	position: Vector3<f32>,
+	target_position: Vector3<f32>,
+) -> Walk {
+	monster.move_count -= 1;
+
+	if monster.move_count >= 0 {
+		let walk = step(tracer, monster, bbox, position);
+
+		if walk != Walk::Blocked {
+			return walk;
+		}
+	}
+
+	new_chase_dir(tracer, rng, monster, bbox, position, target_position)
+}
+
+// P_NewChaseDir: heads towards the target if possible, otherwise tries the other directions,
+// avoiding turning around
+fn new_chase_dir(
+	tracer: &EntityTracer,
+	rng: &mut Pcg64Mcg,
+	monster: &mut Monster,
+	bbox: &AABB3,
+	position: Vector3<f32>,
+	target_position: Vector3<f32>,
+) -> Walk {
+	let old_dir = monster.move_dir;
+	let turnaround = old_dir.map(|dir| (dir + 4) % 8);
+	let delta = target_position - position;
+
+	let mut dirs = [
+		if delta[0] > 10.0 {
+			Some(EAST)
+		} else if delta[0] < -10.0 {
+			Some(WEST)
+		} else {
+			None
+		},
+		if delta[1] < -10.0 {
+			Some(SOUTH)
+		} else if delta[1] > 10.0 {
+			Some(NORTH)
+		} else {
+			None
+		},
+	];
+
+	// Try going straight at the target
+	if let [Some(_), Some(_)] = dirs {
+		let dir = DIAGONALS[((delta[1] < 0.0) as usize) << 1 | (delta[0] > 0.0) as usize];
+
+		if Some(dir) != turnaround {
+			let walk = try_walk(tracer, rng, monster, bbox, position, dir);
+
+			if walk != Walk::Blocked {
+				return walk;
+			}
+		}
+	}
+
+	// Try the two directions closest to the target, usually the one with the furthest to go first
+	if rng.gen::<u8>() > 200 || delta[1].abs() > delta[0].abs() {
+		dirs.swap(0, 1);
+	}
+
+	let mut candidates: SmallVec<[usize; 12]> = dirs
+		.iter()
+		.flatten()
+		.copied()
+		.filter(|&dir| Some(dir) != turnaround)
+		.collect();
+
+	// Then keep going the same way
+	candidates.extend(old_dir);
+
+	// Then search all directions from a random end, and only turn around as a last resort
+	if rng.gen::<u8>() & 1 != 0 {
+		candidates.extend((0..8).filter(|&dir| Some(dir) != turnaround));
+	} else {
+		candidates.extend((0..8).rev().filter(|&dir| Some(dir) != turnaround));
+	}
+
+	candidates.extend(turnaround);
+
+	for dir in candidates {
+		let walk = try_walk(tracer, rng, monster, bbox, position, dir);
+
+		if walk != Walk::Blocked {
+			return walk;
+		}
+	}
+
+	monster.move_dir = None;
+	Walk::Blocked
+}
+
+// Sets the monster's direction and tries to take a step, picking a random number of steps to
+// continue for if it can move
+fn try_walk(
+	tracer: &EntityTracer,
+	rng: &mut Pcg64Mcg,
+	monster: &mut Monster,
+	bbox: &AABB3,
+	position: Vector3<f32>,
+	dir: usize,
+) -> Walk {
+	monster.move_dir = Some(dir);
+	let walk = step(tracer, monster, bbox, position);
+
+	if walk != Walk::Blocked {
+		monster.move_count = (rng.gen::<u8>() & 15) as i32;
+	}
+
+	walk
+}
+
+// P_Move: takes one step in the current direction
+fn step(tracer: &EntityTracer, monster: &Monster, bbox: &AABB3, position: Vector3<f32>) -> Walk {
+	let angle = match monster.move_dir {
+		Some(dir) => move_dir_angle(dir),
+		None => return Walk::Blocked,
+	};
+	let move_step = Vector3::new(angle.cos() as f32, angle.sin() as f32, 0.0) * monster.speed;
+
+	match try_move(tracer, bbox, position, move_step) {
+		Ok((position, crossed)) => Walk::Moved(position, crossed),
+		Err(Some(entity)) if is_monster_door(tracer, entity) => Walk::Door(entity),
+		Err(_) => Walk::Blocked,
+	}
+}
+
+// P_TryMove for monsters: moves horizontally, stepping up ledges no higher than STEP_HEIGHT,
+// and then down onto the ground below. Refuses to move into anything or to walk off a ledge that
+// is too high. On success, returns the new position and the linedefs crossed on the way, on
+// failure what blocked the move.
+fn try_move(
+	tracer: &EntityTracer,
+	bbox: &AABB3,
+	position: Vector3<f32>,
+	move_step: Vector3<f32>,
+) -> Result<(Vector3<f32>, SmallVec<[(Entity, Side); 4]>), Option<Entity>> {
+	let trace = tracer.trace(&bbox.offset(position), move_step, SolidMask::MONSTER);
+	let mut new_position = position + trace.move_step;
+
+	if let Some(collision) = trace.collision {
+		let step_height = match collision.step_z {
+			Some(step_z) if step_z - position[2] <= STEP_HEIGHT => step_z - position[2],
+			_ => return Err(Some(collision.entity)),
+		};
+
+		let trace = tracer.trace(
+			&bbox.offset(position),
+			Vector3::new(0.0, 0.0, step_height),
+			SolidMask::MONSTER,
+		);
+
+		if trace.collision.is_some() {
+			return Err(Some(collision.entity));
+		}
+
+		let raised = position + trace.move_step;
+		let trace = tracer.trace(&bbox.offset(raised), move_step, SolidMask::MONSTER);
+
+		if let Some(collision) = trace.collision {
+			return Err(Some(collision.entity));
+		}
+
+		new_position = raised + trace.move_step;
+	}
+
+	// Find the ground again, no further below than a step from where the monster started
+	let drop = new_position[2] - position[2] + STEP_HEIGHT;
+	let trace = tracer.trace(
+		&bbox.offset(new_position),
+		Vector3::new(0.0, 0.0, -drop),
+		SolidMask::MONSTER,
+	);
+
+	if trace.collision.is_none() {
+		return Err(None);
+	}
+
+	let mut crossed = SmallVec::new();
+	find_crossed_linedefs(tracer, position, new_position - position, &mut crossed);
+
+	Ok((new_position + trace.move_step, crossed))
+}
+
+// Monsters can open doors that need no key, unless they're marked as secret
+fn is_monster_door(tracer: &EntityTracer, entity: Entity) -> bool {
+	let world = tracer.world;
+
+	let unlocked = match world.get_component::<UseAction>(entity).as_deref() {
+		Some(UseAction::DoorUse(door_use)) => door_use.lock.is_none(),
+		_ => false,
+	};
+
+	unlocked
+		&& world
+			.get_component::<LinedefRef>(entity)
+			.map_or(false, |linedef_ref| {
+				!tracer.map.linedefs[linedef_ref.index]
+					.flags
+					.contains(LinedefFlags::SECRET)
+			})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::doom::{
+		data::FRAME_TIME,
+		map::test_map::{TestMap, TestSector},
+		wad::WadLoader,
+	};
+	use fnv::FnvHashMap;
+
+	fn monster() -> Monster {
+		Monster {
+			speed: 8.0,
+			reaction_time: 8,
+			target: None,
+			move_dir: None,
+			move_count: 0,
+			just_attacked: false,
+			just_hit: false,
+			ambush: false,
+			charge: None,
+			min_missile_range: 0.0,
+			max_missile_range: None,
+			missile_distance_scale: 1.0,
+			missile_distance_cap: 200.0,
+			see_sound: None,
+			attack_sound: None,
+			pain_sound: None,
+			death_sound: None,
+			active_sound: None,
+		}
+	}
+
+	fn collider() -> BoxCollider {
+		BoxCollider {
+			height: 56.0,
+			radius: 20.0,
+			solid_mask: SolidMask::all(),
+		}
+	}
+
+	fn move_entity(test_map: &mut TestMap, entity: Entity, position: Vector3<f32>) {
+		let box_collider = collider();
+		let bbox = AABB3::from_radius_height(box_collider.radius, box_collider.height);
+		test_map
+			.world
+			.get_component_mut::<Transform>(entity)
+			.unwrap()
+			.position = position;
+		test_map.quadtree.remove(entity);
+		test_map
+			.quadtree
+			.insert(entity, &AABB2::from(&bbox.offset(position)));
+	}
+
+	// Chases the player for a number of steps, returning the monster's positions along the way
+	fn chase(test_map: &mut TestMap, start: Vector3<f32>, steps: usize) -> Vec<Vector3<f32>> {
+		let player = test_map.spawn(Vector3::new(900.0, 0.0, 0.0), collider(), Some(100));
+		let entity = test_map.spawn(start, collider(), Some(20));
+		let bbox = AABB3::from_radius_height(collider().radius, collider().height);
+		let mut rng = Pcg64Mcg::new(0xcafe_f00d_d15e_a5e5);
+		let mut monster = monster();
+		let mut positions = vec![start];
+
+		for _ in 0..steps {
+			let position = *positions.last().unwrap();
+
+			if test_map.with_tracer(|tracer| check_melee_range(tracer, entity, player)) {
+				break;
+			}
+
+			let target_position = test_map
+				.world
+				.get_component::<Transform>(player)
+				.unwrap()
+				.position;
+			let walk = test_map.with_tracer(|tracer| {
+				chase_move(
+					tracer,
+					&mut rng,
+					&mut monster,
+					&bbox,
+					position,
+					target_position,
+				)
+			});
+
+			if let Walk::Moved(position, _) = walk {
+				move_entity(test_map, entity, position);
+				positions.push(position);
+			}
+		}
+
+		positions
+	}
+
+	#[test]
+	fn reaches_player_on_flat_floor() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(512.0, 0.0, 128.0),
+			TestSector::new(512.0, 0.0, 128.0),
+		]);
+		let positions = chase(&mut test_map, Vector3::new(64.0, 0.0, 0.0), 300);
+
+		assert!(positions.last().unwrap()[0] > 900.0 - MELEE_RANGE);
+	}
+
+	#[test]
+	fn reaches_player_over_steps() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(300.0, 0.0, 128.0),
+			TestSector::new(300.0, 16.0, 128.0),
+			TestSector::new(400.0, 0.0, 128.0),
+		]);
+		let positions = chase(&mut test_map, Vector3::new(64.0, 0.0, 0.0), 300);
+		let last = positions.last().unwrap();
+
+		assert!(last[0] > 900.0 - MELEE_RANGE);
+		assert!(last[2].abs() < 1.0);
+
+		// It climbed the step in between
+		assert!(positions
+			.iter()
+			.any(|position| position[0] > 320.0 && position[0] < 580.0 && position[2] > 15.0));
+	}
+
+	#[test]
+	fn blocked_by_high_step() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(400.0, 0.0, 128.0),
+			TestSector::new(600.0, 32.0, 128.0),
+		]);
+		let positions = chase(&mut test_map, Vector3::new(64.0, 0.0, 0.0), 200);
+
+		assert!(positions
+			.iter()
+			.all(|position| position[0] < 400.0 && position[2].abs() < 1.0));
+	}
+
+	#[test]
+	fn wont_walk_off_ledge() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(400.0, 64.0, 192.0),
+			TestSector::new(600.0, 0.0, 192.0),
+		]);
+		let positions = chase(&mut test_map, Vector3::new(64.0, 0.0, 64.0), 200);
+
+		// The box can hang over the edge, but the monster stays on the ledge
+		assert!(positions
+			.iter()
+			.all(|position| position[0] <= 400.0 + 20.0 && (position[2] - 64.0).abs() < 1.0));
+	}
+
+	#[test]
+	fn reports_crossed_linedefs() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(512.0, 0.0, 128.0),
+			TestSector::new(512.0, 0.0, 128.0),
+		]);
+		let handle = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.map
+			.clone();
+
+		// Give the linedef between the two sectors a special
+		let index = {
+			let map = test_map.asset_storage.get_mut(&handle).unwrap();
+			let index = map
+				.linedefs
+				.iter()
+				.position(|linedef| linedef.flags.contains(LinedefFlags::TWOSIDED))
+				.unwrap();
+			map.linedefs[index].special_type = 88;
+			index
+		};
+		let linedef_entity = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.linedefs[index]
+			.entity;
+
+		let bbox = AABB3::from_radius_height(collider().radius, collider().height);
+		let (position, crossed) = test_map
+			.with_tracer(|tracer| {
+				try_move(
+					tracer,
+					&bbox,
+					Vector3::new(500.0, 0.0, 0.0),
+					Vector3::new(16.0, 0.0, 0.0),
+				)
+			})
+			.unwrap();
+
+		assert!((position[0] - 516.0).abs() < 0.01);
+		assert_eq!(crossed.as_slice(), &[(linedef_entity, Side::Right)]);
+
+		// Stepping back crosses it the other way
+		let (_, crossed) = test_map
+			.with_tracer(|tracer| try_move(tracer, &bbox, position, Vector3::new(-16.0, 0.0, 0.0)))
+			.unwrap();
+
+		assert_eq!(crossed.as_slice(), &[(linedef_entity, Side::Left)]);
+	}
+
+	#[test]
+	fn looks_ahead_for_players() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(512.0, 0.0, 128.0),
+			TestSector::new(512.0, 0.0, 128.0),
+		]);
+		let player = test_map.spawn(Vector3::new(700.0, 0.0, 0.0), collider(), Some(100));
+		let entity = test_map.spawn(Vector3::new(200.0, 0.0, 0.0), collider(), Some(20));
+
+		// Facing away
+		test_map
+			.world
+			.get_component_mut::<Transform>(entity)
+			.unwrap()
+			.rotation[2] = Angle::from_degrees(180.0);
+		assert_eq!(
+			test_map.with_tracer(|tracer| look_for_players(tracer, entity, &[player], false)),
+			None
+		);
+		assert_eq!(
+			test_map.with_tracer(|tracer| look_for_players(tracer, entity, &[player], true)),
+			Some(player)
+		);
+
+		// Facing towards
+		test_map
+			.world
+			.get_component_mut::<Transform>(entity)
+			.unwrap()
+			.rotation[2] = Angle::from_degrees(0.0);
+		assert_eq!(
+			test_map.with_tracer(|tracer| look_for_players(tracer, entity, &[player], false)),
+			Some(player)
+		);
+	}
+
+	// Floods a noise from a sector, returning which sectors it reached
+	fn flood_from(test_map: &mut TestMap, sector_index: usize, block_sound: &[usize]) -> Vec<bool> {
+		let source = test_map.spawn(Vector3::zeros(), collider(), None);
+		let handle = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.map
+			.clone();
+
+		// Mark the linedefs between each given sector and the one before it
+		{
+			let map = test_map.asset_storage.get_mut(&handle).unwrap();
+
+			for linedef in map.linedefs.iter_mut() {
+				if let [Some(front), Some(back)] = &linedef.sidedefs {
+					if block_sound.contains(&back.sector_index.max(front.sector_index)) {
+						linedef.flags.insert(LinedefFlags::BLOCKSOUND);
+					}
+				}
+			}
+		}
+
+		let map = test_map.asset_storage.get(&handle).unwrap();
+		let mut map_dynamic = test_map
+			.world
+			.get_component_mut::<MapDynamic>(test_map.map_entity)
+			.unwrap();
+		noise_flood(map, &mut map_dynamic, sector_index, source);
+
+		map_dynamic
+			.sectors
+			.iter()
+			.map(|sector| sector.sound_target == Some(source))
+			.collect()
+	}
+
+	#[test]
+	fn noise_stops_at_second_sound_block() {
+		let mut test_map = TestMap::corridor(&[TestSector::new(128.0, 0.0, 128.0); 4]);
+
+		assert_eq!(
+			flood_from(&mut test_map, 0, &[1, 3]),
+			[true, true, true, false]
+		);
+	}
+
+	#[test]
+	fn noise_spreads_both_ways() {
+		let mut test_map = TestMap::corridor(&[TestSector::new(128.0, 0.0, 128.0); 4]);
+
+		assert_eq!(flood_from(&mut test_map, 1, &[1, 3]), [true; 4]);
+	}
+
+	#[test]
+	fn noise_blocked_by_closed_door() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(128.0, 0.0, 128.0),
+			TestSector::new(16.0, 0.0, 0.0),
+			TestSector::new(128.0, 0.0, 128.0),
+		]);
+
+		assert_eq!(flood_from(&mut test_map, 0, &[]), [true, false, false]);
+	}
+
+	#[test]
+	fn charge_stops_at_target() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let start = Vector3::new(100.0, 0.0, 0.0);
+		let player = test_map.spawn(Vector3::new(400.0, 0.0, 0.0), collider(), Some(100));
+		let skull = test_map.spawn(start, collider(), Some(100));
+		let mut monster = Monster {
+			target: Some(player),
+			charge: charge_velocity(&test_map.world, start, 0.into(), player),
+			..monster()
+		};
+
+		let entity_types = MobjTypes {
+			names: FnvHashMap::default(),
+			doomednums: FnvHashMap::default(),
+			translations: FnvHashMap::default(),
+			sounds: FnvHashMap::default(),
+		};
+		let mut resources = Resources::default();
+		resources.insert(AssetStorage::default());
+		resources.insert(WadLoader::new());
+		let states = States::new(&mut resources);
+		let mut damage_event_channel = EventChannel::<DamageEvent>::new();
+		let mut damage_event_reader = damage_event_channel.register_reader();
+		let mut use_event_channel = EventChannel::<UseEvent>::new();
+		let mut rng = Pcg64Mcg::new(0xcafe_f00d_d15e_a5e5);
+		let mut sound_queue = Vec::new();
+
+		for _ in 0..20 {
+			let moved = {
+				let mut command_buffer = CommandBuffer::new(&test_map.world);
+				let map_dynamic = test_map
+					.world
+					.get_component::<MapDynamic>(test_map.map_entity)
+					.unwrap();
+
+				MonsterContext {
+					tracer: EntityTracer {
+						map: test_map.asset_storage.get(&map_dynamic.map).unwrap(),
+						map_dynamic: map_dynamic.as_ref(),
+						quadtree: &test_map.quadtree,
+						world: &test_map.world,
+					},
+					asset_storage: &test_map.asset_storage,
+					command_buffer: &mut command_buffer,
+					damage_event_channel: &mut damage_event_channel,
+					entity_types: &entity_types,
+					players: &[player],
+					rng: &mut rng,
+					sound_queue: &mut sound_queue,
+					states: &states,
+					use_event_channel: &mut use_event_channel,
+				}
+				.charge(skull, &mut monster, FRAME_TIME)
+			};
+
+			if let Some((position, _)) = moved {
+				move_entity(&mut test_map, skull, position);
+			}
+		}
+
+		// It slammed into the player and stopped there
+		let position = test_map
+			.world
+			.get_component::<Transform>(skull)
+			.unwrap()
+			.position;
+		assert!(monster.charge.is_none());
+		assert!((position[0] - 360.0).abs() < 1.0);
+
+		let damage_events: Vec<_> = damage_event_channel
+			.read(&mut damage_event_reader)
+			.collect();
+		assert_eq!(damage_events.len(), 1);
+		assert_eq!(damage_events[0].target, player);
+	}
+}
@@ -0,0 +1,65 @@
+use crate::doom::{data::FRAME_TIME, inventory::Powers};
+use legion::prelude::{IntoQuery, Resources, World, Write};
+
+pub fn palette_flash_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(|world, _resources| {
+		for mut palette_flash in <Write<PaletteFlash>>::query().iter_mut(world) {
+			palette_flash.damage_count = palette_flash.damage_count.saturating_sub(1);
+			palette_flash.bonus_count = palette_flash.bonus_count.saturating_sub(1);
+		}
+	})
+}
+
+// Palette indices within PLAYPAL
+const STARTREDPALS: usize = 1;
+const NUMREDPALS: usize = 8;
+const STARTBONUSPALS: usize = 9;
+const NUMBONUSPALS: usize = 4;
+const RADIATIONPAL: usize = 13;
+
+const BONUSADD: u32 = 6;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PaletteFlash {
+	pub damage_count: u32,
+	pub bonus_count: u32,
+}
+
+impl PaletteFlash {
+	pub fn add_damage(&mut self, damage: u32) {
+		self.damage_count = u32::min(self.damage_count + damage, 100);
+	}
+
+	pub fn add_bonus(&mut self) {
+		self.bonus_count += BONUSADD;
+	}
+
+	pub fn palette_index(&self, powers: Option<&Powers>) -> usize {
+		let mut damage_count = self.damage_count;
+
+		// Berserk tints the screen red, fading out over time
+		if let Some(strength) = powers.and_then(|powers| powers.strength) {
+			let tics = (strength.as_nanos() / FRAME_TIME.as_nanos()) as u32;
+			damage_count = u32::max(damage_count, 12u32.saturating_sub(tics >> 6));
+		}
+
+		if damage_count > 0 {
+			let index = (damage_count as usize + 7) >> 3;
+			STARTREDPALS + usize::min(index, NUMREDPALS - 1)
+		} else if self.bonus_count > 0 {
+			let index = (self.bonus_count as usize + 7) >> 3;
+			STARTBONUSPALS + usize::min(index, NUMBONUSPALS - 1)
+		} else {
+			// Flicker when the suit is about to run out
+			let tics = powers.map_or(0, |powers| {
+				(powers.iron_feet.as_nanos() / FRAME_TIME.as_nanos()) as u32
+			});
+
+			if tics > 4 * 32 || tics & 8 != 0 {
+				RADIATIONPAL
+			} else {
+				0
+			}
+		}
+	}
+}
@@ -1,18 +1,29 @@
 use crate::{
 	assets::AssetStorage,
 	doom::{
-		components::{Transform, Velocity},
-		data::{FRICTION, GRAVITY},
+		ceiling::{CeilingCross, CeilingStopCross},
+		components::{Health, Transform, Velocity},
+		damage::gib,
+		data::{States, FRICTION, GRAVITY},
+		door::DoorCross,
+		floor::{FloorCross, StairsCross},
+		game::ExitCross,
+		hitscan::is_shootable,
+		light::LightCross,
 		map::{Map, MapDynamic, NodeChild, Subsector},
+		plat::{PlatCross, PlatStopCross},
+		projectile::Projectile,
+		teleport::TeleportCross,
 	},
-	geometry::{Interval, Plane3, AABB2, AABB3},
+	geometry::{Interval, Line2, Plane3, Side, AABB2, AABB3},
 	quadtree::Quadtree,
 };
 use arrayvec::ArrayVec;
 use bitflags::bitflags;
 use lazy_static::lazy_static;
 use legion::prelude::{component, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write};
-use nalgebra::Vector3;
+use nalgebra::{Vector2, Vector3};
+use shrev::EventChannel;
 use smallvec::SmallVec;
 use std::time::Duration;
 
@@ -21,15 +32,24 @@ pub struct PhysicsSystem;
 
 pub fn physics_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 	Box::new(|world, resources| {
-		let (asset_storage, delta, mut quadtree) =
-			<(Read<AssetStorage>, Read<Duration>, Write<Quadtree>)>::fetch_mut(resources);
+		let (asset_storage, delta, mut cross_event_channel, mut quadtree, mut touch_event_channel) =
+			<(
+				Read<AssetStorage>,
+				Read<Duration>,
+				Write<EventChannel<CrossEvent>>,
+				Write<Quadtree>,
+				Write<EventChannel<TouchEvent>>,
+			)>::fetch_mut(resources);
 
 		let map_dynamic = <Read<MapDynamic>>::query().iter(world).next().unwrap();
 		let map = asset_storage.get(&map_dynamic.map).unwrap();
 
-		// Clone the mask so that transform_component is free to be borrowed during the loop
+		// Clone the mask so that transform_component is free to be borrowed during the loop.
+		// Projectiles do their own movement, without friction or gravity.
 		let entities: Vec<Entity> = <Read<Transform>>::query()
-			.filter(component::<BoxCollider>() & component::<Velocity>())
+			.filter(
+				component::<BoxCollider>() & component::<Velocity>() & !component::<Projectile>(),
+			)
 			.iter_entities(world)
 			.map(|(e, _)| e)
 			.collect();
@@ -46,6 +66,7 @@ pub fn physics_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 
 			quadtree.remove(entity);
 			let mut touched: SmallVec<[Entity; 8]> = SmallVec::new();
+			let mut crossed: SmallVec<[(Entity, Side); 4]> = SmallVec::new();
 
 			let tracer = EntityTracer {
 				map,
@@ -63,7 +84,9 @@ pub fn physics_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 
 			if let Some(collision) = trace.collision {
 				touched.push(collision.entity);
-				new_velocity[2] = 0.0;
+
+				// Stop falling, but let things be thrown upwards
+				new_velocity[2] = new_velocity[2].max(0.0);
 			}
 
 			// Apply the move
@@ -72,6 +95,7 @@ pub fn physics_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 				&mut new_position,
 				&mut new_velocity,
 				&mut touched,
+				&mut crossed,
 				&entity_bbox,
 				SolidMask::NON_MONSTER, // TODO solid mask
 				*delta,
@@ -105,6 +129,18 @@ pub fn physics_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
 					.velocity = new_velocity;
 			}
 			quadtree.insert(entity, &AABB2::from(&entity_bbox.offset(new_position)));
+
+			cross_event_channel.iter_write(crossed.into_iter().map(|(linedef_entity, side)| {
+				CrossEvent {
+					entity,
+					linedef_entity,
+					side,
+				}
+			}));
+			touch_event_channel.iter_write(touched.into_iter().map(|touched_entity| TouchEvent {
+				entity,
+				touched_entity,
+			}));
 		}
 	})
 }
@@ -114,6 +150,7 @@ fn step_slide_move(
 	position: &mut Vector3<f32>,
 	velocity: &mut Vector3<f32>,
 	touched: &mut SmallVec<[Entity; 8]>,
+	crossed: &mut SmallVec<[(Entity, Side); 4]>,
 	entity_bbox: &AABB3,
 	solid_mask: SolidMask,
 	mut time_left: Duration,
@@ -125,6 +162,7 @@ fn step_slide_move(
 		let move_step = *velocity * time_left.as_secs_f32();
 		let trace = tracer.trace(&entity_bbox.offset(*position), move_step, solid_mask);
 
+		find_crossed_linedefs(tracer, *position, trace.move_step, crossed);
 		*position += trace.move_step;
 		time_left = time_left
 			.checked_sub(time_left.mul_f32(trace.fraction))
@@ -174,6 +212,98 @@ fn step_slide_move(
 	}
 }
 
+// Finds linedefs with a special that the centre of an entity crosses during a move,
+// along with the side it crossed from
+pub(crate) fn find_crossed_linedefs(
+	tracer: &EntityTracer,
+	position: Vector3<f32>,
+	move_step: Vector3<f32>,
+	crossed: &mut SmallVec<[(Entity, Side); 4]>,
+) {
+	let start = Vector2::new(position[0], position[1]);
+	let move_step = Vector2::new(move_step[0], move_step[1]);
+
+	if move_step == Vector2::zeros() {
+		return;
+	}
+
+	let move_line = Line2::new(start, move_step);
+	let end = start + move_step;
+	let move_bbox = AABB2::from_extents(
+		f32::max(start[1], end[1]),
+		f32::min(start[1], end[1]),
+		f32::min(start[0], end[0]),
+		f32::max(start[0], end[0]),
+	);
+
+	tracer
+		.map
+		.traverse_nodes(NodeChild::Node(0), &move_bbox, &mut |node: NodeChild| {
+			let linedefs = match node {
+				NodeChild::Subsector(index) => &tracer.map.subsectors[index].linedefs,
+				NodeChild::Node(index) => &tracer.map.nodes[index].linedefs,
+			};
+
+			for linedef_index in linedefs.iter().copied() {
+				let linedef = &tracer.map.linedefs[linedef_index];
+
+				if linedef.special_type == 0 || !move_bbox.overlaps(&linedef.bbox) {
+					continue;
+				}
+
+				let start_dist = (start - linedef.line.point).dot(&linedef.normal);
+				let end_dist = (end - linedef.line.point).dot(&linedef.normal);
+
+				// Must end up on the other side
+				if (start_dist > 0.0) == (end_dist > 0.0) {
+					continue;
+				}
+
+				if let Some((linedef_p, move_p)) = linedef.line.intersect(&move_line) {
+					if linedef_p >= 0.0 && linedef_p <= 1.0 && move_p >= 0.0 && move_p <= 1.0 {
+						let linedef_entity = tracer.map_dynamic.linedefs[linedef_index].entity;
+						let side = if start_dist > 0.0 {
+							Side::Right
+						} else {
+							Side::Left
+						};
+
+						if !crossed.iter().any(|(e, _)| *e == linedef_entity) {
+							crossed.push((linedef_entity, side));
+						}
+					}
+				}
+			}
+		});
+}
+
+#[derive(Clone, Debug)]
+pub struct CrossEvent {
+	pub entity: Entity,
+	pub linedef_entity: Entity,
+	pub side: Side,
+}
+
+#[derive(Clone, Debug)]
+pub struct TouchEvent {
+	pub entity: Entity,
+	pub touched_entity: Entity,
+}
+
+#[derive(Clone, Debug)]
+pub enum CrossAction {
+	CeilingCross(CeilingCross),
+	CeilingStopCross(CeilingStopCross),
+	DoorCross(DoorCross),
+	ExitCross(ExitCross),
+	FloorCross(FloorCross),
+	LightCross(LightCross),
+	PlatCross(PlatCross),
+	PlatStopCross(PlatStopCross),
+	StairsCross(StairsCross),
+	TeleportCross(TeleportCross),
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct BoxCollider {
 	pub height: f32,
@@ -503,6 +633,164 @@ impl<'a> SectorTracer<'a> {
 	}
 }
 
+// Moves the floor of a sector, carrying along any entities standing on it.
+// Shootable entities that no longer fit under the ceiling are returned. Unless crushing, nothing
+// is moved if there are any.
+pub fn move_floor(
+	world: &World,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	states: &States,
+	sector_index: usize,
+	move_step: f32,
+	crush: bool,
+) -> SmallVec<[Entity; 4]> {
+	let sector_dynamic = &mut map_dynamic.sectors[sector_index];
+	let new_floor = sector_dynamic.interval.min + move_step;
+	let mut blocked = SmallVec::new();
+	let mut moved: SmallVec<[(Entity, f32); 8]> = SmallVec::new();
+
+	for entity in sector_entities(world, map, sector_index) {
+		let position = world.get_component::<Transform>(entity).unwrap().position;
+		let height = world.get_component::<BoxCollider>(entity).unwrap().height;
+		let on_floor = position[2] <= sector_dynamic.interval.min + DISTANCE_EPSILON * 2.0;
+
+		if move_step > 0.0 {
+			if on_floor || position[2] < new_floor {
+				let new_z = f32::max(position[2] + move_step, new_floor);
+
+				if new_z + height > sector_dynamic.interval.max {
+					squash(world, states, entity, &mut blocked);
+				}
+
+				moved.push((entity, new_z));
+			}
+		} else if on_floor {
+			// Let entities that move by themselves fall, in case something else holds them up
+			if let Some(mut velocity) =
+				unsafe { world.get_component_mut_unchecked::<Velocity>(entity) }
+			{
+				if velocity.velocity == Vector3::zeros() {
+					velocity.velocity[2] = -DISTANCE_EPSILON;
+				}
+			} else {
+				moved.push((entity, position[2] + move_step));
+			}
+		}
+	}
+
+	if !blocked.is_empty() && !crush {
+		return blocked;
+	}
+
+	sector_dynamic.interval.min = new_floor;
+
+	for (entity, new_z) in moved {
+		unsafe {
+			world
+				.get_component_mut_unchecked::<Transform>(entity)
+				.unwrap()
+				.position[2] = new_z;
+		}
+	}
+
+	blocked
+}
+
+// Moves the ceiling of a sector, pushing down any entities in the way.
+// Shootable entities that can't be pushed any further are returned. Unless crushing, nothing is
+// moved if there are any.
+pub fn move_ceiling(
+	world: &World,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	states: &States,
+	sector_index: usize,
+	move_step: f32,
+	crush: bool,
+) -> SmallVec<[Entity; 4]> {
+	let sector_dynamic = &mut map_dynamic.sectors[sector_index];
+	let new_ceiling = sector_dynamic.interval.max + move_step;
+	let mut blocked = SmallVec::new();
+	let mut moved: SmallVec<[(Entity, f32); 8]> = SmallVec::new();
+
+	if move_step < 0.0 {
+		for entity in sector_entities(world, map, sector_index) {
+			let position = world.get_component::<Transform>(entity).unwrap().position;
+			let height = world.get_component::<BoxCollider>(entity).unwrap().height;
+
+			if position[2] + height > new_ceiling {
+				let new_z = new_ceiling - height;
+
+				if new_z < sector_dynamic.interval.min {
+					squash(world, states, entity, &mut blocked);
+				} else {
+					moved.push((entity, new_z));
+				}
+			}
+		}
+	}
+
+	if !blocked.is_empty() && !crush {
+		return blocked;
+	}
+
+	sector_dynamic.interval.max = new_ceiling;
+
+	for (entity, new_z) in moved {
+		unsafe {
+			world
+				.get_component_mut_unchecked::<Transform>(entity)
+				.unwrap()
+				.position[2] = new_z;
+		}
+	}
+
+	blocked
+}
+
+// Deals with an entity that doesn't fit between the floor and ceiling, like vanilla's
+// PIT_ChangeSector. Corpses are crushed into gibs, and other things that can't be shot are left
+// sticking out. Only shootable entities get in the way.
+fn squash(world: &World, states: &States, entity: Entity, blocked: &mut SmallVec<[Entity; 4]>) {
+	if is_shootable(world, entity) {
+		blocked.push(entity);
+	} else if world.get_component::<Health>(entity).is_some() {
+		gib(world, states, entity);
+	}
+}
+
+// Finds all entities whose bounding box overlaps the sector horizontally
+fn sector_entities(world: &World, map: &Map, sector_index: usize) -> SmallVec<[Entity; 8]> {
+	let sector = &map.sectors[sector_index];
+
+	<(Read<Transform>, Read<BoxCollider>)>::query()
+		.iter_entities(world)
+		.filter(|(_, (transform, box_collider))| {
+			let entity_bbox = AABB3::from_radius_height(box_collider.radius, box_collider.height)
+				.offset(transform.position);
+			let entity_bbox2 = AABB2::from(&entity_bbox);
+
+			sector.subsectors.iter().any(|index| {
+				let subsector = &map.subsectors[*index];
+
+				entity_bbox2.overlaps(&subsector.bbox)
+					&& subsector.planes.iter().all(|plane| {
+						let closest_point = entity_bbox.vector().zip_map(&plane.normal, |b, n| {
+							if n < 0.0 {
+								b.max
+							} else {
+								b.min
+							}
+						});
+						closest_point.dot(&plane.normal) - plane.distance < 0.0
+					})
+			})
+		})
+		.map(|(entity, _)| entity)
+		.collect()
+}
+
 fn trace_planes<'a>(
 	entity_bbox: &AABB3,
 	move_step: Vector3<f32>,
@@ -558,3 +846,80 @@ lazy_static! {
 		Vector3::new(0.0, -1.0, 0.0),  // down
 	];
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::doom::{
+		map::test_map::{TestMap, TestSector},
+		wad::WadLoader,
+	};
+
+	fn collider(height: f32) -> BoxCollider {
+		BoxCollider {
+			height,
+			radius: 20.0,
+			solid_mask: SolidMask::all(),
+		}
+	}
+
+	#[test]
+	fn only_shootable_entities_block_floors() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let pickup = test_map.spawn(
+			Vector3::new(64.0, 0.0, 0.0),
+			BoxCollider {
+				height: 16.0,
+				radius: 20.0,
+				solid_mask: SolidMask::empty(),
+			},
+			None,
+		);
+		let corpse = test_map.spawn(Vector3::new(160.0, 0.0, 0.0), collider(14.0), Some(0));
+		let live = test_map.spawn(Vector3::new(384.0, 0.0, 0.0), collider(56.0), Some(100));
+
+		let TestMap {
+			asset_storage,
+			map_entity,
+			world,
+			..
+		} = test_map;
+		let mut resources = Resources::default();
+		resources.insert(asset_storage);
+		resources.insert(WadLoader::new());
+		let states = States::new(&mut resources);
+		let asset_storage = resources.get::<AssetStorage>().unwrap();
+
+		let mut map_dynamic =
+			unsafe { world.get_component_mut_unchecked::<MapDynamic>(map_entity) }.unwrap();
+		let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+		// Something alive stops the floor from rising
+		let blocked = move_floor(&world, map, &mut map_dynamic, &states, 1, 120.0, false);
+		assert_eq!(blocked.as_slice(), &[live]);
+		assert_eq!(map_dynamic.sectors[1].interval.min, 0.0);
+
+		// A pickup is carried up into the ceiling, and a corpse is crushed into gibs
+		let blocked = move_floor(&world, map, &mut map_dynamic, &states, 0, 120.0, false);
+		assert!(blocked.is_empty());
+		assert_eq!(map_dynamic.sectors[0].interval.min, 120.0);
+		assert_eq!(
+			world.get_component::<Transform>(pickup).unwrap().position[2],
+			120.0
+		);
+		assert_eq!(
+			world.get_component::<BoxCollider>(corpse).unwrap().height,
+			0.0
+		);
+
+		// The same goes for ceilings
+		let blocked = move_ceiling(&world, map, &mut map_dynamic, &states, 1, -100.0, false);
+		assert_eq!(blocked.as_slice(), &[live]);
+		let blocked = move_ceiling(&world, map, &mut map_dynamic, &states, 0, -4.0, false);
+		assert!(blocked.is_empty());
+		assert_eq!(map_dynamic.sectors[0].interval.max, 124.0);
+	}
+}
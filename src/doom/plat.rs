@@ -0,0 +1,661 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		ceiling::CeilingActive,
+		client::{Client, ShootAction, ShootEvent, UseAction, UseEvent},
+		data::{States, FRAME_TIME},
+		door::{activate_switch, DoorActive, SwitchActive},
+		floor::FloorActive,
+		map::{LinedefRef, Map, MapDynamic, SectorRef, SectorSlot},
+		physics::{move_floor, CrossAction, CrossEvent},
+		sector::{SectorDamage, SectorSecret},
+	},
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn plat_use_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut use_event_reader = resources
+		.get_mut::<EventChannel<UseEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, use_event_channel, mut rng, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<EventChannel<UseEvent>>,
+				Write<Pcg64Mcg>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for use_event in use_event_channel.read(&mut use_event_reader) {
+			if let Some(UseAction::PlatSwitchUse(plat_use)) = world
+				.get_component::<UseAction>(use_event.linedef_entity)
+				.as_deref()
+			{
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(use_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(use_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				let used = activate_plats(
+					world,
+					&plat_use.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut rng,
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				if used {
+					activate_switch(
+						use_event.linedef_entity,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&plat_use.switch_sound,
+						if plat_use.retrigger {
+							Some(plat_use.switch_time)
+						} else {
+							None
+						},
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !plat_use.retrigger {
+						command_buffer.remove_component::<UseAction>(use_event.linedef_entity);
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn plat_cross_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, cross_event_channel, mut rng, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Read<EventChannel<CrossEvent>>,
+				Write<Pcg64Mcg>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			match world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				Some(CrossAction::PlatCross(plat_cross)) => {
+					// Only some linedefs can be triggered by monsters
+					if !plat_cross.monsters && client.entity != Some(cross_event.entity) {
+						continue;
+					}
+
+					let linedef_ref = world
+						.get_component::<LinedefRef>(cross_event.linedef_entity)
+						.unwrap();
+					let mut map_dynamic = unsafe {
+						world
+							.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+							.unwrap()
+					};
+					let map_dynamic = map_dynamic.as_mut();
+					let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+					activate_plats(
+						world,
+						&plat_cross.params,
+						linedef_ref.index,
+						map,
+						map_dynamic,
+						&mut rng,
+						&mut sound_queue,
+						&mut command_buffer,
+					);
+
+					if !plat_cross.retrigger {
+						command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+					}
+				}
+				Some(CrossAction::PlatStopCross(plat_stop_cross)) => {
+					if client.entity != Some(cross_event.entity) {
+						continue;
+					}
+
+					let linedef_ref = world
+						.get_component::<LinedefRef>(cross_event.linedef_entity)
+						.unwrap();
+					let map_dynamic = world
+						.get_component::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap();
+					let map = asset_storage.get(&map_dynamic.map).unwrap();
+					let linedef = &map.linedefs[linedef_ref.index];
+
+					// Put all the active platforms with the same tag in stasis
+					for (i, _) in map
+						.sectors
+						.iter()
+						.enumerate()
+						.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+					{
+						let sector_entity = map_dynamic.sectors[i].entity;
+
+						if let Some(mut plat_active) = unsafe {
+							world.get_component_mut_unchecked::<PlatActive>(sector_entity)
+						} {
+							plat_active.in_stasis = true;
+						}
+					}
+
+					if !plat_stop_cross.retrigger {
+						command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+					}
+				}
+				_ => {}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+pub fn plat_shoot_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut shoot_event_reader = resources
+		.get_mut::<EventChannel<ShootEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, shoot_event_channel, mut rng, mut sound_queue) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Read<EventChannel<ShootEvent>>,
+				Write<Pcg64Mcg>,
+				Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for shoot_event in shoot_event_channel.read(&mut shoot_event_reader) {
+			if let Some(ShootAction::PlatShoot(plat_shoot)) = world
+				.get_component::<ShootAction>(shoot_event.linedef_entity)
+				.as_deref()
+			{
+				// Only some linedefs can be triggered by monsters
+				if !plat_shoot.monsters && client.entity != Some(shoot_event.entity) {
+					continue;
+				}
+
+				// Skip if switch is already in active state
+				if world.has_component::<SwitchActive>(shoot_event.linedef_entity) {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(shoot_event.linedef_entity)
+					.unwrap();
+				let mut map_dynamic = unsafe {
+					world
+						.get_component_mut_unchecked::<MapDynamic>(linedef_ref.map_entity)
+						.unwrap()
+				};
+				let map_dynamic = map_dynamic.as_mut();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+				activate_plats(
+					world,
+					&plat_shoot.params,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&mut rng,
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				// Shot linedefs always flip the switch, even if nothing was activated
+				activate_switch(
+					shoot_event.linedef_entity,
+					linedef_ref.index,
+					map,
+					map_dynamic,
+					&plat_shoot.switch_sound,
+					if plat_shoot.retrigger {
+						Some(plat_shoot.switch_time)
+					} else {
+						None
+					},
+					&mut sound_queue,
+					&mut command_buffer,
+				);
+
+				if !plat_shoot.retrigger {
+					command_buffer.remove_component::<ShootAction>(shoot_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Starts a platform in every sector with the same tag as the linedef.
+// Returns whether any platform was started or resumed.
+fn activate_plats(
+	world: &World,
+	params: &PlatParams,
+	linedef_index: usize,
+	map: &Map,
+	map_dynamic: &mut MapDynamic,
+	rng: &mut Pcg64Mcg,
+	sound_queue: &mut Vec<(AssetHandle<Sound>, Entity)>,
+	command_buffer: &mut CommandBuffer,
+) -> bool {
+	let linedef = &map.linedefs[linedef_index];
+	let mut used = false;
+
+	for (i, sector) in map
+		.sectors
+		.iter()
+		.enumerate()
+		.filter(|(_, s)| s.sector_tag == linedef.sector_tag)
+	{
+		let sector_entity = map_dynamic.sectors[i].entity;
+
+		// Perpetual platforms that were stopped start moving again
+		if let Some(mut plat_active) =
+			unsafe { world.get_component_mut_unchecked::<PlatActive>(sector_entity) }
+		{
+			if plat_active.in_stasis && params.plat_type == PlatType::Perpetual {
+				plat_active.in_stasis = false;
+				used = true;
+			}
+
+			continue;
+		}
+
+		if world.has_component::<CeilingActive>(sector_entity)
+			|| world.has_component::<DoorActive>(sector_entity)
+			|| world.has_component::<FloorActive>(sector_entity)
+		{
+			continue;
+		}
+
+		let floor_height = map_dynamic.sectors[i].interval.min;
+		let neighbour_floors = sector
+			.neighbours
+			.iter()
+			.map(|index| map_dynamic.sectors[*index].interval.min);
+		let lowest_floor = neighbour_floors
+			.clone()
+			.fold(floor_height, |x, y| f32::min(x, y));
+		let highest_floor = neighbour_floors
+			.clone()
+			.fold(floor_height, |x, y| f32::max(x, y));
+		let next_highest_floor = neighbour_floors
+			.filter(|h| *h > floor_height)
+			.min_by(|x, y| x.partial_cmp(y).unwrap())
+			.unwrap_or(floor_height);
+
+		let (state, low_height, high_height) = match params.plat_type {
+			PlatType::DownWaitUpStay => (PlatState::Down, lowest_floor, floor_height),
+			PlatType::Perpetual => (
+				if rng.gen::<bool>() {
+					PlatState::Up
+				} else {
+					PlatState::Down
+				},
+				lowest_floor,
+				highest_floor,
+			),
+			PlatType::RaiseAndChange(amount) => {
+				(PlatState::Up, floor_height, floor_height + amount)
+			}
+			PlatType::RaiseToNearestAndChange => (PlatState::Up, floor_height, next_highest_floor),
+		};
+
+		// Take on the floor texture of the sector in front of the linedef
+		match params.plat_type {
+			PlatType::RaiseAndChange(_) | PlatType::RaiseToNearestAndChange => {
+				let sidedef = linedef.sidedefs[0].as_ref().unwrap();
+				let floor_texture = map_dynamic.sectors[sidedef.sector_index].textures
+					[SectorSlot::Floor as usize]
+					.clone();
+				map_dynamic.sectors[i].textures[SectorSlot::Floor as usize] = floor_texture;
+			}
+			_ => {}
+		}
+
+		// The new floor no longer hurts
+		if params.plat_type == PlatType::RaiseToNearestAndChange {
+			command_buffer.remove_component::<SectorDamage>(sector_entity);
+			command_buffer.remove_component::<SectorSecret>(sector_entity);
+		}
+
+		sound_queue.push((params.start_sound.clone(), sector_entity));
+		command_buffer.add_component(
+			sector_entity,
+			PlatActive {
+				plat_type: params.plat_type,
+				speed: params.speed,
+				wait_time: params.wait_time,
+				time_left: Duration::default(),
+				state,
+				in_stasis: false,
+				low_height,
+				high_height,
+
+				start_sound: params.start_sound.clone(),
+				move_sound: params.move_sound.clone(),
+				move_sound_time: Duration::default(),
+				stop_sound: params.stop_sound.clone(),
+			},
+		);
+		used = true;
+	}
+
+	used
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum PlatType {
+	DownWaitUpStay,
+	Perpetual,
+	RaiseAndChange(f32),
+	RaiseToNearestAndChange,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatParams {
+	pub plat_type: PlatType,
+	pub speed: f32,
+	pub wait_time: Duration,
+	pub start_sound: AssetHandle<Sound>,
+	pub move_sound: Option<AssetHandle<Sound>>,
+	pub stop_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatSwitchUse {
+	pub params: PlatParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatCross {
+	pub params: PlatParams,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatStopCross {
+	pub retrigger: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatShoot {
+	pub params: PlatParams,
+	pub switch_sound: AssetHandle<Sound>,
+	pub switch_time: Duration,
+	pub retrigger: bool,
+	pub monsters: bool,
+}
+
+pub fn plat_active_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(move |world, resources| {
+		let (asset_storage, delta, mut sound_queue, states) = <(
+			Read<AssetStorage>,
+			Read<Duration>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Read<States>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for (entity, (sector_ref, mut plat_active)) in unsafe {
+			<(Read<SectorRef>, Write<PlatActive>)>::query().iter_entities_unchecked(world)
+		} {
+			if plat_active.in_stasis {
+				continue;
+			}
+
+			let mut map_dynamic = unsafe {
+				world
+					.get_component_mut_unchecked::<MapDynamic>(sector_ref.map_entity)
+					.unwrap()
+			};
+			let map_dynamic = map_dynamic.as_mut();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+			let floor_height = map_dynamic.sectors[sector_ref.index].interval.min;
+
+			match plat_active.state {
+				PlatState::Up => {
+					let move_step = f32::min(
+						plat_active.speed * delta.as_secs_f32(),
+						plat_active.high_height - floor_height,
+					);
+
+					// Play the moving sound every 8 tics
+					if let Some(move_sound) = &plat_active.move_sound {
+						if let Some(new_time) = plat_active.move_sound_time.checked_sub(*delta) {
+							plat_active.move_sound_time = new_time;
+						} else {
+							plat_active.move_sound_time = 8 * FRAME_TIME;
+							sound_queue.push((move_sound.clone(), entity));
+						}
+					}
+
+					if !move_floor(
+						world,
+						map,
+						map_dynamic,
+						&states,
+						sector_ref.index,
+						move_step,
+						false,
+					)
+					.is_empty()
+					{
+						// Hit something on the way up, go back down
+						plat_active.state = PlatState::Down;
+						plat_active.time_left = plat_active.wait_time;
+						sound_queue.push((plat_active.start_sound.clone(), entity));
+					} else if floor_height + move_step >= plat_active.high_height {
+						sound_queue.push((plat_active.stop_sound.clone(), entity));
+
+						if plat_active.plat_type == PlatType::Perpetual {
+							plat_active.state = PlatState::Waiting;
+							plat_active.time_left = plat_active.wait_time;
+						} else {
+							command_buffer.remove_component::<PlatActive>(entity);
+						}
+					}
+				}
+				PlatState::Down => {
+					let move_step = f32::max(
+						-plat_active.speed * delta.as_secs_f32(),
+						plat_active.low_height - floor_height,
+					);
+					move_floor(
+						world,
+						map,
+						map_dynamic,
+						&states,
+						sector_ref.index,
+						move_step,
+						false,
+					);
+
+					if floor_height + move_step <= plat_active.low_height {
+						plat_active.state = PlatState::Waiting;
+						plat_active.time_left = plat_active.wait_time;
+						sound_queue.push((plat_active.stop_sound.clone(), entity));
+					}
+				}
+				PlatState::Waiting => {
+					if let Some(new_time) = plat_active.time_left.checked_sub(*delta) {
+						plat_active.time_left = new_time;
+					} else {
+						if floor_height <= plat_active.low_height {
+							plat_active.state = PlatState::Up;
+						} else {
+							plat_active.state = PlatState::Down;
+						}
+
+						sound_queue.push((plat_active.start_sound.clone(), entity));
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+#[derive(Clone, Debug)]
+pub struct PlatActive {
+	pub plat_type: PlatType,
+	pub speed: f32,
+	pub wait_time: Duration,
+	pub time_left: Duration,
+	pub state: PlatState,
+	pub in_stasis: bool,
+	pub low_height: f32,
+	pub high_height: f32,
+
+	pub start_sound: AssetHandle<Sound>,
+	pub move_sound: Option<AssetHandle<Sound>>,
+	pub move_sound_time: Duration,
+	pub stop_sound: AssetHandle<Sound>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlatState {
+	Up,
+	Down,
+	Waiting,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::doom::{
+		data::FRAME_RATE,
+		map::test_map::{TestMap, TestSector},
+		physics::{BoxCollider, SolidMask},
+		wad::WadLoader,
+	};
+	use nalgebra::Vector3;
+
+	#[test]
+	fn pickups_dont_block_lifts() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 120.0, 248.0),
+		]);
+		test_map.spawn(
+			Vector3::new(64.0, 0.0, 0.0),
+			BoxCollider {
+				height: 16.0,
+				radius: 20.0,
+				solid_mask: SolidMask::empty(),
+			},
+			None,
+		);
+
+		let sector_entity = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.sectors[0]
+			.entity;
+		let sound = test_map
+			.asset_storage
+			.load::<Sound>("DSPSTOP", &mut WadLoader::new());
+		let mut command_buffer = CommandBuffer::new(&test_map.world);
+		command_buffer.add_component(
+			sector_entity,
+			PlatActive {
+				plat_type: PlatType::Perpetual,
+				speed: 4.0 * FRAME_RATE,
+				wait_time: 105 * FRAME_TIME,
+				time_left: Duration::default(),
+				state: PlatState::Up,
+				in_stasis: false,
+				low_height: 0.0,
+				high_height: 120.0,
+
+				start_sound: sound.clone(),
+				move_sound: None,
+				move_sound_time: Duration::default(),
+				stop_sound: sound,
+			},
+		);
+		command_buffer.write(&mut test_map.world);
+
+		let TestMap {
+			asset_storage,
+			map_entity,
+			mut world,
+			..
+		} = test_map;
+		let mut resources = Resources::default();
+		resources.insert(asset_storage);
+		resources.insert(WadLoader::new());
+		let states = States::new(&mut resources);
+		resources.insert(states);
+		resources.insert(FRAME_TIME);
+		resources.insert(Vec::<(AssetHandle<Sound>, Entity)>::new());
+
+		let mut system = plat_active_system();
+
+		for _ in 0..40 {
+			system(&mut world, &mut resources);
+		}
+
+		// The pickup reaches the ceiling, but the lift keeps going up
+		let plat_active = world.get_component::<PlatActive>(sector_entity).unwrap();
+		assert_eq!(plat_active.state, PlatState::Waiting);
+		let map_dynamic = world.get_component::<MapDynamic>(map_entity).unwrap();
+		assert!((map_dynamic.sectors[0].interval.min - 120.0).abs() < 0.01);
+	}
+}
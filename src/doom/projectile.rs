@@ -0,0 +1,589 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		components::{Health, Transform, Velocity},
+		damage::{DamageEvent, DamageType},
+		data::{MobjTypes, FRAME_RATE, FRAME_TIME},
+		game::GameSession,
+		hitscan::{self, AUTOAIM_RANGE},
+		map::{LinedefRef, Map, MapDynamic, SectorRef, SectorSlot},
+		monster::Monster,
+		physics::{BoxCollider, EntityTracer, SolidMask, TraceCollision},
+		state::{StateAction, StateActionEvent, StateMachine},
+	},
+	geometry::{Angle, AABB2, AABB3},
+	quadtree::Quadtree,
+};
+use legion::prelude::{
+	component, CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use nalgebra::{Vector2, Vector3};
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use shrev::EventChannel;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct Projectile {
+	pub speed: f32,
+	// Damage on impact is this multiplied by a random number from 1 to 8
+	pub damage: i32,
+	// The entity that fired it, which it passes through and which gets the blame for damage
+	pub owner: Option<Entity>,
+	pub spawn_sound: Option<AssetHandle<Sound>>,
+	pub death_sound: Option<AssetHandle<Sound>>,
+}
+
+// Explosions damage everything within this distance of their centre
+const EXPLOSION_DAMAGE: i32 = 128;
+
+// How far a homing missile can turn each time it steers
+const TRACE_ANGLE: i32 = 0xc00_0000;
+
+pub fn projectile_system(resources: &mut Resources) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut state_action_event_reader = resources
+		.get_mut::<EventChannel<StateActionEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (
+			asset_storage,
+			mut damage_event_channel,
+			delta,
+			entity_types,
+			game_session,
+			mut quadtree,
+			mut rng,
+			mut sound_queue,
+			state_action_event_channel,
+		) = <(
+			Read<AssetStorage>,
+			Write<EventChannel<DamageEvent>>,
+			Read<Duration>,
+			Read<MobjTypes>,
+			Read<GameSession>,
+			Write<Quadtree>,
+			Write<Pcg64Mcg>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Read<EventChannel<StateActionEvent>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		{
+			let map_dynamic = <Read<MapDynamic>>::query().iter(world).next().unwrap();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+
+			for state_action_event in
+				state_action_event_channel.read(&mut state_action_event_reader)
+			{
+				let entity = state_action_event.entity;
+				let tracer = EntityTracer {
+					map,
+					map_dynamic: map_dynamic.as_ref(),
+					quadtree: &quadtree,
+					world,
+				};
+
+				let name = match state_action_event.action {
+					StateAction::FireMissile => "ROCKET",
+					StateAction::FirePlasma => "PLASMA",
+					StateAction::FireBfg => "BFG",
+					StateAction::BfgSpray => {
+						bfg_spray(
+							&tracer,
+							&asset_storage,
+							&entity_types,
+							&mut command_buffer,
+							&mut damage_event_channel,
+							&mut rng,
+							entity,
+						);
+						continue;
+					}
+					StateAction::Explode => {
+						let middle = match hitscan::entity_bbox(world, entity) {
+							Some(bbox) => bbox.middle(),
+							None => continue,
+						};
+
+						// Barrels blame whoever blew them up
+						let source = world
+							.get_component::<Projectile>(entity)
+							.and_then(|projectile| projectile.owner)
+							.or_else(|| {
+								world
+									.get_component::<Health>(entity)
+									.and_then(|health| health.killer)
+							});
+						radius_attack(
+							&tracer,
+							&mut damage_event_channel,
+							entity,
+							middle,
+							source,
+							EXPLOSION_DAMAGE,
+						);
+						continue;
+					}
+					StateAction::Tracer => {
+						// Only steer every fourth frame
+						let frame = game_session.level_time.as_nanos() / FRAME_TIME.as_nanos();
+
+						if frame % 4 == 0 {
+							seek_target(
+								world,
+								&asset_storage,
+								&entity_types,
+								&mut command_buffer,
+								&mut rng,
+								entity,
+							);
+						}
+
+						continue;
+					}
+					_ => continue,
+				};
+
+				// Player projectiles are autoaimed like bullets
+				let (start, rotation) = match hitscan::shoot_origin(world, entity) {
+					Some(x) => x,
+					None => continue,
+				};
+				let slope = hitscan::autoaim_slope(&tracer, entity, start, rotation);
+				let position = world.get_component::<Transform>(entity).unwrap().position;
+
+				if let Some(sound) = spawn_projectile(
+					&asset_storage,
+					&entity_types,
+					&mut command_buffer,
+					name,
+					entity,
+					position,
+					rotation[2],
+					slope,
+				) {
+					sound_queue.push(sound);
+				}
+			}
+
+			// Move projectiles in a straight line until they hit something
+			let entities: Vec<Entity> = <Read<Projectile>>::query()
+				.filter(
+					component::<Transform>() & component::<Velocity>() & component::<BoxCollider>(),
+				)
+				.iter_entities(world)
+				.map(|(e, _)| e)
+				.collect();
+
+			for entity in entities {
+				let velocity = world.get_component::<Velocity>(entity).unwrap().velocity;
+
+				// Exploded already
+				if velocity == Vector3::zeros() {
+					continue;
+				}
+
+				let mut position = world.get_component::<Transform>(entity).unwrap().position;
+				let box_collider = *world.get_component::<BoxCollider>(entity).unwrap();
+				let projectile = world.get_component::<Projectile>(entity).unwrap().clone();
+				let entity_bbox =
+					AABB3::from_radius_height(box_collider.radius, box_collider.height);
+
+				// Projectiles start inside their owner, so take it out of the way while moving
+				quadtree.remove(entity);
+				let owner_bbox = projectile.owner.and_then(|owner| {
+					let bbox = hitscan::entity_bbox(world, owner)?;
+					quadtree.remove(owner);
+					Some((owner, bbox))
+				});
+
+				let trace = EntityTracer {
+					map,
+					map_dynamic: map_dynamic.as_ref(),
+					quadtree: &quadtree,
+					world,
+				}
+				.trace(
+					&entity_bbox.offset(position),
+					velocity * delta.as_secs_f32(),
+					SolidMask::NON_MONSTER,
+				);
+				position += trace.move_step;
+
+				if let Some((owner, bbox)) = owner_bbox {
+					quadtree.insert(owner, &AABB2::from(&bbox));
+				}
+
+				unsafe {
+					world
+						.get_component_mut_unchecked::<Transform>(entity)
+						.unwrap()
+						.position = position;
+				}
+
+				let collision = match trace.collision {
+					Some(x) => x,
+					None => {
+						quadtree.insert(entity, &AABB2::from(&entity_bbox.offset(position)));
+						continue;
+					}
+				};
+
+				// Projectiles vanish into the sky without exploding
+				if hits_sky(
+					world,
+					map,
+					&map_dynamic,
+					&collision,
+					&entity_bbox.offset(position),
+				) {
+					command_buffer.delete(entity);
+					continue;
+				}
+
+				if hitscan::is_shootable(world, collision.entity) {
+					damage_event_channel.single_write(DamageEvent {
+						source: projectile.owner,
+						inflictor: Some(entity),
+						target: collision.entity,
+						amount: (rng.gen::<u8>() % 8 + 1) as i32 * projectile.damage,
+						damage_type: DamageType::Projectile,
+					});
+				}
+
+				// Explode
+				unsafe {
+					world
+						.get_component_mut_unchecked::<Velocity>(entity)
+						.unwrap()
+						.velocity = Vector3::zeros();
+				}
+
+				match unsafe { world.get_component_mut_unchecked::<StateMachine>(entity) } {
+					Some(mut state_machine) if state_machine.death.is_some() => {
+						state_machine.next = state_machine.death;
+					}
+					_ => command_buffer.delete(entity),
+				}
+
+				if let Some(sound) = projectile.death_sound {
+					sound_queue.push((sound, entity));
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+// Spawns a projectile fired by an entity, returning the sound to play for it
+pub fn spawn_projectile(
+	asset_storage: &AssetStorage,
+	entity_types: &MobjTypes,
+	command_buffer: &mut CommandBuffer,
+	name: &str,
+	owner: Entity,
+	position: Vector3<f32>,
+	yaw: Angle,
+	slope: f32,
+) -> Option<(AssetHandle<Sound>, Entity)> {
+	let template = asset_storage.get(&entity_types.names[name]).unwrap();
+	let projectile = template.get_component::<Projectile>()?;
+
+	let entity = command_buffer.insert((), vec![()])[0];
+	template.add_to_entity(entity, command_buffer);
+
+	command_buffer.add_component(
+		entity,
+		Transform {
+			position: position + Vector3::new(0.0, 0.0, 32.0),
+			rotation: Vector3::new(0.into(), 0.into(), yaw),
+		},
+	);
+	command_buffer.add_component(
+		entity,
+		Velocity {
+			velocity: Vector3::new(yaw.cos() as f32, yaw.sin() as f32, slope) * projectile.speed,
+		},
+	);
+	command_buffer.add_component(
+		entity,
+		Projectile {
+			owner: Some(owner),
+			..projectile.clone()
+		},
+	);
+
+	projectile.spawn_sound.clone().map(|sound| (sound, entity))
+}
+
+// A_Tracer: leaves a trail of smoke behind a homing missile, and steers it towards its
+// owner's target
+fn seek_target(
+	world: &World,
+	asset_storage: &AssetStorage,
+	entity_types: &MobjTypes,
+	command_buffer: &mut CommandBuffer,
+	rng: &mut Pcg64Mcg,
+	missile: Entity,
+) {
+	let (position, yaw) = match world.get_component::<Transform>(missile) {
+		Some(transform) => (transform.position, transform.rotation[2]),
+		None => return,
+	};
+	let velocity = match world.get_component::<Velocity>(missile) {
+		Some(velocity) => velocity.velocity,
+		None => return,
+	};
+	let (speed, owner) = match world.get_component::<Projectile>(missile) {
+		Some(projectile) => (projectile.speed, projectile.owner),
+		None => return,
+	};
+
+	// A puff where the missile is, and smoke where it was a frame ago
+	let behind = Vector3::new(velocity[0], velocity[1], 0.0) * FRAME_TIME.as_secs_f32();
+
+	for &(name, position) in &[("PUFF", position), ("SMOKE", position - behind)] {
+		let template = asset_storage.get(&entity_types.names[name]).unwrap();
+		let entity = command_buffer.insert((), vec![()])[0];
+		template.add_to_entity(entity, command_buffer);
+		command_buffer.add_component(
+			entity,
+			Transform {
+				position,
+				rotation: Vector3::zeros(),
+			},
+		);
+
+		if let Some(state_machine) = template.get_component::<StateMachine>() {
+			let mut state_machine = *state_machine;
+
+			// Vary the length of the first frame a little
+			let random_time = FRAME_TIME * (rng.gen::<u8>() & 3) as u32;
+			state_machine.time_left = state_machine.time_left.map(|time| {
+				time.checked_sub(random_time)
+					.unwrap_or(FRAME_TIME)
+					.max(FRAME_TIME)
+			});
+
+			command_buffer.add_component(entity, state_machine);
+		}
+	}
+
+	let target = match owner
+		.and_then(|owner| world.get_component::<Monster>(owner)?.target)
+		.filter(|&target| hitscan::is_shootable(world, target))
+	{
+		Some(x) => x,
+		None => return,
+	};
+	let target_position = match world.get_component::<Transform>(target) {
+		Some(transform) => transform.position,
+		None => return,
+	};
+
+	// Turn towards the target, a limited amount at a time
+	let delta = target_position - position;
+	let exact = Angle::from_radians(f64::atan2(delta[1] as f64, delta[0] as f64));
+	let turn = exact - yaw;
+	let yaw = if turn.0 > TRACE_ANGLE {
+		yaw + TRACE_ANGLE
+	} else if turn.0 < -TRACE_ANGLE {
+		yaw - TRACE_ANGLE
+	} else {
+		exact
+	};
+
+	// Climb or dive towards a point a little above the target's feet,
+	// judging the slope by how long the missile will take to get there
+	let time = (Vector2::new(delta[0], delta[1]).norm() / speed).max(FRAME_TIME.as_secs_f32());
+	let slope = (delta[2] + 40.0) / time;
+	let z_velocity = if slope < velocity[2] {
+		velocity[2] - 0.125 * FRAME_RATE
+	} else {
+		velocity[2] + 0.125 * FRAME_RATE
+	};
+
+	unsafe {
+		world
+			.get_component_mut_unchecked::<Transform>(missile)
+			.unwrap()
+			.rotation[2] = yaw;
+		world
+			.get_component_mut_unchecked::<Velocity>(missile)
+			.unwrap()
+			.velocity = Vector3::new(
+			yaw.cos() as f32 * speed,
+			yaw.sin() as f32 * speed,
+			z_velocity,
+		);
+	}
+}
+
+// Checks whether a collision was with a sky ceiling, or with an upper wall between two skies
+fn hits_sky(
+	world: &World,
+	map: &Map,
+	map_dynamic: &MapDynamic,
+	collision: &TraceCollision,
+	entity_bbox: &AABB3,
+) -> bool {
+	let is_sky = |sector_index: usize| {
+		map_dynamic.sectors[sector_index].textures[SectorSlot::Ceiling as usize].is_sky()
+	};
+
+	if let Some(sector_ref) = world.get_component::<SectorRef>(collision.entity) {
+		collision.normal[2] < 0.0 && is_sky(sector_ref.index)
+	} else if let Some(linedef_ref) = world.get_component::<LinedefRef>(collision.entity) {
+		if let [Some(front_sidedef), Some(back_sidedef)] = &map.linedefs[linedef_ref.index].sidedefs
+		{
+			let opening = map_dynamic.sectors[front_sidedef.sector_index]
+				.interval
+				.intersection(map_dynamic.sectors[back_sidedef.sector_index].interval);
+
+			entity_bbox[2].max > opening.max
+				&& is_sky(front_sidedef.sector_index)
+				&& is_sky(back_sidedef.sector_index)
+		} else {
+			false
+		}
+	} else {
+		false
+	}
+}
+
+// Damages everything within range of an explosion centred at the given point that it can see,
+// less the further away it is
+pub fn radius_attack(
+	tracer: &EntityTracer,
+	damage_event_channel: &mut EventChannel<DamageEvent>,
+	bomb: Entity,
+	middle: Vector3<f32>,
+	source: Option<Entity>,
+	damage: i32,
+) {
+	// Allow for the radius of the things being damaged
+	let range = damage as f32 + 32.0;
+	let bbox = AABB2::from_extents(
+		middle[1] + range,
+		middle[1] - range,
+		middle[0] - range,
+		middle[0] + range,
+	);
+
+	tracer
+		.quadtree
+		.traverse_nodes(&bbox, &mut |entities: &[Entity]| {
+			for entity in entities.iter().copied() {
+				if entity == bomb || !hitscan::is_shootable(tracer.world, entity) {
+					continue;
+				}
+
+				// The bosses shrug off splash damage
+				if tracer
+					.world
+					.get_component::<Health>(entity)
+					.map_or(false, |health| health.no_radius_damage)
+				{
+					continue;
+				}
+
+				let entity_bbox = match hitscan::entity_bbox(tracer.world, entity) {
+					Some(x) => x,
+					None => continue,
+				};
+
+				// Distance is measured to the edge of the box, ignoring height
+				let entity_middle = entity_bbox.middle();
+				let distance = f32::max(
+					(entity_middle[0] - middle[0]).abs(),
+					(entity_middle[1] - middle[1]).abs(),
+				) - (entity_bbox[0].max - entity_bbox[0].min) * 0.5;
+				let distance = distance.max(0.0) as i32;
+
+				if distance >= damage {
+					continue;
+				}
+
+				// Walls shield from explosions
+				if !hitscan::check_sight(tracer, bomb, middle, &entity_bbox) {
+					continue;
+				}
+
+				damage_event_channel.single_write(DamageEvent {
+					source,
+					inflictor: Some(bomb),
+					target: entity,
+					amount: damage - distance,
+					damage_type: DamageType::Explosion,
+				});
+			}
+		});
+}
+
+// The BFG ball's spray of tracers, fired from its owner in the direction the ball flew
+fn bfg_spray(
+	tracer: &EntityTracer,
+	asset_storage: &AssetStorage,
+	entity_types: &MobjTypes,
+	command_buffer: &mut CommandBuffer,
+	damage_event_channel: &mut EventChannel<DamageEvent>,
+	rng: &mut Pcg64Mcg,
+	ball: Entity,
+) {
+	let owner = match tracer
+		.world
+		.get_component::<Projectile>(ball)
+		.and_then(|projectile| projectile.owner)
+	{
+		Some(x) => x,
+		None => return,
+	};
+	let (start, _) = match hitscan::shoot_origin(tracer.world, owner) {
+		Some(x) => x,
+		None => return,
+	};
+	let yaw = tracer
+		.world
+		.get_component::<Transform>(ball)
+		.unwrap()
+		.rotation[2];
+	let template = asset_storage.get(&entity_types.names["EXTRABFG"]).unwrap();
+
+	for i in 0..40 {
+		let yaw = yaw - (1 << 29) + (1 << 30) / 40 * i;
+		let (target, _) = match hitscan::aim(tracer, owner, start, yaw, AUTOAIM_RANGE) {
+			Some(x) => x,
+			None => continue,
+		};
+		let target_bbox = hitscan::entity_bbox(tracer.world, target).unwrap();
+
+		// Show a flash on the target
+		let mut position = target_bbox.middle();
+		position[2] = target_bbox[2].min + (target_bbox[2].max - target_bbox[2].min) * 0.25;
+
+		let entity = command_buffer.insert((), vec![()])[0];
+		template.add_to_entity(entity, command_buffer);
+		command_buffer.add_component(
+			entity,
+			Transform {
+				position,
+				rotation: Vector3::zeros(),
+			},
+		);
+
+		let amount: i32 = (0..15).map(|_| (rng.gen::<u8>() & 7) as i32 + 1).sum();
+
+		damage_event_channel.single_write(DamageEvent {
+			source: Some(owner),
+			inflictor: Some(owner),
+			target,
+			amount,
+			damage_type: DamageType::Hitscan,
+		});
+	}
+}
@@ -0,0 +1,117 @@
+use crate::{
+	assets::AssetStorage,
+	doom::{client::Client, image::Palettes, inventory::Powers, palette::PaletteFlash},
+};
+use anyhow::Context;
+use legion::prelude::{Read, ResourceSet, Resources, World};
+use std::sync::Arc;
+use vulkano::{
+	command_buffer::{
+		pool::standard::StandardCommandPoolBuilder, AutoCommandBufferBuilder, DynamicState,
+	},
+	device::DeviceOwned,
+	framebuffer::{RenderPassAbstract, Subpass},
+	pipeline::{
+		vertex::{BufferlessDefinition, BufferlessVertices},
+		GraphicsPipeline, GraphicsPipelineAbstract,
+	},
+};
+
+pub struct FlashRenderSystem {
+	pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+}
+
+impl FlashRenderSystem {
+	pub fn new(
+		render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+	) -> anyhow::Result<FlashRenderSystem> {
+		let device = render_pass.device();
+
+		// Create pipeline
+		let vert = flash_vert::Shader::load(device.clone()).context("Couldn't load shader")?;
+		let frag = flash_frag::Shader::load(device.clone()).context("Couldn't load shader")?;
+
+		let pipeline = Arc::new(
+			GraphicsPipeline::start()
+				.render_pass(
+					Subpass::from(render_pass.clone(), 0).context("Subpass index out of range")?,
+				)
+				.vertex_input(BufferlessDefinition)
+				.vertex_shader(vert.main_entry_point(), ())
+				.fragment_shader(frag.main_entry_point(), ())
+				.triangle_list()
+				.viewports_dynamic_scissors_irrelevant(1)
+				.cull_mode_disabled()
+				.depth_stencil_disabled()
+				.blend_alpha_blending()
+				.build(device.clone())
+				.context("Couldn't create pipeline")?,
+		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
+
+		Ok(FlashRenderSystem { pipeline })
+	}
+
+	pub fn draw(
+		&mut self,
+		world: &World,
+		resources: &Resources,
+		mut command_buffer_builder: AutoCommandBufferBuilder<StandardCommandPoolBuilder>,
+		dynamic_state: DynamicState,
+	) -> anyhow::Result<AutoCommandBufferBuilder> {
+		let (asset_storage, client) = <(Read<AssetStorage>, Read<Client>)>::fetch(resources);
+
+		let palette_index = match client.entity.and_then(|entity| {
+			world
+				.get_component::<PaletteFlash>(entity)
+				.map(|palette_flash| (palette_flash, world.get_component::<Powers>(entity)))
+		}) {
+			Some((palette_flash, powers)) => palette_flash.palette_index(powers.as_deref()),
+			None => 0,
+		};
+
+		if palette_index == 0 {
+			return Ok(command_buffer_builder);
+		}
+
+		let palettes = match asset_storage
+			.handle_for::<Palettes>("PLAYPAL")
+			.and_then(|handle| asset_storage.get(&handle))
+		{
+			Some(palettes) if palette_index < palettes.len() => palettes,
+			_ => return Ok(command_buffer_builder),
+		};
+
+		let push_constants = flash_frag::ty::PushConstants {
+			tint: palettes.tint(palette_index),
+		};
+
+		command_buffer_builder = command_buffer_builder
+			.draw(
+				self.pipeline.clone(),
+				&dynamic_state,
+				BufferlessVertices {
+					vertices: 3,
+					instances: 1,
+				},
+				(),
+				push_constants,
+			)
+			.context("Draw error")?;
+
+		Ok(command_buffer_builder)
+	}
+}
+
+mod flash_vert {
+	vulkano_shaders::shader! {
+		ty: "vertex",
+		path: "shaders/flash.vert",
+	}
+}
+
+mod flash_frag {
+	vulkano_shaders::shader! {
+		ty: "fragment",
+		path: "shaders/flash.frag",
+	}
+}
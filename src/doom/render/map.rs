@@ -167,7 +167,7 @@ impl MapRenderSystem {
 				let texture_set = Arc::new(
 					self.normal_texture_set_pool
 						.next()
-						.add_sampled_image(image.clone(), sampler.clone())?
+						.add_sampled_image(image.image.clone(), sampler.clone())?
 						.build()?,
 				);
 
@@ -200,7 +200,7 @@ impl MapRenderSystem {
 				let texture_set = Arc::new(
 					self.normal_texture_set_pool
 						.next()
-						.add_sampled_image(image.clone(), sampler.clone())?
+						.add_sampled_image(image.image.clone(), sampler.clone())?
 						.build()?,
 				);
 
@@ -231,7 +231,7 @@ impl MapRenderSystem {
 			let texture_params_set = Arc::new(
 				self.sky_texture_set_pool
 					.next()
-					.add_sampled_image(image.clone(), sampler.clone())?
+					.add_sampled_image(image.image.clone(), sampler.clone())?
 					.add_buffer(sky_buffer)?
 					.build()?,
 			);
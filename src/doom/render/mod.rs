@@ -1,4 +1,6 @@
+pub mod flash;
 pub mod map;
+pub mod psprite;
 pub mod sprite;
 
 use crate::{
@@ -6,7 +8,9 @@ use crate::{
 		client::Client,
 		components::{Camera, Transform},
 		render::{
+			flash::FlashRenderSystem,
 			map::{MapRenderSystem, UniformBufferObject},
+			psprite::PSpriteRenderSystem,
 			sprite::SpriteRenderSystem,
 		},
 	},
@@ -32,10 +36,12 @@ use vulkano::{
 };
 
 pub struct RenderSystem {
+	flash: FlashRenderSystem,
 	framebuffers: Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
 	map: MapRenderSystem,
 	matrix_uniform_pool: CpuBufferPool<UniformBufferObject>,
 	matrix_set_pool: FixedSizeDescriptorSetsPool,
+	psprites: PSpriteRenderSystem,
 	render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
 	sampler: Arc<Sampler>,
 	sprites: SpriteRenderSystem,
@@ -133,6 +139,8 @@ impl RenderSystem {
 		let matrix_set_pool = FixedSizeDescriptorSetsPool::new(layout);
 
 		Ok(RenderSystem {
+			flash: FlashRenderSystem::new(render_pass.clone())
+				.context("Couldn't create FlashRenderSystem")?,
 			framebuffers,
 			map: MapRenderSystem::new(render_pass.clone())
 				.context("Couldn't create MapRenderSystem")?,
@@ -141,6 +149,8 @@ impl RenderSystem {
 				BufferUsage::uniform_buffer(),
 			),
 			matrix_set_pool,
+			psprites: PSpriteRenderSystem::new(render_pass.clone(), &*render_context)
+				.context("Couldn't create PSpriteRenderSystem")?,
 			render_pass: render_pass.clone(),
 			sampler,
 			sprites: SpriteRenderSystem::new(render_pass, &*render_context)
@@ -283,13 +293,45 @@ impl RenderSystem {
 					world,
 					resources,
 					command_buffer_builder,
-					dynamic_state,
+					dynamic_state.clone(),
 					self.sampler.clone(),
 					matrix_set,
 					rotation[2],
 					position,
 				)
 				.context("Draw error")?;
+
+			// Draw player weapon sprites, in screen space
+			let data = UniformBufferObject {
+				view: Matrix4::identity().into(),
+				proj: screen_matrix(dimensions).into(),
+			};
+
+			let matrix_buffer = self.matrix_uniform_pool.next(data)?;
+			let matrix_set = Arc::new(
+				self.matrix_set_pool
+					.next()
+					.add_buffer(matrix_buffer)?
+					.build()?,
+			);
+
+			command_buffer_builder = self
+				.psprites
+				.draw(
+					world,
+					resources,
+					command_buffer_builder,
+					dynamic_state.clone(),
+					self.sampler.clone(),
+					matrix_set,
+				)
+				.context("Draw error")?;
+
+			// Draw palette flash
+			command_buffer_builder = self
+				.flash
+				.draw(world, resources, command_buffer_builder, dynamic_state)
+				.context("Draw error")?;
 		}
 
 		// Finalise
@@ -336,3 +378,20 @@ fn projection_matrix(fovx: f32, aspect: f32, near: f32, far: f32) -> Matrix4<f32
 		1.0       , 0.0, 0.0        , 0.0               ,
 	)
 }
+
+// A matrix that maps Doom's 320x200 screen coordinates to the middle of the screen,
+// keeping the original 4:3 shape.
+// The whole matrix is scaled so that the fragment shader sees a small fixed distance,
+// which makes it light psprites the same way as the original.
+#[rustfmt::skip]
+fn screen_matrix(dimensions: [f32; 2]) -> Matrix4<f32> {
+	let ratio = (4.0 / 3.0) / (dimensions[0] / dimensions[1]);
+	let w = (80.0 / 23.5 - 1.0) / 0.0625;
+
+	Matrix4::new(
+		ratio / 160.0, 0.0        , 0.0, -ratio,
+		0.0          , 1.0 / 100.0, 0.0, -1.0  ,
+		0.0          , 0.0        , 0.0, 0.0   ,
+		0.0          , 0.0        , 0.0, 1.0   ,
+	) * w
+}
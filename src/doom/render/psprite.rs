@@ -0,0 +1,209 @@
+use crate::{
+	assets::AssetStorage,
+	doom::{
+		client::Client,
+		components::Transform,
+		data::States,
+		map::MapDynamic,
+		render::{
+			normal_frag,
+			sprite::{InstanceData, VertexData},
+		},
+		weapon::WeaponState,
+	},
+	renderer::{AsBytes, RenderContext},
+};
+use anyhow::Context;
+use legion::prelude::{IntoQuery, Read, ResourceSet, Resources, World};
+use nalgebra::{Matrix4, Vector2};
+use std::sync::Arc;
+use vulkano::{
+	buffer::{BufferUsage, CpuBufferPool, ImmutableBuffer},
+	command_buffer::{
+		pool::standard::StandardCommandPoolBuilder, AutoCommandBufferBuilder, DynamicState,
+	},
+	descriptor::{
+		descriptor_set::{DescriptorSet, FixedSizeDescriptorSetsPool},
+		PipelineLayoutAbstract,
+	},
+	device::DeviceOwned,
+	framebuffer::{RenderPassAbstract, Subpass},
+	pipeline::{
+		vertex::OneVertexOneInstanceDefinition, GraphicsPipeline, GraphicsPipelineAbstract,
+	},
+	sampler::Sampler,
+};
+
+pub struct PSpriteRenderSystem {
+	instance_buffer_pool: CpuBufferPool<InstanceData>,
+	vertex_buffer: Arc<ImmutableBuffer<[u8]>>,
+	pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+	texture_set_pool: FixedSizeDescriptorSetsPool,
+}
+
+impl PSpriteRenderSystem {
+	pub fn new(
+		render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+		render_context: &RenderContext,
+	) -> anyhow::Result<PSpriteRenderSystem> {
+		let device = render_pass.device();
+
+		// Create pipeline
+		let vert = sprite_vert::Shader::load(device.clone()).context("Couldn't load shader")?;
+		let frag = normal_frag::Shader::load(device.clone()).context("Couldn't load shader")?;
+
+		// Psprites are drawn over everything else
+		let pipeline = Arc::new(
+			GraphicsPipeline::start()
+				.render_pass(
+					Subpass::from(render_pass.clone(), 0).context("Subpass index out of range")?,
+				)
+				.vertex_input(OneVertexOneInstanceDefinition::<VertexData, InstanceData>::new())
+				.vertex_shader(vert.main_entry_point(), ())
+				.fragment_shader(frag.main_entry_point(), ())
+				.triangle_fan()
+				.primitive_restart(true)
+				.viewports_dynamic_scissors_irrelevant(1)
+				.cull_mode_disabled()
+				.depth_stencil_disabled()
+				.build(device.clone())
+				.context("Couldn't create pipeline")?,
+		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
+
+		// Create mesh
+		let (vertex_buffer, _future) = ImmutableBuffer::from_iter(
+			vec![
+				VertexData {
+					in_position: [0.0, -1.0, 0.0],
+					in_texture_coord: [1.0, 0.0],
+				},
+				VertexData {
+					in_position: [0.0, 0.0, 0.0],
+					in_texture_coord: [0.0, 0.0],
+				},
+				VertexData {
+					in_position: [0.0, 0.0, -1.0],
+					in_texture_coord: [0.0, 1.0],
+				},
+				VertexData {
+					in_position: [0.0, -1.0, -1.0],
+					in_texture_coord: [1.0, 1.0],
+				},
+			]
+			.as_bytes()
+			.iter()
+			.copied(),
+			BufferUsage::vertex_buffer(),
+			render_context.queues().graphics.clone(),
+		)?;
+
+		Ok(PSpriteRenderSystem {
+			vertex_buffer,
+			instance_buffer_pool: CpuBufferPool::new(device.clone(), BufferUsage::vertex_buffer()),
+			texture_set_pool: FixedSizeDescriptorSetsPool::new(
+				pipeline.descriptor_set_layout(1).unwrap().clone(),
+			),
+			pipeline,
+		})
+	}
+
+	pub fn draw(
+		&mut self,
+		world: &World,
+		resources: &Resources,
+		mut command_buffer_builder: AutoCommandBufferBuilder<StandardCommandPoolBuilder>,
+		dynamic_state: DynamicState,
+		sampler: Arc<Sampler>,
+		matrix_set: Arc<dyn DescriptorSet + Send + Sync>,
+	) -> anyhow::Result<AutoCommandBufferBuilder> {
+		let (asset_storage, client, states) =
+			<(Read<AssetStorage>, Read<Client>, Read<States>)>::fetch(resources);
+
+		let entity = match client.entity {
+			Some(x) => x,
+			None => return Ok(command_buffer_builder),
+		};
+
+		let weapon_state = match world.get_component::<WeaponState>(entity) {
+			Some(x) => x,
+			None => return Ok(command_buffer_builder),
+		};
+
+		// Psprites are lit by the sector the player is in
+		let sector_light_level = {
+			let map_dynamic = <Read<MapDynamic>>::query().iter(world).next().unwrap();
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+			let position = world.get_component::<Transform>(entity).unwrap().position;
+			let ssect = map.find_subsector(Vector2::new(position[0], position[1]));
+			map_dynamic.sectors[ssect.sector_index].light_level
+		};
+
+		for psprite in weapon_state.psprites.iter() {
+			let state = match psprite.state {
+				Some(x) => &states.states[x],
+				None => continue,
+			};
+
+			let sprite = asset_storage.get(&state.sprite).unwrap();
+			let frame = &sprite.frames()[state.frame];
+
+			// This frame has no images, nothing to render
+			if frame.is_empty() {
+				continue;
+			}
+
+			let image_info = &frame[0];
+			let sprite_image = asset_storage.get(&image_info.handle).unwrap();
+
+			let light_level = if state.full_bright {
+				1.0
+			} else {
+				sector_light_level
+			};
+
+			// The sprite's left and up axes become screen right and down,
+			// with the horizontal position relative to the middle of the screen
+			#[rustfmt::skip]
+			let instance_matrix = Matrix4::new(
+				0.0, -1.0, 0.0 , 160.0 + psprite.position[0],
+				0.0, 0.0 , -1.0, psprite.position[1]        ,
+				0.0, 0.0 , 0.0 , 0.0                        ,
+				0.0, 0.0 , 0.0 , 1.0                        ,
+			) * sprite_image.matrix;
+
+			let instance_data = InstanceData {
+				in_flip: image_info.flip,
+				in_light_level: light_level,
+				in_matrix: instance_matrix.into(),
+			};
+
+			let texture_set = Arc::new(
+				self.texture_set_pool
+					.next()
+					.add_sampled_image(sprite_image.image.clone(), sampler.clone())?
+					.build()?,
+			);
+
+			let instance_buffer = Arc::new(self.instance_buffer_pool.chunk(vec![instance_data])?);
+
+			command_buffer_builder = command_buffer_builder
+				.draw(
+					self.pipeline.clone(),
+					&dynamic_state,
+					vec![self.vertex_buffer.clone(), instance_buffer],
+					(matrix_set.clone(), texture_set),
+					(),
+				)
+				.context("Draw error")?;
+		}
+
+		Ok(command_buffer_builder)
+	}
+}
+
+mod sprite_vert {
+	vulkano_shaders::shader! {
+		ty: "vertex",
+		path: "shaders/sprite.vert",
+	}
+}
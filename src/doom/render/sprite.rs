@@ -1,7 +1,8 @@
 use crate::{
 	assets::{AssetHandle, AssetStorage},
 	doom::{
-		client::Client, components::Transform, map::MapDynamic, render::normal_frag, sprite::Sprite,
+		client::Client, components::Transform, image::Translation, map::MapDynamic,
+		render::normal_frag, sprite::Sprite,
 	},
 	geometry::Angle,
 	renderer::{AsBytes, RenderContext},
@@ -10,7 +11,8 @@ use anyhow::Context;
 use fnv::FnvHashMap;
 use legion::prelude::{IntoQuery, Read, ResourceSet, Resources, World};
 use nalgebra::{Matrix4, Vector2, Vector3};
-use std::{collections::hash_map::Entry, sync::Arc};
+use serde::Deserialize;
+use std::{cmp::Ordering, collections::hash_map::Entry, sync::Arc};
 use vulkano::{
 	buffer::{BufferUsage, CpuBufferPool, ImmutableBuffer},
 	command_buffer::{
@@ -25,7 +27,10 @@ use vulkano::{
 	image::ImageViewAccess,
 	impl_vertex,
 	pipeline::{
-		vertex::OneVertexOneInstanceDefinition, GraphicsPipeline, GraphicsPipelineAbstract,
+		blend::{AttachmentBlend, BlendFactor},
+		depth_stencil::DepthStencil,
+		vertex::OneVertexOneInstanceDefinition,
+		GraphicsPipeline, GraphicsPipelineAbstract,
 	},
 	sampler::Sampler,
 };
@@ -34,7 +39,12 @@ pub struct SpriteRenderSystem {
 	instance_buffer_pool: CpuBufferPool<InstanceData>,
 	vertex_buffer: Arc<ImmutableBuffer<[u8]>>,
 	pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+	pipeline_fuzz: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+	pipeline_translucent: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+	pipeline_additive: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
 	texture_set_pool: FixedSizeDescriptorSetsPool,
+	fuzz_table: [u32; 2],
+	fuzz_pos: usize,
 }
 
 impl SpriteRenderSystem {
@@ -65,6 +75,82 @@ impl SpriteRenderSystem {
 				.context("Couldn't create pipeline")?,
 		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
 
+		// Blended sprites are tested against the depth buffer, but don't write to it
+		let blend_depth_stencil = DepthStencil {
+			depth_write: false,
+			..DepthStencil::simple_depth_test()
+		};
+
+		let fuzz_frag =
+			sprite_fuzz_frag::Shader::load(device.clone()).context("Couldn't load shader")?;
+		let pipeline_fuzz = Arc::new(
+			GraphicsPipeline::start()
+				.render_pass(
+					Subpass::from(render_pass.clone(), 0).context("Subpass index out of range")?,
+				)
+				.vertex_input(OneVertexOneInstanceDefinition::<VertexData, InstanceData>::new())
+				.vertex_shader(vert.main_entry_point(), ())
+				.fragment_shader(fuzz_frag.main_entry_point(), ())
+				.triangle_fan()
+				.primitive_restart(true)
+				.viewports_dynamic_scissors_irrelevant(1)
+				.cull_mode_disabled()
+				.depth_stencil(blend_depth_stencil.clone())
+				.blend_alpha_blending()
+				.build(device.clone())
+				.context("Couldn't create pipeline")?,
+		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
+
+		let blend_frag =
+			sprite_blend_frag::Shader::load(device.clone()).context("Couldn't load shader")?;
+		let pipeline_translucent = Arc::new(
+			GraphicsPipeline::start()
+				.render_pass(
+					Subpass::from(render_pass.clone(), 0).context("Subpass index out of range")?,
+				)
+				.vertex_input(OneVertexOneInstanceDefinition::<VertexData, InstanceData>::new())
+				.vertex_shader(vert.main_entry_point(), ())
+				.fragment_shader(blend_frag.main_entry_point(), ())
+				.triangle_fan()
+				.primitive_restart(true)
+				.viewports_dynamic_scissors_irrelevant(1)
+				.cull_mode_disabled()
+				.depth_stencil(blend_depth_stencil.clone())
+				.blend_alpha_blending()
+				.build(device.clone())
+				.context("Couldn't create pipeline")?,
+		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
+
+		let pipeline_additive = Arc::new(
+			GraphicsPipeline::start()
+				.render_pass(
+					Subpass::from(render_pass.clone(), 0).context("Subpass index out of range")?,
+				)
+				.vertex_input(OneVertexOneInstanceDefinition::<VertexData, InstanceData>::new())
+				.vertex_shader(vert.main_entry_point(), ())
+				.fragment_shader(blend_frag.main_entry_point(), ())
+				.triangle_fan()
+				.primitive_restart(true)
+				.viewports_dynamic_scissors_irrelevant(1)
+				.cull_mode_disabled()
+				.depth_stencil(blend_depth_stencil)
+				.blend_collective(AttachmentBlend {
+					color_destination: BlendFactor::One,
+					..AttachmentBlend::alpha_blending()
+				})
+				.build(device.clone())
+				.context("Couldn't create pipeline")?,
+		) as Arc<dyn GraphicsPipelineAbstract + Send + Sync>;
+
+		// Pack the fuzz table into bits for the shader, 1 for down and 0 for up
+		let mut fuzz_table = [0u32; 2];
+
+		for (i, &offset) in FUZZ_OFFSETS.iter().enumerate() {
+			if offset > 0 {
+				fuzz_table[i / 32] |= 1 << (i % 32);
+			}
+		}
+
 		// Create mesh
 		let (vertex_buffer, _future) = ImmutableBuffer::from_iter(
 			vec![
@@ -94,6 +180,11 @@ impl SpriteRenderSystem {
 
 		Ok(SpriteRenderSystem {
 			vertex_buffer,
+			pipeline_fuzz,
+			pipeline_translucent,
+			pipeline_additive,
+			fuzz_table,
+			fuzz_pos: 0,
 
 			instance_buffer_pool: CpuBufferPool::new(device.clone(), BufferUsage::vertex_buffer()),
 			texture_set_pool: FixedSizeDescriptorSetsPool::new(
@@ -123,6 +214,14 @@ impl SpriteRenderSystem {
 		let mut batches: FnvHashMap<Arc<dyn ImageViewAccess + Send + Sync>, Vec<InstanceData>> =
 			FnvHashMap::default();
 
+		// Blended sprites are drawn separately afterwards, back to front
+		let mut blended: Vec<(
+			f32,
+			RenderStyle,
+			Arc<dyn ImageViewAccess + Send + Sync>,
+			InstanceData,
+		)> = Vec::new();
+
 		for (entity, (sprite_render, transform)) in
 			<(Read<SpriteRender>, Read<Transform>)>::query().iter_entities(world)
 		{
@@ -176,8 +275,26 @@ impl SpriteRenderSystem {
 				in_matrix: instance_matrix.into(),
 			};
 
+			// Use the translated image if there is one
+			let image = sprite_render
+				.translation
+				.as_ref()
+				.and_then(|handle| sprite_image.translations.get(handle))
+				.unwrap_or(&sprite_image.image);
+
+			if sprite_render.render_style != RenderStyle::Normal {
+				let distance = (transform.position - view_pos).norm_squared();
+				blended.push((
+					distance,
+					sprite_render.render_style,
+					image.clone(),
+					instance_data,
+				));
+				continue;
+			}
+
 			// Add to batches
-			match batches.entry(asset_storage.get(&image_info.handle).unwrap().image.clone()) {
+			match batches.entry(image.clone()) {
 				Entry::Occupied(mut entry) => {
 					entry.get_mut().push(instance_data);
 				}
@@ -209,6 +326,56 @@ impl SpriteRenderSystem {
 				.context("Draw error")?;
 		}
 
+		// Draw blended sprites, furthest first
+		blended.sort_unstable_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+		for (_, render_style, texture, instance_data) in blended {
+			let texture_set = Arc::new(
+				self.texture_set_pool
+					.next()
+					.add_sampled_image(texture, sampler.clone())?
+					.build()?,
+			);
+
+			let instance_buffer = Arc::new(self.instance_buffer_pool.chunk(vec![instance_data])?);
+			let vertex_buffers = vec![self.vertex_buffer.clone(), instance_buffer];
+			let sets = (matrix_set.clone(), texture_set);
+
+			command_buffer_builder = match render_style {
+				RenderStyle::Normal => unreachable!(),
+				RenderStyle::Fuzz => {
+					let push_constants = sprite_fuzz_frag::ty::PushConstants {
+						fuzz_table: self.fuzz_table,
+						fuzz_pos: self.fuzz_pos as u32,
+					};
+					self.fuzz_pos = (self.fuzz_pos + 1) % FUZZ_OFFSETS.len();
+
+					command_buffer_builder.draw(
+						self.pipeline_fuzz.clone(),
+						&dynamic_state,
+						vertex_buffers,
+						sets,
+						push_constants,
+					)
+				}
+				RenderStyle::Translucent(alpha) => command_buffer_builder.draw(
+					self.pipeline_translucent.clone(),
+					&dynamic_state,
+					vertex_buffers,
+					sets,
+					sprite_blend_frag::ty::PushConstants { alpha },
+				),
+				RenderStyle::Additive(alpha) => command_buffer_builder.draw(
+					self.pipeline_additive.clone(),
+					&dynamic_state,
+					vertex_buffers,
+					sets,
+					sprite_blend_frag::ty::PushConstants { alpha },
+				),
+			}
+			.context("Draw error")?;
+		}
+
 		Ok(command_buffer_builder)
 	}
 }
@@ -220,6 +387,26 @@ mod sprite_vert {
 	}
 }
 
+mod sprite_blend_frag {
+	vulkano_shaders::shader! {
+		ty: "fragment",
+		path: "shaders/sprite_blend.frag",
+	}
+}
+
+mod sprite_fuzz_frag {
+	vulkano_shaders::shader! {
+		ty: "fragment",
+		path: "shaders/sprite_fuzz.frag",
+	}
+}
+
+// Vanilla fuzzoffset table, in rows relative to the pixel being drawn
+pub const FUZZ_OFFSETS: [i8; 50] = [
+	1, -1, 1, -1, 1, 1, -1, 1, 1, -1, 1, 1, 1, -1, 1, 1, 1, -1, -1, -1, -1, 1, -1, -1, 1, 1, 1, 1,
+	-1, 1, -1, 1, 1, -1, -1, 1, 1, -1, -1, -1, -1, 1, 1, 1, 1, -1, 1, 1, -1, 1,
+];
+
 #[derive(Clone, Debug, Default)]
 pub struct VertexData {
 	pub in_position: [f32; 3],
@@ -240,4 +427,14 @@ pub struct SpriteRender {
 	pub sprite: AssetHandle<Sprite>,
 	pub frame: usize,
 	pub full_bright: bool,
+	pub translation: Option<AssetHandle<Translation>>,
+	pub render_style: RenderStyle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum RenderStyle {
+	Normal,
+	Fuzz,
+	Translucent(f32),
+	Additive(f32),
 }
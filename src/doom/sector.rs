@@ -0,0 +1,119 @@
+use crate::{
+	assets::AssetStorage,
+	doom::{
+		client::Client,
+		components::{Health, Transform},
+		damage::{DamageEvent, DamageType},
+		data::FRAME_TIME,
+		game::{ExitType, GameSession},
+		inventory::Powers,
+		map::MapDynamic,
+	},
+};
+use legion::prelude::{CommandBuffer, IntoQuery, Read, ResourceSet, Resources, World, Write};
+use nalgebra::Vector2;
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub fn sector_special_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut damage_time = Duration::default();
+
+	Box::new(move |world, resources| {
+		let (asset_storage, client, mut damage_event_channel, delta, mut game_session, mut rng) =
+			<(
+				Read<AssetStorage>,
+				Read<Client>,
+				Write<EventChannel<DamageEvent>>,
+				Read<Duration>,
+				Write<GameSession>,
+				Write<Pcg64Mcg>,
+			)>::fetch_mut(resources);
+
+		// Floors only do damage once every 32 tics
+		damage_time += *delta;
+		let do_damage = if damage_time >= 32 * FRAME_TIME {
+			damage_time -= 32 * FRAME_TIME;
+			true
+		} else {
+			false
+		};
+
+		// Like in vanilla, only players are affected by sector specials
+		let entity = match client.entity {
+			Some(x) => x,
+			None => return,
+		};
+		let position = match world.get_component::<Transform>(entity) {
+			Some(transform) => transform.position,
+			None => return,
+		};
+
+		let (sector_entity, floor_height) = {
+			let map_dynamic = match <Read<MapDynamic>>::query().iter(world).next() {
+				Some(x) => x,
+				None => return,
+			};
+			let map = asset_storage.get(&map_dynamic.map).unwrap();
+			let sector_index = map
+				.find_subsector(Vector2::new(position[0], position[1]))
+				.sector_index;
+			let sector_dynamic = &map_dynamic.sectors[sector_index];
+			(sector_dynamic.entity, sector_dynamic.interval.min)
+		};
+
+		// Must be standing on the floor
+		if position[2] > floor_height {
+			return;
+		}
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		if world.has_component::<SectorSecret>(sector_entity) {
+			game_session.secrets_found += 1;
+			command_buffer.remove_component::<SectorSecret>(sector_entity);
+		}
+
+		if let Some(sector_damage) = world.get_component::<SectorDamage>(sector_entity) {
+			if do_damage {
+				let suit = world
+					.get_component::<Powers>(entity)
+					.map_or(false, |powers| powers.iron_feet > Duration::default());
+
+				if sector_damage.end_level || !suit || rng.gen::<u8>() < sector_damage.leak_chance {
+					damage_event_channel.single_write(DamageEvent {
+						source: None,
+						inflictor: None,
+						target: entity,
+						amount: sector_damage.damage,
+						damage_type: DamageType::Sector,
+					});
+				}
+			}
+
+			if sector_damage.end_level {
+				if let Some(health) = world.get_component::<Health>(entity) {
+					if health.current <= 10 {
+						game_session.exit = Some(ExitType::Normal);
+					}
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct SectorDamage {
+	pub damage: i32,
+	// Chance out of 256 that the damage gets through a radiation suit
+	pub leak_chance: u8,
+	// Ignore the radiation suit, and end the level when health gets low
+	pub end_level: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SectorSecret;
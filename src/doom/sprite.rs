@@ -1,8 +1,8 @@
 use crate::{
 	assets::{Asset, AssetFormat, AssetHandle, AssetStorage, DataSource},
-	doom::image::{Image, ImageFormat},
+	doom::image::{ImageFormat, ReplaceableImage, Translation},
 };
-use anyhow::bail;
+use fnv::FnvHashMap;
 use lazy_static::lazy_static;
 use nalgebra::Matrix4;
 use regex::Regex;
@@ -91,83 +91,64 @@ impl Asset for Sprite {
 	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
 		lazy_static! {
 			static ref SPRITENAME: Regex =
-				Regex::new(r#"^....[A-Z][0-9](?:[A-Z][0-9])?$"#).unwrap();
+				Regex::new(r#"^....[A-Z\[\\\]][0-9A-G](?:[A-Z\[\\\]][0-9A-G])?$"#).unwrap();
 		}
 
 		let mut image_names = Vec::new();
-		let mut info = Vec::new();
-		let mut max_frame = 0;
+		let mut info: Vec<Vec<(Option<usize>, SpriteImageInfoIntermediate)>> = Vec::new();
 
 		for lump_name in source
 			.names()
 			.filter(|n| n.starts_with(name) && SPRITENAME.is_match(n))
 		{
-			// Regular frame
-			let frame = lump_name.chars().nth(4).unwrap() as isize - 'A' as isize;
-			assert!(frame >= 0 && frame < 29);
-			let rotation = lump_name.chars().nth(5).unwrap() as isize - '1' as isize;
-			assert!(rotation >= -1 && rotation < 8);
-			info.push((
-				frame as usize,
-				rotation,
-				SpriteImageInfoIntermediate {
-					flip: 1.0,
-					image_index: image_names.len(),
-				},
-			));
-			max_frame = usize::max(max_frame, frame as usize);
+			let chars: Vec<char> = lump_name.chars().collect();
+			let mut halves = vec![(chars[4], chars[5], 1.0)];
 
 			// Horizontally flipped frame, if any
-			if lump_name.len() == 8 {
-				let frame = lump_name.chars().nth(6).unwrap() as isize - 'A' as isize;
-				assert!(frame >= 0 && frame < 29);
-				let rotation = lump_name.chars().nth(7).unwrap() as isize - '1' as isize;
-				assert!(rotation >= -1 && rotation < 8);
-				info.push((
-					frame as usize,
+			if chars.len() == 8 {
+				halves.push((chars[6], chars[7], -1.0));
+			}
+
+			let mut parsed = Vec::with_capacity(halves.len());
+
+			for (frame, rotation, flip) in halves {
+				match (parse_frame(frame), parse_rotation(rotation)) {
+					(Some(frame), Some(rotation)) => parsed.push((frame, rotation, flip)),
+					_ => {
+						log::warn!("Sprite lump {} has an invalid name, skipping", lump_name);
+						parsed.clear();
+						break;
+					}
+				}
+			}
+
+			if parsed.is_empty() {
+				continue;
+			}
+
+			for (frame, rotation, flip) in parsed {
+				if frame >= info.len() {
+					info.resize_with(frame + 1, Vec::new);
+				}
+
+				info[frame].push((
 					rotation,
 					SpriteImageInfoIntermediate {
-						flip: -1.0,
+						flip,
 						image_index: image_names.len(),
 					},
 				));
-				max_frame = usize::max(max_frame, frame as usize);
 			}
 
 			// Add the texture
 			image_names.push(lump_name.to_owned());
 		}
 
-		info.sort_unstable_by(|a, b| Ord::cmp(&a.0, &b.0).then(Ord::cmp(&a.1, &b.1)));
-		let mut slice = info.as_slice();
-		let mut frames: Vec<Vec<SpriteImageInfoIntermediate>> = vec![Vec::new(); max_frame + 1];
-
-		while !slice.is_empty() {
-			let frame = slice[0].0;
-			let next_pos = slice
-				.iter()
-				.position(|x| x.0 != frame)
-				.unwrap_or(slice.len());
-			let current = &slice[..next_pos];
-			slice = &slice[next_pos..];
-
-			if current.len() == 1 {
-				let rotation = current[0].1;
-				assert_eq!(rotation, -1);
-				frames[frame] = current.iter().map(|r| r.2).collect();
-			} else if current.len() == 8 {
-				frames[frame] = current
-					.iter()
-					.enumerate()
-					.map(|(i, r)| {
-						assert_eq!(i as isize, r.1);
-						r.2
-					})
-					.collect();
-			} else {
-				bail!("Frame {} has an invalid number of rotations", frame);
-			}
-		}
+		let frames = info
+			.into_iter()
+			.enumerate()
+			.map(|(frame, rotations)| build_rotations(name, frame, rotations))
+			.collect();
 
 		Ok(SpriteBuilder::new()
 			.with_frames(frames)
@@ -175,18 +156,112 @@ impl Asset for Sprite {
 	}
 }
 
+// Frames are A-Z, followed by [, \ and ] for sprites with more than 26 frames
+fn parse_frame(c: char) -> Option<usize> {
+	match c {
+		'A'..=']' => Some(c as usize - 'A' as usize),
+		_ => None,
+	}
+}
+
+// Returns the rotation as an index out of 16, or None for rotation 0 (all angles).
+// Rotations 1-8 are the traditional ones, 9 and A-G lie in between them.
+fn parse_rotation(c: char) -> Option<Option<usize>> {
+	match c {
+		'0' => Some(None),
+		'1'..='8' => Some(Some((c as usize - '1' as usize) * 2)),
+		'9' => Some(Some(1)),
+		'A'..='G' => Some(Some((c as usize - 'A' as usize) * 2 + 3)),
+		_ => None,
+	}
+}
+
+fn build_rotations(
+	name: &str,
+	frame: usize,
+	rotations: Vec<(Option<usize>, SpriteImageInfoIntermediate)>,
+) -> Vec<SpriteImageInfoIntermediate> {
+	let mut all_angles = None;
+	let mut specific = Vec::new();
+
+	for (rotation, info) in rotations {
+		match rotation {
+			None => {
+				if all_angles.is_some() {
+					log::warn!(
+						"Sprite {} frame {} has multiple rotation 0 images",
+						name,
+						frame
+					);
+				}
+
+				all_angles = Some(info);
+			}
+			Some(rotation) => specific.push((rotation, info)),
+		}
+	}
+
+	// Frame with only a single image for all angles
+	if specific.is_empty() {
+		return all_angles.into_iter().collect();
+	}
+
+	// Frame with individual rotations, with rotation 0 as a fallback for missing ones
+	let count = if specific.iter().any(|(rotation, _)| rotation % 2 == 1) {
+		16
+	} else {
+		8
+	};
+	let step = 16 / count;
+	let mut frames = vec![None; count];
+
+	for (rotation, info) in specific {
+		let index = rotation / step;
+
+		if frames[index].is_some() {
+			log::warn!(
+				"Sprite {} frame {} has multiple images for rotation {}",
+				name,
+				frame,
+				index + 1
+			);
+		}
+
+		frames[index] = Some(info);
+	}
+
+	let fallback = all_angles.or_else(|| {
+		if frames.iter().any(Option::is_none) {
+			log::warn!(
+				"Sprite {} frame {} is missing rotations without a rotation 0 image",
+				name,
+				frame
+			);
+		}
+
+		frames.iter().flatten().next().copied()
+	});
+
+	frames
+		.into_iter()
+		.map(|info| info.or(fallback).unwrap())
+		.collect()
+}
+
 pub struct SpriteImage {
 	pub image: Arc<dyn ImageViewAccess + Send + Sync>,
 	pub matrix: Matrix4<f32>,
+	pub translations: FnvHashMap<AssetHandle<Translation>, Arc<dyn ImageViewAccess + Send + Sync>>,
 }
 
 impl Asset for SpriteImage {
 	type Data = Self;
-	type Intermediate = Image;
+	type Intermediate = ReplaceableImage;
 	const NAME: &'static str = "SpriteImage";
 
 	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
-		ImageFormat.import(name, source)
+		let image = ImageFormat.import(name, source)?;
+		Ok(ReplaceableImage::import(image, name, source))
 	}
 }
 
@@ -0,0 +1,208 @@
+use crate::{
+	assets::AssetHandle,
+	doom::{data::States, render::sprite::SpriteRender, sprite::Sprite},
+	quadtree::Quadtree,
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use shrev::EventChannel;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct State {
+	pub sprite: AssetHandle<Sprite>,
+	pub frame: usize,
+	pub full_bright: bool,
+	// None means the state lasts forever
+	pub time: Option<Duration>,
+	pub action: Option<StateAction>,
+	pub next: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateAction {
+	Light0,
+	WeaponReady,
+	Lower,
+	Raise,
+	Punch,
+	ReFire,
+	FirePistol,
+	Light1,
+	FireShotgun,
+	Light2,
+	FireShotgun2,
+	CheckReload,
+	OpenShotgun2,
+	LoadShotgun2,
+	CloseShotgun2,
+	FireCGun,
+	GunFlash,
+	FireMissile,
+	Saw,
+	FirePlasma,
+	BfgSound,
+	FireBfg,
+	BfgSpray,
+	Explode,
+	Pain,
+	PlayerScream,
+	Fall,
+	XScream,
+	Look,
+	Chase,
+	FaceTarget,
+	PosAttack,
+	Scream,
+	SPosAttack,
+	VileChase,
+	VileStart,
+	VileTarget,
+	VileAttack,
+	StartFire,
+	Fire,
+	FireCrackle,
+	Tracer,
+	SkelWhoosh,
+	SkelFist,
+	SkelMissile,
+	FatRaise,
+	FatAttack1,
+	FatAttack2,
+	FatAttack3,
+	BossDeath,
+	CPosAttack,
+	CPosRefire,
+	TroopAttack,
+	SargAttack,
+	HeadAttack,
+	BruisAttack,
+	SkullAttack,
+	Metal,
+	SpidRefire,
+	BabyMetal,
+	BspiAttack,
+	Hoof,
+	CyberAttack,
+	PainAttack,
+	PainDie,
+	KeenDie,
+	BrainPain,
+	BrainScream,
+	BrainDie,
+	BrainAwake,
+	BrainSpit,
+	SpawnSound,
+	SpawnFly,
+	BrainExplode,
+}
+
+// Sent when an entity enters a state with an action,
+// to be handled by whichever system implements that action
+#[derive(Clone, Copy, Debug)]
+pub struct StateActionEvent {
+	pub entity: Entity,
+	pub action: StateAction,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StateMachine {
+	pub current: usize,
+	// None means the current state lasts forever
+	pub time_left: Option<Duration>,
+	// Set by other systems to switch to another state on the next update
+	pub next: Option<usize>,
+
+	pub spawn: usize,
+	pub see: Option<usize>,
+	pub pain: Option<usize>,
+	pub melee: Option<usize>,
+	pub missile: Option<usize>,
+	pub death: Option<usize>,
+	pub xdeath: Option<usize>,
+	pub raise: Option<usize>,
+}
+
+impl StateMachine {
+	pub fn new(states: &States, spawn: usize) -> StateMachine {
+		StateMachine {
+			current: spawn,
+			time_left: states.states[spawn].time,
+			next: None,
+
+			spawn,
+			see: None,
+			pain: None,
+			melee: None,
+			missile: None,
+			death: None,
+			xdeath: None,
+			raise: None,
+		}
+	}
+}
+
+pub fn state_machine_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	Box::new(|world, resources| {
+		let (delta, mut quadtree, mut state_action_event_channel, states) =
+			<(
+				Read<Duration>,
+				Write<Quadtree>,
+				Write<EventChannel<StateActionEvent>>,
+				Read<States>,
+			)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for (entity, mut state_machine) in
+			unsafe { <Write<StateMachine>>::query().iter_entities_unchecked(world) }
+		{
+			if state_machine.next.is_none() {
+				let time_left = match state_machine.time_left {
+					Some(x) => x.checked_sub(*delta).unwrap_or_default(),
+					None => continue,
+				};
+
+				if time_left > Duration::default() {
+					state_machine.time_left = Some(time_left);
+					continue;
+				}
+
+				state_machine.next = Some(states.states[state_machine.current].next);
+			}
+
+			// States with zero duration are passed through immediately
+			while let Some(next) = state_machine.next.take() {
+				// The null state removes the entity
+				if next == 0 {
+					quadtree.remove(entity);
+					command_buffer.delete(entity);
+					break;
+				}
+
+				let state = &states.states[next];
+				state_machine.current = next;
+				state_machine.time_left = state.time;
+
+				if let Some(mut sprite_render) =
+					unsafe { world.get_component_mut_unchecked::<SpriteRender>(entity) }
+				{
+					sprite_render.sprite = state.sprite.clone();
+					sprite_render.frame = state.frame;
+					sprite_render.full_bright = state.full_bright;
+				}
+
+				if let Some(action) = state.action {
+					state_action_event_channel.single_write(StateActionEvent { entity, action });
+				}
+
+				if state.time == Some(Duration::default()) {
+					state_machine.next = Some(state.next);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
@@ -0,0 +1,370 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		client::Client,
+		components::{Transform, Velocity},
+		damage::{DamageEvent, DamageType},
+		data::MobjTypes,
+		hitscan::is_shootable,
+		map::{LinedefRef, MapDynamic},
+		physics::{BoxCollider, CrossAction, CrossEvent},
+	},
+	geometry::{Side, AABB2, AABB3},
+	quadtree::Quadtree,
+};
+use legion::prelude::{
+	CommandBuffer, Entity, IntoQuery, Read, ResourceSet, Resources, World, Write,
+};
+use nalgebra::{Vector2, Vector3};
+use shrev::EventChannel;
+
+pub fn teleport_cross_system(
+	resources: &mut Resources,
+) -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut cross_event_reader = resources
+		.get_mut::<EventChannel<CrossEvent>>()
+		.unwrap()
+		.register_reader();
+
+	Box::new(move |world, resources| {
+		let (
+			asset_storage,
+			client,
+			cross_event_channel,
+			mut damage_event_channel,
+			entity_types,
+			mut quadtree,
+			mut sound_queue,
+		) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<EventChannel<CrossEvent>>,
+			Write<EventChannel<DamageEvent>>,
+			Read<MobjTypes>,
+			Write<Quadtree>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+		)>::fetch_mut(resources);
+
+		let mut command_buffer = CommandBuffer::new(world);
+
+		for cross_event in cross_event_channel.read(&mut cross_event_reader) {
+			if let Some(CrossAction::TeleportCross(teleport_cross)) = world
+				.get_component::<CrossAction>(cross_event.linedef_entity)
+				.as_deref()
+			{
+				// Teleporters only work when crossed from the front
+				if cross_event.side != Side::Right {
+					continue;
+				}
+
+				let is_player = client.entity == Some(cross_event.entity);
+
+				if teleport_cross.monsters_only && is_player {
+					continue;
+				}
+
+				let linedef_ref = world
+					.get_component::<LinedefRef>(cross_event.linedef_entity)
+					.unwrap();
+				let map_dynamic = world
+					.get_component::<MapDynamic>(linedef_ref.map_entity)
+					.unwrap();
+				let map = asset_storage.get(&map_dynamic.map).unwrap();
+				let linedef = &map.linedefs[linedef_ref.index];
+
+				// Find a destination in a sector with the same tag
+				let dest = <(Read<Transform>, Read<TeleportDest>)>::query()
+					.iter(world)
+					.find_map(|(transform, _)| {
+						let position = Vector2::new(transform.position[0], transform.position[1]);
+						let sector_index = map.find_subsector(position).sector_index;
+
+						if map.sectors[sector_index].sector_tag == linedef.sector_tag {
+							Some((*transform, sector_index))
+						} else {
+							None
+						}
+					});
+
+				let (dest_transform, dest_sector_index) = match dest {
+					Some(x) => x,
+					None => continue,
+				};
+
+				let box_collider = match world.get_component::<BoxCollider>(cross_event.entity) {
+					Some(x) => *x,
+					None => continue,
+				};
+
+				let dest_position = Vector3::new(
+					dest_transform.position[0],
+					dest_transform.position[1],
+					map_dynamic.sectors[dest_sector_index].interval.min,
+				);
+				let entity_bbox =
+					AABB3::from_radius_height(box_collider.radius, box_collider.height);
+				let dest_bbox = AABB2::from(&entity_bbox.offset(dest_position));
+
+				// Find everything that's in the way at the destination
+				let mut telefragged = Vec::new();
+				quadtree.traverse_nodes(&dest_bbox, &mut |entities: &[Entity]| {
+					for &entity in entities {
+						if entity == cross_event.entity || !is_shootable(world, entity) {
+							continue;
+						}
+
+						let other_bbox = {
+							let box_collider = world.get_component::<BoxCollider>(entity).unwrap();
+							let transform = world.get_component::<Transform>(entity).unwrap();
+							AABB2::from(
+								&AABB3::from_radius_height(
+									box_collider.radius,
+									box_collider.height,
+								)
+								.offset(transform.position),
+							)
+						};
+
+						if dest_bbox.overlaps(&other_bbox) {
+							telefragged.push(entity);
+						}
+					}
+				});
+
+				// Only players can telefrag, monsters get blocked instead
+				if !is_player && !telefragged.is_empty() {
+					continue;
+				}
+
+				for target in telefragged {
+					damage_event_channel.single_write(DamageEvent {
+						source: Some(cross_event.entity),
+						inflictor: Some(cross_event.entity),
+						target,
+						amount: 10000,
+						damage_type: DamageType::Telefrag,
+					});
+				}
+
+				// Move the entity
+				let old_position = {
+					let mut transform = unsafe {
+						world.get_component_mut_unchecked::<Transform>(cross_event.entity)
+					}
+					.unwrap();
+					let old_position = transform.position;
+					transform.position = dest_position;
+					transform.rotation[2] = dest_transform.rotation[2];
+					old_position
+				};
+
+				if let Some(mut velocity) =
+					unsafe { world.get_component_mut_unchecked::<Velocity>(cross_event.entity) }
+				{
+					velocity.velocity = Vector3::zeros();
+				}
+
+				quadtree.remove(cross_event.entity);
+				quadtree.insert(cross_event.entity, &dest_bbox);
+
+				// Spawn fog at the source and in front of the destination
+				let angle = dest_transform.rotation[2];
+				let fog_handle = entity_types.names.get("TFOG").unwrap();
+				let fog_template = asset_storage.get(fog_handle).unwrap();
+
+				for &position in &[
+					old_position,
+					dest_position
+						+ Vector3::new(angle.cos() as f32, angle.sin() as f32, 0.0) * 20.0,
+				] {
+					let fog_entity = command_buffer.insert((), vec![()])[0];
+					fog_template.add_to_entity(fog_entity, &mut command_buffer);
+					command_buffer.add_component(
+						fog_entity,
+						Transform {
+							position,
+							rotation: Vector3::zeros(),
+						},
+					);
+					sound_queue.push((teleport_cross.sound.clone(), fog_entity));
+				}
+
+				if !teleport_cross.retrigger {
+					command_buffer.remove_component::<CrossAction>(cross_event.linedef_entity);
+				}
+			}
+		}
+
+		command_buffer.write(world);
+	})
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TeleportDest;
+
+#[derive(Clone, Debug)]
+pub struct TeleportCross {
+	pub sound: AssetHandle<Sound>,
+	pub retrigger: bool,
+	pub monsters_only: bool,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		component::EntityTemplate,
+		doom::{
+			map::{
+				load::LinedefFlags,
+				test_map::{TestMap, TestSector},
+			},
+			physics::SolidMask,
+			wad::WadLoader,
+		},
+	};
+	use fnv::FnvHashMap;
+
+	fn collider() -> BoxCollider {
+		BoxCollider {
+			height: 56.0,
+			radius: 20.0,
+			solid_mask: SolidMask::all(),
+		}
+	}
+
+	#[test]
+	fn monster_only_teleport() {
+		let mut test_map = TestMap::corridor(&[
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+			TestSector::new(256.0, 0.0, 128.0),
+		]);
+		let handle = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.map
+			.clone();
+
+		// Make the linedef between the first two sectors a W1 monster teleporter into the last
+		let index = {
+			let map = test_map.asset_storage.get_mut(&handle).unwrap();
+			let index = map
+				.linedefs
+				.iter()
+				.position(|linedef| linedef.flags.contains(LinedefFlags::TWOSIDED))
+				.unwrap();
+			map.linedefs[index].special_type = 125;
+			map.linedefs[index].sector_tag = 1;
+			map.sectors[2].sector_tag = 1;
+			index
+		};
+		let linedef_entity = test_map
+			.world
+			.get_component::<MapDynamic>(test_map.map_entity)
+			.unwrap()
+			.linedefs[index]
+			.entity;
+		let sound = test_map
+			.asset_storage
+			.load::<Sound>("DSTELEPT", &mut WadLoader::new());
+		let mut command_buffer = CommandBuffer::new(&test_map.world);
+		command_buffer.add_component(
+			linedef_entity,
+			CrossAction::TeleportCross(TeleportCross {
+				sound,
+				retrigger: false,
+				monsters_only: true,
+			}),
+		);
+		command_buffer.write(&mut test_map.world);
+
+		let dest_position = Vector3::new(640.0, 0.0, 0.0);
+		test_map.world.insert(
+			(),
+			vec![(
+				Transform {
+					position: dest_position,
+					rotation: Vector3::zeros(),
+				},
+				TeleportDest,
+			)],
+		);
+
+		let player = test_map.spawn(Vector3::new(260.0, 64.0, 0.0), collider(), Some(100));
+		let monster = test_map.spawn(Vector3::new(260.0, -64.0, 0.0), collider(), Some(20));
+
+		// A corpse lying on the destination doesn't get in the way
+		test_map.spawn(dest_position, collider(), Some(0));
+
+		let TestMap {
+			mut asset_storage,
+			quadtree,
+			mut world,
+			..
+		} = test_map;
+		let mut names = FnvHashMap::default();
+		names.insert(
+			"TFOG".to_owned(),
+			asset_storage.insert(EntityTemplate::new()),
+		);
+
+		let mut resources = Resources::default();
+		resources.insert(asset_storage);
+		resources.insert(Client {
+			entity: Some(player),
+			..Client::default()
+		});
+		resources.insert(EventChannel::<CrossEvent>::new());
+		resources.insert(EventChannel::<DamageEvent>::new());
+		resources.insert(MobjTypes {
+			names,
+			doomednums: FnvHashMap::default(),
+			translations: FnvHashMap::default(),
+			sounds: FnvHashMap::default(),
+		});
+		resources.insert(quadtree);
+		resources.insert(Vec::<(AssetHandle<Sound>, Entity)>::new());
+
+		let mut damage_event_reader = resources
+			.get_mut::<EventChannel<DamageEvent>>()
+			.unwrap()
+			.register_reader();
+		let mut system = teleport_cross_system(&mut resources);
+		let cross = |resources: &mut Resources, entity: Entity| {
+			resources
+				.get_mut::<EventChannel<CrossEvent>>()
+				.unwrap()
+				.single_write(CrossEvent {
+					entity,
+					linedef_entity,
+					side: Side::Right,
+				});
+		};
+		let position = |world: &World, entity: Entity| {
+			world.get_component::<Transform>(entity).unwrap().position
+		};
+
+		// The player can't use it
+		cross(&mut resources, player);
+		system(&mut world, &mut resources);
+		assert_eq!(position(&world, player), Vector3::new(260.0, 64.0, 0.0));
+		assert!(world.has_component::<CrossAction>(linedef_entity));
+
+		// The monster can, and only once
+		cross(&mut resources, monster);
+		system(&mut world, &mut resources);
+		assert_eq!(position(&world, monster), dest_position);
+		assert!(!world.has_component::<CrossAction>(linedef_entity));
+		assert_eq!(
+			resources
+				.get::<EventChannel<DamageEvent>>()
+				.unwrap()
+				.read(&mut damage_event_reader)
+				.count(),
+			0
+		);
+	}
+}
@@ -0,0 +1,1242 @@
+// Serializable definitions of entity templates, loaded from JSON lumps.
+// Mods can use these to add new entity types or replace existing ones, and the built-in types
+// in doom/data are defined the same way.
+// Assets are referred to by name, times are given in tics and speeds in map units per tic,
+// the same as in vanilla.
+use crate::{
+	assets::{Asset, AssetHandle, AssetStorage, DataSource},
+	audio::Sound,
+	component::EntityTemplate,
+	doom::{
+		ceiling::{CeilingCross, CeilingParams, CeilingStopCross, CeilingSwitchUse, CeilingType},
+		client::{ShootAction, UseAction},
+		components::{Armor, Camera, Health, SpawnOnCeiling, SpawnPoint, Velocity},
+		data::{States, FRAME_RATE, FRAME_TIME},
+		door::{DoorCross, DoorLock, DoorParams, DoorShoot, DoorState, DoorSwitchUse, DoorUse},
+		floor::{
+			DonutSwitchUse, FloorCross, FloorParams, FloorShoot, FloorSwitchUse, FloorTarget,
+			StairsCross, StairsParams, StairsSwitchUse,
+		},
+		game::{ExitCross, ExitSwitchUse, ExitType},
+		image::Translation,
+		inventory::{Inventory, Item, ItemPickup, Keys, Power, Powers},
+		light::{LightChange, LightCross, LightFlash, LightFlashType, LightGlow, LightSwitchUse},
+		monster::Monster,
+		palette::PaletteFlash,
+		physics::{BoxCollider, CrossAction, SolidMask},
+		plat::{PlatCross, PlatParams, PlatShoot, PlatStopCross, PlatSwitchUse, PlatType},
+		projectile::Projectile,
+		render::sprite::{RenderStyle, SpriteRender},
+		sector::{SectorDamage, SectorSecret},
+		state::StateMachine,
+		teleport::{TeleportCross, TeleportDest},
+		update::TextureScroll,
+		weapon::{AmmoType, WeaponState, WeaponType},
+	},
+};
+use anyhow::anyhow;
+use fnv::FnvHashMap;
+use nalgebra::{Vector2, Vector3};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{fmt::Display, hash::Hash, time::Duration};
+
+impl Asset for EntityTemplate {
+	type Data = Self;
+	type Intermediate = EntityTemplateDef;
+	const NAME: &'static str = "EntityTemplate";
+
+	fn import(name: &str, source: &impl DataSource) -> anyhow::Result<Self::Intermediate> {
+		Ok(serde_json::from_slice(&source.load(name)?)?)
+	}
+}
+
+// Lists the templates to add or replace, by the names of the lumps containing them
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeIndex {
+	pub names: FnvHashMap<String, String>,
+	pub doomednums: FnvHashMap<u16, String>,
+}
+
+// Loads a JSON index lump, if one exists
+pub fn load_index<T: DeserializeOwned + Default>(name: &str, source: &impl DataSource) -> T {
+	if !source.names().any(|n| n == name) {
+		return T::default();
+	}
+
+	match source
+		.load(name)
+		.and_then(|data| Ok(serde_json::from_slice(&data)?))
+	{
+		Ok(index) => index,
+		Err(e) => {
+			log::error!("{} could not be loaded: {}", name, e);
+			T::default()
+		}
+	}
+}
+
+// Loads and builds a template, returning None if it failed
+pub fn load_template(
+	name: &str,
+	asset_storage: &mut AssetStorage,
+	source: &mut impl DataSource,
+	states: &States,
+	translations: &FnvHashMap<&'static str, AssetHandle<Translation>>,
+) -> Option<AssetHandle<EntityTemplate>> {
+	let handle = asset_storage.load(name, source);
+	asset_storage.build_waiting::<EntityTemplate, _>(|def, asset_storage| {
+		def.build(asset_storage, source, states, translations)
+	});
+	asset_storage.get(&handle).map(|_| handle)
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "PascalCase")]
+pub struct EntityTemplateDef {
+	pub armor: Option<Armor>,
+	pub box_collider: Option<BoxColliderDef>,
+	pub camera: Option<CameraDef>,
+	pub cross_action: Option<CrossActionDef>,
+	pub health: Option<Health>,
+	pub inventory: Option<InventoryDef>,
+	pub item_pickup: Option<ItemPickupDef>,
+	pub light_flash: Option<LightFlashDef>,
+	pub light_glow: Option<LightGlowDef>,
+	pub monster: Option<MonsterDef>,
+	pub palette_flash: Option<MarkerDef>,
+	pub powers: Option<MarkerDef>,
+	pub projectile: Option<ProjectileDef>,
+	pub sector_damage: Option<SectorDamage>,
+	pub sector_secret: Option<MarkerDef>,
+	pub shoot_action: Option<ShootActionDef>,
+	pub spawn_on_ceiling: Option<SpawnOnCeiling>,
+	pub spawn_point: Option<SpawnPoint>,
+	pub sprite_render: Option<SpriteRenderDef>,
+	pub state_machine: Option<StateMachineDef>,
+	pub teleport_dest: Option<MarkerDef>,
+	pub texture_scroll: Option<TextureScrollDef>,
+	pub use_action: Option<UseActionDef>,
+	pub velocity: Option<MarkerDef>,
+	pub weapon_state: Option<WeaponStateDef>,
+}
+
+impl EntityTemplateDef {
+	pub fn build(
+		self,
+		asset_storage: &mut AssetStorage,
+		source: &mut impl DataSource,
+		states: &States,
+		translations: &FnvHashMap<&'static str, AssetHandle<Translation>>,
+	) -> anyhow::Result<EntityTemplate> {
+		let mut template = EntityTemplate::new();
+		let mut sound = |name: &str| -> AssetHandle<Sound> { asset_storage.load(name, source) };
+
+		if let Some(def) = self.cross_action {
+			template.add_component(def.build(&mut sound)?);
+		}
+
+		if let Some(def) = self.item_pickup {
+			template.add_component(ItemPickup {
+				item: def.item.build()?,
+				sound: sound(&def.sound),
+				message: def.message,
+				count_item: def.count_item,
+			});
+		}
+
+		if let Some(def) = self.monster {
+			template.add_component(Monster {
+				speed: def.speed,
+				reaction_time: def.reaction_time,
+				target: None,
+				move_dir: None,
+				move_count: 0,
+				just_attacked: false,
+				just_hit: false,
+				ambush: false,
+				charge: None,
+				min_missile_range: def.min_missile_range,
+				max_missile_range: def.max_missile_range,
+				missile_distance_scale: def.missile_distance_scale,
+				missile_distance_cap: def.missile_distance_cap,
+				see_sound: def.see_sound.map(|name| sound(&name)),
+				attack_sound: def.attack_sound.map(|name| sound(&name)),
+				pain_sound: def.pain_sound.map(|name| sound(&name)),
+				death_sound: def.death_sound.map(|name| sound(&name)),
+				active_sound: def.active_sound.map(|name| sound(&name)),
+			});
+		}
+
+		if let Some(def) = self.projectile {
+			template.add_component(Projectile {
+				speed: def.speed * FRAME_RATE,
+				damage: def.damage,
+				owner: None,
+				spawn_sound: def.spawn_sound.map(|name| sound(&name)),
+				death_sound: def.death_sound.map(|name| sound(&name)),
+			});
+		}
+
+		if let Some(def) = self.shoot_action {
+			template.add_component(def.build(&mut sound)?);
+		}
+
+		if let Some(def) = self.use_action {
+			template.add_component(def.build(&mut sound)?);
+		}
+
+		if let Some(def) = self.sprite_render {
+			template.add_component(SpriteRender {
+				sprite: asset_storage.load(&def.sprite, source),
+				frame: def.frame,
+				full_bright: def.full_bright,
+				translation: def
+					.translation
+					.map(|name| {
+						translations
+							.get(name.as_str())
+							.cloned()
+							.ok_or_else(|| anyhow!("Unknown translation \"{}\"", name))
+					})
+					.transpose()?,
+				render_style: def.render_style,
+			});
+		}
+
+		if let Some(armor) = self.armor {
+			template.add_component(armor);
+		}
+
+		if let Some(def) = self.box_collider {
+			template.add_component(BoxCollider {
+				height: def.height,
+				radius: def.radius,
+				solid_mask: solid_mask(&def.solid_mask)?,
+			});
+		}
+
+		if let Some(def) = self.camera {
+			template.add_component(Camera {
+				base: Vector3::new(def.base[0], def.base[1], def.base[2]),
+				..Camera::default()
+			});
+		}
+
+		if let Some(health) = self.health {
+			template.add_component(health);
+		}
+
+		if let Some(def) = self.inventory {
+			let mut inventory = Inventory::default();
+
+			for weapon in def.weapons {
+				inventory.weapons[weapon as usize] = true;
+			}
+
+			for (ammo_type, amount) in def.ammo {
+				inventory.ammo[ammo_type as usize] = amount;
+			}
+
+			template.add_component(inventory);
+		}
+
+		if let Some(def) = self.light_flash {
+			template.add_component(def.build());
+		}
+
+		if let Some(def) = self.light_glow {
+			template.add_component(LightGlow {
+				speed: def.speed * FRAME_RATE,
+				..LightGlow::default()
+			});
+		}
+
+		if self.palette_flash.is_some() {
+			template.add_component(PaletteFlash::default());
+		}
+
+		if self.powers.is_some() {
+			template.add_component(Powers::default());
+		}
+
+		if let Some(sector_damage) = self.sector_damage {
+			template.add_component(sector_damage);
+		}
+
+		if self.sector_secret.is_some() {
+			template.add_component(SectorSecret);
+		}
+
+		if let Some(spawn_on_ceiling) = self.spawn_on_ceiling {
+			template.add_component(spawn_on_ceiling);
+		}
+
+		if let Some(spawn_point) = self.spawn_point {
+			template.add_component(spawn_point);
+		}
+
+		if let Some(def) = self.state_machine {
+			template.add_component(def.build(states)?);
+		}
+
+		if self.teleport_dest.is_some() {
+			template.add_component(TeleportDest);
+		}
+
+		if let Some(def) = self.texture_scroll {
+			template.add_component(TextureScroll {
+				speed: Vector2::new(def.speed[0], def.speed[1]) * FRAME_RATE,
+			});
+		}
+
+		if self.velocity.is_some() {
+			template.add_component(Velocity::default());
+		}
+
+		if let Some(def) = self.weapon_state {
+			template.add_component(WeaponState::new(def.current));
+		}
+
+		Ok(template)
+	}
+}
+
+// Builds a table of templates bundled with the engine, skipping any that fail
+pub fn build_templates<K: Eq + Hash + Display>(
+	defs: FnvHashMap<K, EntityTemplateDef>,
+	asset_storage: &mut AssetStorage,
+	source: &mut impl DataSource,
+	states: &States,
+	translations: &FnvHashMap<&'static str, AssetHandle<Translation>>,
+) -> FnvHashMap<K, AssetHandle<EntityTemplate>> {
+	defs.into_iter()
+		.filter_map(
+			|(key, def)| match def.build(asset_storage, source, states, translations) {
+				Ok(template) => Some((key, asset_storage.insert(template))),
+				Err(e) => {
+					log::error!("Template {} could not be built: {}", key, e);
+					None
+				}
+			},
+		)
+		.collect()
+}
+
+// For components that have no parameters, given as {}
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarkerDef {}
+
+fn tics(tics: u32) -> Duration {
+	tics * FRAME_TIME
+}
+
+fn keys(names: &[String]) -> anyhow::Result<Keys> {
+	names.iter().try_fold(Keys::empty(), |keys, name| {
+		Ok(keys
+			| match name.as_str() {
+				"BLUECARD" => Keys::BLUECARD,
+				"YELLOWCARD" => Keys::YELLOWCARD,
+				"REDCARD" => Keys::REDCARD,
+				"BLUESKULL" => Keys::BLUESKULL,
+				"YELLOWSKULL" => Keys::YELLOWSKULL,
+				"REDSKULL" => Keys::REDSKULL,
+				"BLUE" => Keys::BLUE,
+				"YELLOW" => Keys::YELLOW,
+				"RED" => Keys::RED,
+				_ => return Err(anyhow!("Unknown key \"{}\"", name)),
+			})
+	})
+}
+
+fn solid_mask(names: &[String]) -> anyhow::Result<SolidMask> {
+	names
+		.iter()
+		.try_fold(SolidMask::empty(), |solid_mask, name| {
+			Ok(solid_mask
+				| match name.as_str() {
+					"NON_MONSTER" => SolidMask::NON_MONSTER,
+					"MONSTER" => SolidMask::MONSTER,
+					_ => return Err(anyhow!("Unknown solid mask flag \"{}\"", name)),
+				})
+		})
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoxColliderDef {
+	pub height: f32,
+	pub radius: f32,
+	pub solid_mask: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDef {
+	pub base: [f32; 3],
+}
+
+// Weapons and ammo to start with
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InventoryDef {
+	pub weapons: Vec<WeaponType>,
+	pub ammo: Vec<(AmmoType, i32)>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemPickupDef {
+	pub item: ItemDef,
+	pub sound: String,
+	pub message: String,
+	#[serde(default)]
+	pub count_item: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum ItemDef {
+	Health {
+		amount: i32,
+		max: i32,
+		#[serde(default)]
+		always: bool,
+	},
+	Armor {
+		class: u8,
+		amount: i32,
+	},
+	ArmorBonus {
+		amount: i32,
+		max: i32,
+	},
+	Megasphere,
+	Ammo {
+		ammo_type: AmmoType,
+		clips: i32,
+	},
+	Weapon {
+		weapon: WeaponType,
+		clips: i32,
+	},
+	Backpack,
+	Key(Vec<String>),
+	Power(Power),
+}
+
+impl ItemDef {
+	fn build(self) -> anyhow::Result<Item> {
+		Ok(match self {
+			ItemDef::Health {
+				amount,
+				max,
+				always,
+			} => Item::Health {
+				amount,
+				max,
+				always,
+			},
+			ItemDef::Armor { class, amount } => Item::Armor { class, amount },
+			ItemDef::ArmorBonus { amount, max } => Item::ArmorBonus { amount, max },
+			ItemDef::Megasphere => Item::Megasphere,
+			ItemDef::Ammo { ammo_type, clips } => Item::Ammo { ammo_type, clips },
+			ItemDef::Weapon { weapon, clips } => Item::Weapon { weapon, clips },
+			ItemDef::Backpack => Item::Backpack,
+			ItemDef::Key(names) => Item::Key(keys(&names)?),
+			ItemDef::Power(power) => Item::Power(power),
+		})
+	}
+}
+
+// Monster speeds are in map units per chase step, not per tic
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonsterDef {
+	pub speed: f32,
+	#[serde(default = "default_reaction_time")]
+	pub reaction_time: u32,
+	#[serde(default)]
+	pub min_missile_range: f32,
+	pub max_missile_range: Option<f32>,
+	#[serde(default = "default_missile_distance_scale")]
+	pub missile_distance_scale: f32,
+	#[serde(default = "default_missile_distance_cap")]
+	pub missile_distance_cap: f32,
+	pub see_sound: Option<String>,
+	pub attack_sound: Option<String>,
+	pub pain_sound: Option<String>,
+	pub death_sound: Option<String>,
+	pub active_sound: Option<String>,
+}
+
+fn default_reaction_time() -> u32 {
+	8
+}
+
+fn default_missile_distance_scale() -> f32 {
+	1.0
+}
+
+fn default_missile_distance_cap() -> f32 {
+	200.0
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectileDef {
+	pub speed: f32,
+	pub damage: i32,
+	pub spawn_sound: Option<String>,
+	pub death_sound: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpriteRenderDef {
+	pub sprite: String,
+	#[serde(default)]
+	pub frame: usize,
+	#[serde(default)]
+	pub full_bright: bool,
+	#[serde(default = "default_render_style")]
+	pub render_style: RenderStyle,
+	// One of the player colours, GRAY, BROWN or RED
+	pub translation: Option<String>,
+}
+
+fn default_render_style() -> RenderStyle {
+	RenderStyle::Normal
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateMachineDef {
+	pub spawn: String,
+	pub see: Option<String>,
+	pub pain: Option<String>,
+	pub melee: Option<String>,
+	pub missile: Option<String>,
+	pub death: Option<String>,
+	pub xdeath: Option<String>,
+	pub raise: Option<String>,
+}
+
+impl StateMachineDef {
+	fn build(self, states: &States) -> anyhow::Result<StateMachine> {
+		let state = |name: &str| {
+			states
+				.names
+				.get(name)
+				.copied()
+				.ok_or_else(|| anyhow!("Unknown state \"{}\"", name))
+		};
+		let optional = |name: Option<String>| name.map(|name| state(&name)).transpose();
+
+		Ok(StateMachine {
+			see: optional(self.see)?,
+			pain: optional(self.pain)?,
+			melee: optional(self.melee)?,
+			missile: optional(self.missile)?,
+			death: optional(self.death)?,
+			xdeath: optional(self.xdeath)?,
+			raise: optional(self.raise)?,
+			..StateMachine::new(states, state(&self.spawn)?)
+		})
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextureScrollDef {
+	pub speed: [f32; 2],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightGlowDef {
+	pub speed: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightFlashDef {
+	pub on_time: u32,
+	pub off_time: u32,
+	pub flash_type: LightFlashTypeDef,
+}
+
+impl LightFlashDef {
+	fn build(self) -> LightFlash {
+		LightFlash {
+			on_time: tics(self.on_time),
+			off_time: tics(self.off_time),
+			flash_type: match self.flash_type {
+				LightFlashTypeDef::Broken => LightFlashType::Broken,
+				LightFlashTypeDef::Strobe => LightFlashType::Strobe,
+				LightFlashTypeDef::StrobeUnSync(time) => LightFlashType::StrobeUnSync(tics(time)),
+			},
+			..LightFlash::default()
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum LightFlashTypeDef {
+	Broken,
+	Strobe,
+	StrobeUnSync(u32),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum LightChangeDef {
+	Set(f32),
+	BrightestNeighbour,
+	DarkestNeighbour,
+	Flash(LightFlashDef),
+}
+
+impl LightChangeDef {
+	fn build(self) -> LightChange {
+		match self {
+			LightChangeDef::Set(light_level) => LightChange::Set(light_level),
+			LightChangeDef::BrightestNeighbour => LightChange::BrightestNeighbour,
+			LightChangeDef::DarkestNeighbour => LightChange::DarkestNeighbour,
+			LightChangeDef::Flash(def) => LightChange::Flash(def.build()),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponStateDef {
+	pub current: WeaponType,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CeilingParamsDef {
+	pub ceiling_type: CeilingType,
+	pub speed: f32,
+	pub move_sound: Option<String>,
+	pub stop_sound: Option<String>,
+}
+
+impl CeilingParamsDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> CeilingParams {
+		CeilingParams {
+			ceiling_type: self.ceiling_type,
+			speed: self.speed * FRAME_RATE,
+			move_sound: self.move_sound.map(|name| sound(&name)),
+			stop_sound: self.stop_sound.map(|name| sound(&name)),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DoorParamsDef {
+	pub start_state: DoorState,
+	pub end_state: DoorState,
+	pub speed: f32,
+	pub wait_time: u32,
+	pub open_sound: String,
+	pub close_sound: String,
+}
+
+impl DoorParamsDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> DoorParams {
+		DoorParams {
+			start_state: self.start_state,
+			end_state: self.end_state,
+			speed: self.speed * FRAME_RATE,
+			wait_time: tics(self.wait_time),
+			open_sound: sound(&self.open_sound),
+			close_sound: sound(&self.close_sound),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DoorLockDef {
+	pub keys: Vec<String>,
+	pub message: String,
+	pub sound: String,
+}
+
+impl DoorLockDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> anyhow::Result<DoorLock> {
+		Ok(DoorLock {
+			keys: keys(&self.keys)?,
+			message: self.message,
+			sound: sound(&self.sound),
+		})
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FloorParamsDef {
+	pub target: FloorTarget,
+	pub speed: f32,
+	pub crush: bool,
+	pub change_texture: bool,
+	pub move_sound: String,
+	pub stop_sound: String,
+}
+
+impl FloorParamsDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> FloorParams {
+		FloorParams {
+			target: self.target,
+			speed: self.speed * FRAME_RATE,
+			crush: self.crush,
+			change_texture: self.change_texture,
+			move_sound: sound(&self.move_sound),
+			stop_sound: sound(&self.stop_sound),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlatParamsDef {
+	pub plat_type: PlatType,
+	pub speed: f32,
+	pub wait_time: u32,
+	pub start_sound: String,
+	pub move_sound: Option<String>,
+	pub stop_sound: String,
+}
+
+impl PlatParamsDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> PlatParams {
+		PlatParams {
+			plat_type: self.plat_type,
+			speed: self.speed * FRAME_RATE,
+			wait_time: tics(self.wait_time),
+			start_sound: sound(&self.start_sound),
+			move_sound: self.move_sound.map(|name| sound(&name)),
+			stop_sound: sound(&self.stop_sound),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StairsParamsDef {
+	pub step_height: f32,
+	pub speed: f32,
+	pub crush: bool,
+	pub move_sound: String,
+	pub stop_sound: String,
+}
+
+impl StairsParamsDef {
+	fn build(self, sound: &mut impl FnMut(&str) -> AssetHandle<Sound>) -> StairsParams {
+		StairsParams {
+			step_height: self.step_height,
+			speed: self.speed * FRAME_RATE,
+			crush: self.crush,
+			move_sound: sound(&self.move_sound),
+			stop_sound: sound(&self.stop_sound),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum UseActionDef {
+	CeilingSwitchUse {
+		params: CeilingParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	DonutSwitchUse {
+		speed: f32,
+		move_sound: String,
+		stop_sound: String,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	DoorUse {
+		params: DoorParamsDef,
+		lock: Option<DoorLockDef>,
+		retrigger: bool,
+	},
+	DoorSwitchUse {
+		params: DoorParamsDef,
+		lock: Option<DoorLockDef>,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	ExitSwitchUse {
+		exit_type: ExitType,
+		switch_sound: String,
+	},
+	FloorSwitchUse {
+		params: FloorParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	LightSwitchUse {
+		change: LightChangeDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	PlatSwitchUse {
+		params: PlatParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+	StairsSwitchUse {
+		params: StairsParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+	},
+}
+
+impl UseActionDef {
+	fn build(
+		self,
+		sound: &mut impl FnMut(&str) -> AssetHandle<Sound>,
+	) -> anyhow::Result<UseAction> {
+		Ok(match self {
+			UseActionDef::CeilingSwitchUse {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::CeilingSwitchUse(CeilingSwitchUse {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::DonutSwitchUse {
+				speed,
+				move_sound,
+				stop_sound,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::DonutSwitchUse(DonutSwitchUse {
+				speed: speed * FRAME_RATE,
+				move_sound: sound(&move_sound),
+				stop_sound: sound(&stop_sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::DoorUse {
+				params,
+				lock,
+				retrigger,
+			} => UseAction::DoorUse(DoorUse {
+				params: params.build(sound),
+				lock: lock.map(|lock| lock.build(sound)).transpose()?,
+				retrigger,
+			}),
+			UseActionDef::DoorSwitchUse {
+				params,
+				lock,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::DoorSwitchUse(DoorSwitchUse {
+				params: params.build(sound),
+				lock: lock.map(|lock| lock.build(sound)).transpose()?,
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::ExitSwitchUse {
+				exit_type,
+				switch_sound,
+			} => UseAction::ExitSwitchUse(ExitSwitchUse {
+				exit_type,
+				switch_sound: sound(&switch_sound),
+			}),
+			UseActionDef::FloorSwitchUse {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::FloorSwitchUse(FloorSwitchUse {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::LightSwitchUse {
+				change,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::LightSwitchUse(LightSwitchUse {
+				change: change.build(),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::PlatSwitchUse {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::PlatSwitchUse(PlatSwitchUse {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+			UseActionDef::StairsSwitchUse {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+			} => UseAction::StairsSwitchUse(StairsSwitchUse {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+			}),
+		})
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum CrossActionDef {
+	CeilingCross {
+		params: CeilingParamsDef,
+		floor: Option<FloorParamsDef>,
+		retrigger: bool,
+		monsters: bool,
+	},
+	CeilingStopCross {
+		retrigger: bool,
+	},
+	DoorCross {
+		params: DoorParamsDef,
+		retrigger: bool,
+		monsters: bool,
+	},
+	ExitCross {
+		exit_type: ExitType,
+	},
+	FloorCross {
+		params: FloorParamsDef,
+		retrigger: bool,
+		monsters: bool,
+	},
+	LightCross {
+		change: LightChangeDef,
+		retrigger: bool,
+	},
+	PlatCross {
+		params: PlatParamsDef,
+		retrigger: bool,
+		monsters: bool,
+	},
+	PlatStopCross {
+		retrigger: bool,
+	},
+	StairsCross {
+		params: StairsParamsDef,
+		retrigger: bool,
+	},
+	TeleportCross {
+		sound: String,
+		retrigger: bool,
+		monsters_only: bool,
+	},
+}
+
+impl CrossActionDef {
+	fn build(
+		self,
+		sound: &mut impl FnMut(&str) -> AssetHandle<Sound>,
+	) -> anyhow::Result<CrossAction> {
+		Ok(match self {
+			CrossActionDef::CeilingCross {
+				params,
+				floor,
+				retrigger,
+				monsters,
+			} => CrossAction::CeilingCross(CeilingCross {
+				params: params.build(sound),
+				floor: floor.map(|params| params.build(sound)),
+				retrigger,
+				monsters,
+			}),
+			CrossActionDef::CeilingStopCross { retrigger } => {
+				CrossAction::CeilingStopCross(CeilingStopCross { retrigger })
+			}
+			CrossActionDef::DoorCross {
+				params,
+				retrigger,
+				monsters,
+			} => CrossAction::DoorCross(DoorCross {
+				params: params.build(sound),
+				retrigger,
+				monsters,
+			}),
+			CrossActionDef::ExitCross { exit_type } => {
+				CrossAction::ExitCross(ExitCross { exit_type })
+			}
+			CrossActionDef::FloorCross {
+				params,
+				retrigger,
+				monsters,
+			} => CrossAction::FloorCross(FloorCross {
+				params: params.build(sound),
+				retrigger,
+				monsters,
+			}),
+			CrossActionDef::LightCross { change, retrigger } => {
+				CrossAction::LightCross(LightCross {
+					change: change.build(),
+					retrigger,
+				})
+			}
+			CrossActionDef::PlatCross {
+				params,
+				retrigger,
+				monsters,
+			} => CrossAction::PlatCross(PlatCross {
+				params: params.build(sound),
+				retrigger,
+				monsters,
+			}),
+			CrossActionDef::PlatStopCross { retrigger } => {
+				CrossAction::PlatStopCross(PlatStopCross { retrigger })
+			}
+			CrossActionDef::StairsCross { params, retrigger } => {
+				CrossAction::StairsCross(StairsCross {
+					params: params.build(sound),
+					retrigger,
+				})
+			}
+			CrossActionDef::TeleportCross {
+				sound: teleport_sound,
+				retrigger,
+				monsters_only,
+			} => CrossAction::TeleportCross(TeleportCross {
+				sound: sound(&teleport_sound),
+				retrigger,
+				monsters_only,
+			}),
+		})
+	}
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum ShootActionDef {
+	DoorShoot {
+		params: DoorParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+		monsters: bool,
+	},
+	FloorShoot {
+		params: FloorParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+		monsters: bool,
+	},
+	PlatShoot {
+		params: PlatParamsDef,
+		switch_sound: String,
+		switch_time: u32,
+		retrigger: bool,
+		monsters: bool,
+	},
+}
+
+impl ShootActionDef {
+	fn build(
+		self,
+		sound: &mut impl FnMut(&str) -> AssetHandle<Sound>,
+	) -> anyhow::Result<ShootAction> {
+		Ok(match self {
+			ShootActionDef::DoorShoot {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+				monsters,
+			} => ShootAction::DoorShoot(DoorShoot {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+				monsters,
+			}),
+			ShootActionDef::FloorShoot {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+				monsters,
+			} => ShootAction::FloorShoot(FloorShoot {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+				monsters,
+			}),
+			ShootActionDef::PlatShoot {
+				params,
+				switch_sound,
+				switch_time,
+				retrigger,
+				monsters,
+			} => ShootAction::PlatShoot(PlatShoot {
+				params: params.build(sound),
+				switch_sound: sound(&switch_sound),
+				switch_time: tics(switch_time),
+				retrigger,
+				monsters,
+			}),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn deserialize_template() {
+		let def: EntityTemplateDef = serde_json::from_str(
+			r#"{
+				"BoxCollider": {"height": 56.0, "radius": 20.0, "solid_mask": ["NON_MONSTER", "MONSTER"]},
+				"Health": {"current": 60, "max": 60, "pain_chance": 200},
+				"Inventory": {"weapons": ["Fist"], "ammo": [["Shell", 8]]},
+				"Monster": {"speed": 8.0, "see_sound": "DSBGSIT1"},
+				"SpriteRender": {"sprite": "TROO", "translation": "RED"},
+				"StateMachine": {"spawn": "TROO_STND", "see": "TROO_RUN1"},
+				"UseAction": {
+					"DoorUse": {
+						"params": {
+							"start_state": "Closed",
+							"end_state": "Closed",
+							"speed": 2.0,
+							"wait_time": 150,
+							"open_sound": "DSDOROPN",
+							"close_sound": "DSDORCLS"
+						},
+						"lock": {"keys": ["BLUECARD", "BLUESKULL"], "message": "Locked", "sound": "DSOOF"},
+						"retrigger": true
+					}
+				},
+				"Velocity": {}
+			}"#,
+		)
+		.unwrap();
+
+		let box_collider = def.box_collider.unwrap();
+		assert_eq!(
+			solid_mask(&box_collider.solid_mask).unwrap(),
+			SolidMask::all()
+		);
+
+		let health = def.health.unwrap();
+		assert_eq!(
+			(health.current, health.pain_chance, health.no_blood),
+			(60, 200, false)
+		);
+
+		let inventory = def.inventory.unwrap();
+		assert_eq!(inventory.weapons, vec![WeaponType::Fist]);
+		assert_eq!(inventory.ammo, vec![(AmmoType::Shell, 8)]);
+
+		let monster = def.monster.unwrap();
+		assert_eq!(monster.reaction_time, 8);
+		assert_eq!(monster.see_sound.as_deref(), Some("DSBGSIT1"));
+		assert!(monster.death_sound.is_none());
+
+		let sprite_render = def.sprite_render.unwrap();
+		assert_eq!(sprite_render.frame, 0);
+		assert_eq!(sprite_render.render_style, RenderStyle::Normal);
+		assert_eq!(sprite_render.translation.as_deref(), Some("RED"));
+
+		let state_machine = def.state_machine.unwrap();
+		assert_eq!(state_machine.see.as_deref(), Some("TROO_RUN1"));
+		assert!(state_machine.death.is_none());
+
+		match def.use_action.unwrap() {
+			UseActionDef::DoorUse { params, lock, .. } => {
+				assert_eq!(params.wait_time, 150);
+				assert_eq!(keys(&lock.unwrap().keys).unwrap(), Keys::BLUE);
+			}
+			_ => panic!("wrong use action"),
+		}
+
+		assert!(def.velocity.is_some());
+		assert!(def.armor.is_none());
+
+		assert!(
+			serde_json::from_str::<EntityTemplateDef>(r#"{"Health": {"current": 1}}"#).is_err()
+		);
+		assert!(serde_json::from_str::<EntityTemplateDef>(r#"{"Unknown": {}}"#).is_err());
+	}
+
+	#[test]
+	fn deserialize_index() {
+		let index: TypeIndex = serde_json::from_str(
+			r#"{
+				"names": {"TROOP": "NEWTROOP", "NEWMONSTER": "NEWMONST"},
+				"doomednums": {"3001": "NEWTROOP", "9000": "NEWMONST"}
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(index.names["TROOP"], "NEWTROOP");
+		assert_eq!(index.names["NEWMONSTER"], "NEWMONST");
+		assert_eq!(index.doomednums[&3001], "NEWTROOP");
+		assert_eq!(index.doomednums[&9000], "NEWMONST");
+
+		let index: TypeIndex = serde_json::from_str(r#"{"doomednums": {"1": "DOOR"}}"#).unwrap();
+		assert!(index.names.is_empty());
+		assert_eq!(index.doomednums[&1], "DOOR");
+
+		assert!(serde_json::from_str::<TypeIndex>(r#"{"doomednums": {"DOOR": "DOOR"}}"#).is_err());
+		assert!(serde_json::from_str::<TypeIndex>(r#"{"types": {}}"#).is_err());
+	}
+
+	#[test]
+	fn deserialize_bundled() {
+		let mobjs: serde_json::Value =
+			serde_json::from_str(include_str!("data/mobjs.json")).unwrap();
+		let templates: FnvHashMap<String, EntityTemplateDef> =
+			serde_json::from_value(mobjs["templates"].clone()).unwrap();
+		let doomednums: FnvHashMap<u16, String> =
+			serde_json::from_value(mobjs["doomednums"].clone()).unwrap();
+		assert!(doomednums.values().all(|name| templates.contains_key(name)));
+
+		let player = &templates["PLAYER"];
+		assert!(player.inventory.is_some() && player.weapon_state.is_some());
+
+		for &source in [
+			include_str!("data/linedefs.json"),
+			include_str!("data/sectors.json"),
+		]
+		.iter()
+		{
+			serde_json::from_str::<FnvHashMap<u16, EntityTemplateDef>>(source).unwrap();
+		}
+	}
+}
@@ -3,7 +3,7 @@ use anyhow::{anyhow, ensure};
 use byteorder::{ReadBytesExt, LE};
 use std::{
 	collections::HashSet,
-	fs::File,
+	fs::{self, File},
 	io::{BufReader, Read, Seek, SeekFrom},
 	path::{Path, PathBuf},
 	str,
@@ -36,6 +36,11 @@ impl WadLoader {
 
 	pub fn add<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
 		let path = path.as_ref();
+
+		if path.is_dir() {
+			return self.add_dir(path);
+		}
+
 		let file = File::open(path)?;
 		let mut reader = BufReader::new(file);
 
@@ -55,6 +60,7 @@ impl WadLoader {
 
 		// Read lump directory
 		reader.seek(SeekFrom::Start(dir_offset))?;
+		let mut in_hires = false;
 
 		for _ in 0..dir_length {
 			let offset = reader.read_u32::<LE>()? as u64;
@@ -65,6 +71,14 @@ impl WadLoader {
 			let mut name = String::from(str::from_utf8(&lump_name)?.trim_end_matches('\0'));
 			name.make_ascii_uppercase();
 
+			// Lumps between HI_START and HI_END go in the hires namespace
+			match name.as_str() {
+				"HI_START" => in_hires = true,
+				"HI_END" => in_hires = false,
+				_ if in_hires => name = format!("HIRES/{}", name),
+				_ => (),
+			}
+
 			self.lump_names.insert(name.clone());
 			self.lumps.push(Lump {
 				path: path.into(),
@@ -79,6 +93,58 @@ impl WadLoader {
 		Ok(())
 	}
 
+	// Adds every file in a directory as a lump, named after the file without its extension.
+	// Files inside a "hires" subdirectory go in the hires namespace.
+	fn add_dir(&mut self, path: &Path) -> anyhow::Result<()> {
+		log::info!("Adding {}", path.display());
+		let mut dirs = vec![(path.to_path_buf(), false)];
+
+		while let Some((dir, in_hires)) = dirs.pop() {
+			let mut entries = fs::read_dir(&dir)?
+				.map(|entry| entry.map(|entry| entry.path()))
+				.collect::<Result<Vec<_>, _>>()?;
+			entries.sort();
+
+			for entry_path in entries {
+				if entry_path.is_dir() {
+					let is_hires = entry_path
+						.file_name()
+						.and_then(|name| name.to_str())
+						.map_or(false, |name| name.eq_ignore_ascii_case("hires"));
+					dirs.push((entry_path, in_hires || is_hires));
+					continue;
+				}
+
+				let stem = match entry_path.file_stem().and_then(|stem| stem.to_str()) {
+					Some(stem) => stem.to_ascii_uppercase(),
+					None => {
+						log::warn!("Skipping file with invalid name: {}", entry_path.display());
+						continue;
+					}
+				};
+
+				let name = if in_hires {
+					format!("HIRES/{}", stem)
+				} else {
+					stem
+				};
+
+				let size = fs::metadata(&entry_path)?.len() as usize;
+				self.lump_names.insert(name.clone());
+				self.lumps.push(Lump {
+					path: entry_path,
+					name,
+					offset: 0,
+					size,
+				});
+			}
+		}
+
+		self.wads.push(path.into());
+
+		Ok(())
+	}
+
 	pub fn wads(&self) -> impl Iterator<Item = &Path> {
 		self.wads.iter().map(PathBuf::as_path)
 	}
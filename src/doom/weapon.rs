@@ -0,0 +1,589 @@
+use crate::{
+	assets::{AssetHandle, AssetStorage},
+	audio::Sound,
+	doom::{
+		client::Client,
+		components::{Health, Velocity},
+		data::{States, WeaponTypes, FRAME_RATE},
+		input::UserCommand,
+		inventory::{Inventory, Powers},
+		monster::noise_alert,
+		state::{StateAction, StateActionEvent, StateMachine},
+	},
+};
+use derivative::Derivative;
+use legion::prelude::{Entity, Read, ResourceSet, Resources, World, Write};
+use nalgebra::Vector2;
+use rand::Rng;
+use rand_pcg::Pcg64Mcg;
+use serde::Deserialize;
+use shrev::EventChannel;
+use std::time::Duration;
+
+pub const WEAPON_TOP: f32 = 32.0;
+pub const WEAPON_BOTTOM: f32 = 128.0;
+
+// Distance moved by each call to A_Raise and A_Lower
+const RAISE_SPEED: f32 = 6.0;
+const LOWER_SPEED: f32 = 6.0;
+
+const MAX_BOB: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum WeaponType {
+	Fist,
+	Pistol,
+	Shotgun,
+	Chaingun,
+	Missile,
+	Plasma,
+	Bfg,
+	Chainsaw,
+	SuperShotgun,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum AmmoType {
+	Clip,
+	Shell,
+	Cell,
+	Missile,
+}
+
+#[derive(Clone, Debug)]
+pub struct WeaponInfo {
+	pub ammo_type: Option<AmmoType>,
+	pub ammo_per_shot: i32,
+	pub up_state: usize,
+	pub down_state: usize,
+	pub ready_state: usize,
+	pub attack_state: usize,
+	pub flash_state: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AmmoInfo {
+	pub max: i32,
+	// Amount given by a small pickup
+	pub clip: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PSpriteType {
+	Weapon,
+	Flash,
+}
+
+// A first-person weapon sprite, with its own state machine
+#[derive(Clone, Copy, Debug, Derivative)]
+#[derivative(Default)]
+pub struct PSprite {
+	pub state: Option<usize>,
+	// None means the current state lasts forever
+	pub time_left: Option<Duration>,
+	// In vanilla screen coordinates, 320x200
+	#[derivative(Default(value = "Vector2::zeros()"))]
+	pub position: Vector2<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponState {
+	pub current: WeaponType,
+	// Weapon to switch to once the current one is lowered
+	pub pending: Option<WeaponType>,
+	pub attack_down: bool,
+	pub refire: u32,
+	// Indexed by PSpriteType
+	pub psprites: [PSprite; 2],
+}
+
+impl WeaponState {
+	pub fn new(current: WeaponType) -> WeaponState {
+		WeaponState {
+			current,
+			pending: None,
+			attack_down: false,
+			refire: 0,
+			psprites: [PSprite::default(); 2],
+		}
+	}
+}
+
+pub fn weapon_system() -> Box<dyn FnMut(&mut World, &mut Resources)> {
+	let mut level_time = Duration::default();
+
+	Box::new(move |world, resources| {
+		let (
+			asset_storage,
+			client,
+			delta,
+			mut rng,
+			mut sound_queue,
+			mut state_action_event_channel,
+			states,
+			weapon_types,
+		) = <(
+			Read<AssetStorage>,
+			Read<Client>,
+			Read<Duration>,
+			Write<Pcg64Mcg>,
+			Write<Vec<(AssetHandle<Sound>, Entity)>>,
+			Write<EventChannel<StateActionEvent>>,
+			Read<States>,
+			Read<WeaponTypes>,
+		)>::fetch_mut(resources);
+
+		level_time += *delta;
+
+		let entity = match client.entity {
+			Some(x) => x,
+			None => return,
+		};
+
+		let mut weapon_state =
+			match unsafe { world.get_component_mut_unchecked::<WeaponState>(entity) } {
+				Some(x) => x,
+				None => return,
+			};
+
+		let mut context = WeaponContext {
+			world,
+			asset_storage: &asset_storage,
+			entity,
+			command: &client.command,
+			level_time,
+			rng: &mut *rng,
+			sound_queue: &mut *sound_queue,
+			state_action_event_channel: &mut *state_action_event_channel,
+			states: &states,
+			weapon_types: &weapon_types,
+		};
+
+		// Switch weapons when a new slot is pressed
+		if let Some(slot) = client.command.action_switch_weapon {
+			if client.previous_command.action_switch_weapon != Some(slot) {
+				context.switch_weapon(&mut weapon_state, slot);
+			}
+		}
+
+		// Bring up a weapon if there is none, like after spawning
+		if weapon_state.psprites[PSpriteType::Weapon as usize]
+			.state
+			.is_none()
+			&& context.is_alive()
+		{
+			context.bring_up_weapon(&mut weapon_state);
+		}
+
+		for index in 0..weapon_state.psprites.len() {
+			let psprite = &mut weapon_state.psprites[index];
+			let state = match psprite.state {
+				Some(x) => x,
+				None => continue,
+			};
+			let time_left = match psprite.time_left {
+				Some(x) => x.checked_sub(*delta).unwrap_or_default(),
+				None => continue,
+			};
+
+			if time_left > Duration::default() {
+				psprite.time_left = Some(time_left);
+				continue;
+			}
+
+			context.set_psprite(&mut weapon_state, index, states.states[state].next);
+		}
+
+		// The flash moves along with the weapon
+		weapon_state.psprites[PSpriteType::Flash as usize].position =
+			weapon_state.psprites[PSpriteType::Weapon as usize].position;
+	})
+}
+
+struct WeaponContext<'a> {
+	world: &'a World,
+	asset_storage: &'a AssetStorage,
+	entity: Entity,
+	command: &'a UserCommand,
+	level_time: Duration,
+	rng: &'a mut Pcg64Mcg,
+	sound_queue: &'a mut Vec<(AssetHandle<Sound>, Entity)>,
+	state_action_event_channel: &'a mut EventChannel<StateActionEvent>,
+	states: &'a States,
+	weapon_types: &'a WeaponTypes,
+}
+
+impl<'a> WeaponContext<'a> {
+	fn set_psprite(
+		&mut self,
+		weapon_state: &mut WeaponState,
+		index: usize,
+		mut state_index: usize,
+	) {
+		let states = self.states;
+
+		loop {
+			// The null state removes the psprite
+			if state_index == 0 {
+				weapon_state.psprites[index].state = None;
+				weapon_state.psprites[index].time_left = None;
+				break;
+			}
+
+			let state = &states.states[state_index];
+			weapon_state.psprites[index].state = Some(state_index);
+			weapon_state.psprites[index].time_left = state.time;
+
+			if let Some(action) = state.action {
+				self.action(weapon_state, index, action);
+
+				// The action switched to another state itself
+				if weapon_state.psprites[index].state != Some(state_index) {
+					break;
+				}
+			}
+
+			// States with zero duration are passed through immediately
+			if state.time != Some(Duration::default()) {
+				break;
+			}
+
+			state_index = state.next;
+		}
+	}
+
+	fn action(&mut self, weapon_state: &mut WeaponState, index: usize, action: StateAction) {
+		match action {
+			StateAction::WeaponReady => self.weapon_ready(weapon_state, index),
+			StateAction::Lower => self.lower(weapon_state, index),
+			StateAction::Raise => self.raise(weapon_state, index),
+			StateAction::ReFire => self.refire(weapon_state),
+			StateAction::CheckReload => {
+				self.check_ammo(weapon_state);
+			}
+			StateAction::GunFlash => {
+				self.set_player_state("PLAY_ATK2");
+				self.set_flash(weapon_state, 0);
+			}
+			StateAction::FirePistol | StateAction::FireShotgun | StateAction::FireShotgun2 => {
+				self.start_sound(match action {
+					StateAction::FirePistol => "DSPISTOL",
+					StateAction::FireShotgun => "DSSHOTGN",
+					_ => "DSDSHTGN",
+				});
+				self.set_player_state("PLAY_ATK2");
+				self.use_ammo(weapon_state);
+				self.set_flash(weapon_state, 0);
+				self.fire(action);
+			}
+			StateAction::FireCGun => {
+				self.start_sound("DSPISTOL");
+
+				if !self.has_ammo(weapon_state.current) {
+					return;
+				}
+
+				self.set_player_state("PLAY_ATK2");
+				self.use_ammo(weapon_state);
+
+				// Each of the two attack states has its own flash
+				let attack_state =
+					self.weapon_types.weapons[weapon_state.current as usize].attack_state;
+				let offset = weapon_state.psprites[index]
+					.state
+					.map_or(0, |state| state.saturating_sub(attack_state));
+				self.set_flash(weapon_state, offset);
+				self.fire(action);
+			}
+			StateAction::FireMissile | StateAction::FireBfg => {
+				self.use_ammo(weapon_state);
+				self.fire(action);
+			}
+			StateAction::FirePlasma => {
+				self.use_ammo(weapon_state);
+				let offset = self.rng.gen::<bool>() as usize;
+				self.set_flash(weapon_state, offset);
+				self.fire(action);
+			}
+			StateAction::Punch | StateAction::Saw => self.fire(action),
+			StateAction::BfgSound => self.start_sound("DSBFG"),
+			StateAction::OpenShotgun2 => self.start_sound("DSDBOPN"),
+			StateAction::LoadShotgun2 => self.start_sound("DSDBLOAD"),
+			StateAction::CloseShotgun2 => {
+				self.start_sound("DSDBCLS");
+				self.refire(weapon_state);
+			}
+			_ => {}
+		}
+	}
+
+	// The attack itself is carried out by whichever system handles the action
+	fn fire(&mut self, action: StateAction) {
+		self.state_action_event_channel
+			.single_write(StateActionEvent {
+				entity: self.entity,
+				action,
+			});
+	}
+
+	fn weapon_ready(&mut self, weapon_state: &mut WeaponState, index: usize) {
+		let weapon_types = self.weapon_types;
+		let weapon_info = &weapon_types.weapons[weapon_state.current as usize];
+
+		if weapon_state.current == WeaponType::Chainsaw
+			&& weapon_state.psprites[index].state == Some(weapon_info.ready_state)
+		{
+			self.start_sound("DSSAWIDL");
+		}
+
+		// Go back to the normal player state after attacking
+		if let Some(mut state_machine) = unsafe {
+			self.world
+				.get_component_mut_unchecked::<StateMachine>(self.entity)
+		} {
+			if state_machine.current == self.states.names["PLAY_ATK1"]
+				|| state_machine.current == self.states.names["PLAY_ATK2"]
+			{
+				state_machine.next = Some(state_machine.spawn);
+			}
+		}
+
+		// Put the weapon away when switching or dead
+		if weapon_state.pending.is_some() || !self.is_alive() {
+			self.set_psprite(weapon_state, index, weapon_info.down_state);
+			return;
+		}
+
+		// Rockets and the BFG don't fire automatically when holding the button
+		if self.command.action_attack {
+			if !weapon_state.attack_down
+				|| (weapon_state.current != WeaponType::Missile
+					&& weapon_state.current != WeaponType::Bfg)
+			{
+				weapon_state.attack_down = true;
+				self.fire_weapon(weapon_state);
+				return;
+			}
+		} else {
+			weapon_state.attack_down = false;
+		}
+
+		// Bob the weapon based on movement speed
+		let bob = self
+			.world
+			.get_component::<Velocity>(self.entity)
+			.map_or(0.0, |velocity| {
+				let speed =
+					Vector2::new(velocity.velocity[0], velocity.velocity[1]).norm() / FRAME_RATE;
+				(speed * speed / 4.0).min(MAX_BOB)
+			});
+		let angle = self.level_time.as_secs_f32() * FRAME_RATE * 128.0 / 8192.0
+			* std::f32::consts::PI
+			* 2.0;
+		weapon_state.psprites[index].position = Vector2::new(
+			1.0 + bob * angle.cos(),
+			WEAPON_TOP + bob * (angle % std::f32::consts::PI).sin(),
+		);
+	}
+
+	fn fire_weapon(&mut self, weapon_state: &mut WeaponState) {
+		if !self.check_ammo(weapon_state) {
+			return;
+		}
+
+		self.set_player_state("PLAY_ATK1");
+		noise_alert(self.world, self.asset_storage, self.entity);
+
+		let attack_state = self.weapon_types.weapons[weapon_state.current as usize].attack_state;
+		self.set_psprite(weapon_state, PSpriteType::Weapon as usize, attack_state);
+	}
+
+	fn refire(&mut self, weapon_state: &mut WeaponState) {
+		if self.command.action_attack && weapon_state.pending.is_none() && self.is_alive() {
+			weapon_state.refire += 1;
+			self.fire_weapon(weapon_state);
+		} else {
+			weapon_state.refire = 0;
+			self.check_ammo(weapon_state);
+		}
+	}
+
+	fn lower(&mut self, weapon_state: &mut WeaponState, index: usize) {
+		let psprite = &mut weapon_state.psprites[index];
+		psprite.position[1] += LOWER_SPEED;
+
+		if psprite.position[1] < WEAPON_BOTTOM {
+			return;
+		}
+
+		// Dead players keep their weapon down
+		if !self.is_alive() {
+			psprite.position[1] = WEAPON_BOTTOM;
+			return;
+		}
+
+		self.bring_up_weapon(weapon_state);
+	}
+
+	fn raise(&mut self, weapon_state: &mut WeaponState, index: usize) {
+		let psprite = &mut weapon_state.psprites[index];
+		psprite.position[1] -= RAISE_SPEED;
+
+		if psprite.position[1] > WEAPON_TOP {
+			return;
+		}
+
+		psprite.position[1] = WEAPON_TOP;
+		let ready_state = self.weapon_types.weapons[weapon_state.current as usize].ready_state;
+		self.set_psprite(weapon_state, index, ready_state);
+	}
+
+	fn bring_up_weapon(&mut self, weapon_state: &mut WeaponState) {
+		if let Some(pending) = weapon_state.pending.take() {
+			weapon_state.current = pending;
+		}
+
+		if weapon_state.current == WeaponType::Chainsaw {
+			self.start_sound("DSSAWUP");
+		}
+
+		let up_state = self.weapon_types.weapons[weapon_state.current as usize].up_state;
+		weapon_state.psprites[PSpriteType::Weapon as usize].position[1] = WEAPON_BOTTOM;
+		self.set_psprite(weapon_state, PSpriteType::Weapon as usize, up_state);
+	}
+
+	fn switch_weapon(&mut self, weapon_state: &mut WeaponState, slot: u8) {
+		let inventory = match self.world.get_component::<Inventory>(self.entity) {
+			Some(x) => *x,
+			None => return,
+		};
+
+		let mut weapon = match slot {
+			1 => WeaponType::Fist,
+			2 => WeaponType::Pistol,
+			3 => WeaponType::Shotgun,
+			4 => WeaponType::Chaingun,
+			5 => WeaponType::Missile,
+			6 => WeaponType::Plasma,
+			7 => WeaponType::Bfg,
+			_ => return,
+		};
+
+		let strength = self
+			.world
+			.get_component::<Powers>(self.entity)
+			.map_or(false, |powers| powers.strength.is_some());
+
+		// Some slots have two weapons, the fist is only worth going back to with berserk
+		if weapon == WeaponType::Fist
+			&& inventory.weapons[WeaponType::Chainsaw as usize]
+			&& !(weapon_state.current == WeaponType::Chainsaw && strength)
+		{
+			weapon = WeaponType::Chainsaw;
+		} else if weapon == WeaponType::Shotgun
+			&& inventory.weapons[WeaponType::SuperShotgun as usize]
+			&& weapon_state.current != WeaponType::SuperShotgun
+		{
+			weapon = WeaponType::SuperShotgun;
+		}
+
+		if inventory.weapons[weapon as usize] && weapon != weapon_state.current {
+			weapon_state.pending = Some(weapon);
+		}
+	}
+
+	// Switches to another weapon if the current one is out of ammo
+	fn check_ammo(&mut self, weapon_state: &mut WeaponState) -> bool {
+		if self.has_ammo(weapon_state.current) {
+			return true;
+		}
+
+		// Vanilla preference order
+		let best = [
+			WeaponType::Plasma,
+			WeaponType::SuperShotgun,
+			WeaponType::Chaingun,
+			WeaponType::Shotgun,
+			WeaponType::Pistol,
+			WeaponType::Chainsaw,
+			WeaponType::Missile,
+			WeaponType::Bfg,
+		]
+		.iter()
+		.copied()
+		.find(|&weapon| self.has_ammo(weapon))
+		.unwrap_or(WeaponType::Fist);
+
+		weapon_state.pending = Some(best);
+		let down_state = self.weapon_types.weapons[weapon_state.current as usize].down_state;
+		self.set_psprite(weapon_state, PSpriteType::Weapon as usize, down_state);
+
+		false
+	}
+
+	// Whether the weapon is owned and has enough ammo to fire
+	fn has_ammo(&self, weapon: WeaponType) -> bool {
+		let inventory = match self.world.get_component::<Inventory>(self.entity) {
+			Some(x) => *x,
+			None => return false,
+		};
+
+		if !inventory.weapons[weapon as usize] {
+			return false;
+		}
+
+		let weapon_info = &self.weapon_types.weapons[weapon as usize];
+
+		match weapon_info.ammo_type {
+			Some(ammo_type) => inventory.ammo[ammo_type as usize] >= weapon_info.ammo_per_shot,
+			None => true,
+		}
+	}
+
+	fn use_ammo(&mut self, weapon_state: &WeaponState) {
+		let weapon_types = self.weapon_types;
+		let weapon_info = &weapon_types.weapons[weapon_state.current as usize];
+
+		if let Some(ammo_type) = weapon_info.ammo_type {
+			if let Some(mut inventory) = unsafe {
+				self.world
+					.get_component_mut_unchecked::<Inventory>(self.entity)
+			} {
+				let ammo = &mut inventory.ammo[ammo_type as usize];
+				*ammo = (*ammo - weapon_info.ammo_per_shot).max(0);
+			}
+		}
+	}
+
+	fn set_flash(&mut self, weapon_state: &mut WeaponState, offset: usize) {
+		let flash_state = self.weapon_types.weapons[weapon_state.current as usize].flash_state;
+
+		if let Some(flash_state) = flash_state {
+			self.set_psprite(
+				weapon_state,
+				PSpriteType::Flash as usize,
+				flash_state + offset,
+			);
+		}
+	}
+
+	fn set_player_state(&mut self, name: &str) {
+		if let Some(mut state_machine) = unsafe {
+			self.world
+				.get_component_mut_unchecked::<StateMachine>(self.entity)
+		} {
+			state_machine.next = Some(self.states.names[name]);
+		}
+	}
+
+	fn start_sound(&mut self, name: &str) {
+		self.sound_queue
+			.push((self.weapon_types.sounds[name].clone(), self.entity));
+	}
+
+	fn is_alive(&self) -> bool {
+		self.world
+			.get_component::<Health>(self.entity)
+			.map_or(true, |health| health.current > 0)
+	}
+}
@@ -20,6 +20,7 @@ use crate::{
 };
 use anyhow::{bail, Context};
 use clap::{App, Arg, ArgMatches};
+use fnv::{FnvHashMap, FnvHashSet};
 use legion::{
 	prelude::{Entity, IntoQuery, Read, ResourceSet, Resources, World, Write},
 	systems::schedule::Builder,
@@ -30,11 +31,12 @@ use rand_pcg::Pcg64Mcg;
 use shrev::EventChannel;
 use std::{
 	path::PathBuf,
+	sync::Arc,
 	time::{Duration, Instant},
 };
 use vulkano::{
 	format::Format,
-	image::{Dimensions, ImmutableImage},
+	image::{Dimensions, ImageViewAccess, ImmutableImage},
 };
 use winit::{
 	event::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent},
@@ -48,7 +50,7 @@ fn main() -> anyhow::Result<()> {
 		.version(clap::crate_version!())
 		.arg(
 			Arg::with_name("PWADS")
-				.help("PWAD files to add")
+				.help("PWAD files or directories to add")
 				.multiple(true),
 		)
 		.arg(
@@ -58,6 +60,14 @@ fn main() -> anyhow::Result<()> {
 				.long("iwad")
 				.value_name("FILE"),
 		)
+		.arg(
+			Arg::with_name("deh")
+				.help("DeHackEd patch file to apply")
+				.long("deh")
+				.value_name("FILE")
+				.multiple(true)
+				.number_of_values(1),
+		)
 		.arg(
 			Arg::with_name("map")
 				.help("Map to load at startup")
@@ -81,7 +91,9 @@ fn main() -> anyhow::Result<()> {
 
 	let mut loader = doom::wad::WadLoader::new();
 	load_wads(&mut loader, &arg_matches)?;
+	let dehacked = load_dehacked(&loader, &arg_matches)?;
 	resources.insert(loader);
+	resources.insert(dehacked);
 
 	let (command_sender, command_receiver) = commands::init()?;
 	let mut event_loop = EventLoop::new();
@@ -101,8 +113,15 @@ fn main() -> anyhow::Result<()> {
 	resources.insert(InputState::new());
 	resources.insert(Vec::<(AssetHandle<Sound>, Entity)>::new());
 	resources.insert(doom::client::Client::default());
+	resources.insert(doom::game::GameSession::default());
 	resources.insert(doom::data::FRAME_TIME);
 	resources.insert(EventChannel::<doom::client::UseEvent>::new());
+	resources.insert(EventChannel::<doom::physics::CrossEvent>::new());
+	resources.insert(EventChannel::<doom::client::ShootEvent>::new());
+	resources.insert(EventChannel::<doom::damage::DamageEvent>::new());
+	resources.insert(EventChannel::<doom::physics::TouchEvent>::new());
+	resources.insert(EventChannel::<doom::state::StateActionEvent>::new());
+	resources.insert(EventChannel::<doom::client::MessageEvent>::new());
 
 	// Select map
 	let map =
@@ -131,11 +150,40 @@ fn main() -> anyhow::Result<()> {
 		.add_thread_local_fn(doom::client::player_command_system())
 		.add_thread_local_fn(doom::client::player_move_system())
 		.add_thread_local_fn(doom::client::player_use_system())
+		.add_thread_local_fn(doom::weapon::weapon_system())
+		.add_thread_local_fn(doom::hitscan::hitscan_system(&mut resources))
+		.add_thread_local_fn(doom::projectile::projectile_system(&mut resources))
+		.add_thread_local_fn(doom::monster::monster_system(&mut resources))
 		.add_thread_local_fn(doom::physics::physics_system())
+		.add_thread_local_fn(doom::inventory::item_pickup_system(&mut resources))
 		.add_thread_local_fn(doom::door::door_use_system(&mut resources))
+		.add_thread_local_fn(doom::door::door_cross_system(&mut resources))
+		.add_thread_local_fn(doom::door::door_shoot_system(&mut resources))
 		.add_thread_local_fn(doom::door::door_active_system())
+		.add_thread_local_fn(doom::plat::plat_use_system(&mut resources))
+		.add_thread_local_fn(doom::plat::plat_cross_system(&mut resources))
+		.add_thread_local_fn(doom::plat::plat_shoot_system(&mut resources))
+		.add_thread_local_fn(doom::plat::plat_active_system())
+		.add_thread_local_fn(doom::floor::floor_use_system(&mut resources))
+		.add_thread_local_fn(doom::floor::floor_cross_system(&mut resources))
+		.add_thread_local_fn(doom::floor::floor_shoot_system(&mut resources))
+		.add_thread_local_fn(doom::floor::floor_active_system())
+		.add_thread_local_fn(doom::ceiling::ceiling_use_system(&mut resources))
+		.add_thread_local_fn(doom::ceiling::ceiling_cross_system(&mut resources))
+		.add_thread_local_fn(doom::ceiling::ceiling_active_system())
+		.add_thread_local_fn(doom::teleport::teleport_cross_system(&mut resources))
+		.add_thread_local_fn(doom::game::exit_use_system(&mut resources))
+		.add_thread_local_fn(doom::game::exit_cross_system(&mut resources))
+		.add_thread_local_fn(doom::light::light_use_system(&mut resources))
+		.add_thread_local_fn(doom::light::light_cross_system(&mut resources))
+		.add_thread_local_fn(doom::sector::sector_special_system())
+		.add_thread_local_fn(doom::state::state_machine_system())
+		.add_thread_local_fn(doom::damage::damage_system(&mut resources))
+		.add_thread_local_fn(doom::client::player_message_system(&mut resources))
 		.add_thread_local_fn(doom::door::switch_active_system())
 		.add_thread_local_fn(doom::light::light_update_system())
+		.add_thread_local_fn(doom::palette::palette_flash_system())
+		.add_thread_local_fn(doom::inventory::powers_system())
 		.add_thread_local_fn(doom::update::texture_anim_system())
 		.build();
 
@@ -239,6 +287,17 @@ fn main() -> anyhow::Result<()> {
 			return Ok(());
 		}
 
+		// Go to the next map if the level was exited
+		let exit = resources
+			.get_mut::<doom::game::GameSession>()
+			.unwrap()
+			.exit
+			.take();
+
+		if let Some(exit_type) = exit {
+			exit_map(exit_type, &mut world, &mut resources)?;
+		}
+
 		// Run game frames
 		leftover_time += delta;
 
@@ -246,6 +305,10 @@ fn main() -> anyhow::Result<()> {
 			leftover_time -= doom::data::FRAME_TIME;
 
 			update_dispatcher.execute(&mut world, &mut resources);
+			resources
+				.get_mut::<doom::game::GameSession>()
+				.unwrap()
+				.level_time += doom::data::FRAME_TIME;
 
 			// Reset input delta state
 			{
@@ -310,12 +373,66 @@ fn load_wads(loader: &mut doom::wad::WadLoader, arg_matches: &ArgMatches) -> any
 	Ok(())
 }
 
+fn load_dehacked(
+	loader: &doom::wad::WadLoader,
+	arg_matches: &ArgMatches,
+) -> anyhow::Result<doom::dehacked::Dehacked> {
+	let mut dehacked = doom::dehacked::Dehacked::default();
+
+	// The lump in the WADs is applied first, then any files given on the command line
+	if loader.names().any(|name| name == "DEHACKED") {
+		let data = loader.load("DEHACKED")?;
+		dehacked
+			.parse(&String::from_utf8_lossy(&data))
+			.context("Couldn't load DEHACKED lump")?;
+	}
+
+	if let Some(iter) = arg_matches.values_of("deh") {
+		for path in iter.map(PathBuf::from) {
+			let data = std::fs::read(&path).context(format!("Couldn't load {}", path.display()))?;
+			dehacked
+				.parse(&String::from_utf8_lossy(&data))
+				.context(format!("Couldn't load {}", path.display()))?;
+		}
+	}
+
+	Ok(dehacked)
+}
+
 fn get_bindings() -> Bindings<doom::input::Action, doom::input::Axis> {
 	let mut bindings = Bindings::new();
 	bindings.bind_action(
 		doom::input::Action::Attack,
 		Button::Mouse(MouseButton::Left),
 	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(1),
+		Button::Key(VirtualKeyCode::Key1),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(2),
+		Button::Key(VirtualKeyCode::Key2),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(3),
+		Button::Key(VirtualKeyCode::Key3),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(4),
+		Button::Key(VirtualKeyCode::Key4),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(5),
+		Button::Key(VirtualKeyCode::Key5),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(6),
+		Button::Key(VirtualKeyCode::Key6),
+	);
+	bindings.bind_action(
+		doom::input::Action::SwitchWeapon(7),
+		Button::Key(VirtualKeyCode::Key7),
+	);
 	bindings.bind_action(doom::input::Action::Use, Button::Key(VirtualKeyCode::Space));
 	bindings.bind_action(doom::input::Action::Use, Button::Mouse(MouseButton::Middle));
 	bindings.bind_action(
@@ -363,17 +480,33 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 	log::info!("Starting map {}...", name);
 	let start_time = Instant::now();
 
+	// Clear out the previous map
+	world.delete_all();
+	<Write<Vec<(AssetHandle<Sound>, Entity)>>>::fetch_mut(resources).clear();
+	let par_time = doom::game::par_time(name, &<Read<doom::dehacked::Dehacked>>::fetch(resources));
+	*<Write<doom::game::GameSession>>::fetch_mut(resources) = doom::game::GameSession {
+		map: name.to_owned(),
+		par_time,
+		..doom::game::GameSession::default()
+	};
+
 	// Load palette
-	let palette_handle: AssetHandle<doom::image::Palette> = {
+	let palette_handle: AssetHandle<doom::image::Palettes> = {
 		let (mut asset_storage, mut loader) =
 			<(Write<AssetStorage>, Write<doom::wad::WadLoader>)>::fetch_mut(resources);
 		let handle = asset_storage.load("PLAYPAL", &mut *loader);
-		asset_storage.build_waiting::<doom::image::Palette, _>(|x, _| Ok(x));
+		asset_storage.build_waiting::<doom::image::Palettes, _>(|x, _| Ok(x));
 		handle
 	};
 
 	// Load entity type data
 	log::info!("Loading entity data...");
+	let states = doom::data::States::new(resources);
+	resources.insert(states);
+
+	let weapon_types = doom::data::WeaponTypes::new(resources);
+	resources.insert(weapon_types);
+
 	let mobj_types = doom::data::MobjTypes::new(resources);
 	let sector_types = doom::data::SectorTypes::new(resources);
 	let linedef_types = doom::data::LinedefTypes::new(resources);
@@ -382,53 +515,134 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 	resources.insert(sector_types);
 	resources.insert(linedef_types);
 
+	doom::dehacked::apply_dehacked(resources);
+
 	// Load sprite images
 	{
-		let (render_context, mut asset_storage, mut source) = <(
+		let (render_context, mobj_types, mut asset_storage, mut source) = <(
 			Read<crate::renderer::RenderContext>,
+			Read<doom::data::MobjTypes>,
 			Write<AssetStorage>,
 			Write<crate::doom::wad::WadLoader>,
 		)>::fetch_mut(resources);
 		asset_storage.build_waiting::<doom::sprite::Sprite, _>(|builder, asset_storage| {
 			Ok(builder.build(asset_storage, &mut *source)?)
 		});
-		asset_storage.build_waiting::<doom::sprite::SpriteImage, _>(|image, asset_storage| {
-			let palette = asset_storage.get(&palette_handle).unwrap();
-			let data: Vec<_> = image
-				.data
-				.into_iter()
-				.map(|pixel| {
-					if pixel.a == 0xFF {
-						palette[pixel.i as usize]
-					} else {
-						crate::doom::image::RGBAColor::default()
+
+		// Only templates drawn with a translation need translated images, plus the player,
+		// whose translation is chosen when spawning
+		let translations: Vec<_> = mobj_types.translations.values().cloned().collect();
+		let player = mobj_types.names.get("PLAYER");
+		let translatable_images: FnvHashSet<AssetHandle<doom::sprite::SpriteImage>> = mobj_types
+			.names
+			.values()
+			.filter_map(|handle| {
+				let sprite_render = asset_storage
+					.get(handle)
+					.unwrap()
+					.get_component::<doom::render::sprite::SpriteRender>()?;
+
+				if sprite_render.translation.is_some() || Some(handle) == player {
+					Some(sprite_render.sprite.clone())
+				} else {
+					None
+				}
+			})
+			.filter_map(|handle| asset_storage.get(&handle))
+			.flat_map(|sprite| sprite.frames().iter().flatten())
+			.map(|info| info.handle.clone())
+			.collect();
+
+		asset_storage.build_waiting::<doom::sprite::SpriteImage, _>(
+			|doom::image::ReplaceableImage { name, image, hires }, asset_storage| {
+				let translated = asset_storage
+					.handle_for::<doom::sprite::SpriteImage>(&name)
+					.map_or(false, |handle| translatable_images.contains(&handle));
+				let palette = &asset_storage.get(&palette_handle).unwrap()[0];
+				let create_image =
+					|data: Vec<doom::image::RGBAColor>, size: [usize; 2]| -> anyhow::Result<_> {
+						let (image, _future) = ImmutableImage::from_iter(
+							data.as_bytes().iter().copied(),
+							Dimensions::Dim2d {
+								width: size[0] as u32,
+								height: size[1] as u32,
+							},
+							Format::R8G8B8A8Unorm,
+							render_context.queues().graphics.clone(),
+						)?;
+						Ok(image as Arc<dyn ImageViewAccess + Send + Sync>)
+					};
+				let convert = |translation: Option<&doom::image::Translation>| -> Vec<_> {
+					image
+						.data
+						.iter()
+						.map(|pixel| {
+							if pixel.a == 0xFF {
+								let index = match translation {
+									Some(translation) => translation[pixel.i as usize],
+									None => pixel.i,
+								};
+								palette[index as usize]
+							} else {
+								crate::doom::image::RGBAColor::default()
+							}
+						})
+						.collect()
+				};
+
+				// The matrix uses the original size, so that replacements are scaled to match
+				let matrix = Matrix4::new_translation(&Vector3::new(
+					0.0,
+					image.offset[0] as f32,
+					image.offset[1] as f32,
+				)) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+					0.0,
+					image.size[0] as f32,
+					image.size[1] as f32,
+				));
+
+				// Hires replacements are true colour, so they can't be translated
+				if let Some(hires) = hires {
+					if translated {
+						log::warn!(
+							"Hires replacement for '{}' can't be translated, translations are ignored",
+							name
+						);
 					}
-				})
-				.collect();
-
-			// Create the image
-			let matrix = Matrix4::new_translation(&Vector3::new(
-				0.0,
-				image.offset[0] as f32,
-				image.offset[1] as f32,
-			)) * Matrix4::new_nonuniform_scaling(&Vector3::new(
-				0.0,
-				image.size[0] as f32,
-				image.size[1] as f32,
-			));
-
-			let (image, _future) = ImmutableImage::from_iter(
-				data.as_bytes().iter().copied(),
-				Dimensions::Dim2d {
-					width: image.size[0] as u32,
-					height: image.size[1] as u32,
-				},
-				Format::R8G8B8A8Unorm,
-				render_context.queues().graphics.clone(),
-			)?;
 
-			Ok(crate::doom::sprite::SpriteImage { matrix, image })
-		});
+					return Ok(crate::doom::sprite::SpriteImage {
+						matrix,
+						image: create_image(hires.data, hires.size)?,
+						translations: FnvHashMap::default(),
+					});
+				}
+
+				// Create translated images, but only if the translation affects this image
+				let mut translated_images = FnvHashMap::default();
+
+				for handle in translations.iter().filter(|_| translated) {
+					let translation = asset_storage.get(handle).unwrap();
+
+					if image
+						.data
+						.iter()
+						.any(|pixel| pixel.a == 0xFF && translation[pixel.i as usize] != pixel.i)
+					{
+						translated_images.insert(
+							handle.clone(),
+							create_image(convert(Some(translation)), image.size)?,
+						);
+					}
+				}
+
+				// Create the image
+				Ok(crate::doom::sprite::SpriteImage {
+					matrix,
+					image: create_image(convert(None), image.size)?,
+					translations: translated_images,
+				})
+			},
+		);
 	}
 
 	// Load sounds
@@ -456,59 +670,18 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 	{
 		let (render_context, mut asset_storage) =
 			<(Read<RenderContext>, Write<AssetStorage>)>::fetch_mut(resources);
-		asset_storage.build_waiting::<doom::map::textures::Wall, _>(|image, asset_storage| {
-			let palette = asset_storage.get(&palette_handle).unwrap();
-			let data: Vec<_> = image
-				.data
-				.into_iter()
-				.map(|pixel| {
-					if pixel.a == 0xFF {
-						palette[pixel.i as usize]
-					} else {
-						crate::doom::image::RGBAColor::default()
-					}
-				})
-				.collect();
-
-			// Create the image
-			let (image, _future) = ImmutableImage::from_iter(
-				data.as_bytes().iter().copied(),
-				Dimensions::Dim2d {
-					width: image.size[0] as u32,
-					height: image.size[1] as u32,
-				},
-				Format::R8G8B8A8Unorm,
-				render_context.queues().graphics.clone(),
-			)?;
-
-			Ok(image)
-		});
-		asset_storage.build_waiting::<doom::map::textures::Flat, _>(|image, asset_storage| {
-			let palette = asset_storage.get(&palette_handle).unwrap();
-			let data: Vec<_> = image
-				.data
-				.into_iter()
-				.map(|pixel| {
-					if pixel.a == 0xFF {
-						palette[pixel.i as usize]
-					} else {
-						crate::doom::image::RGBAColor::default()
-					}
-				})
-				.collect();
-
-			let (image, _future) = ImmutableImage::from_iter(
-				data.as_bytes().iter().copied(),
-				Dimensions::Dim2d {
-					width: image.size[0] as u32,
-					height: image.size[1] as u32,
-				},
-				Format::R8G8B8A8Unorm,
-				render_context.queues().graphics.clone(),
-			)?;
-
-			Ok(image)
-		});
+		asset_storage.build_waiting::<doom::map::textures::Wall, _>(
+			|intermediate, asset_storage| {
+				let palette = &asset_storage.get(&palette_handle).unwrap()[0];
+				build_texture_image(intermediate, palette, &render_context)
+			},
+		);
+		asset_storage.build_waiting::<doom::map::textures::Flat, _>(
+			|intermediate, asset_storage| {
+				let palette = &asset_storage.get(&palette_handle).unwrap()[0];
+				build_texture_image(intermediate, palette, &render_context)
+			},
+		);
 	}
 
 	log::info!("Spawning entities...");
@@ -522,7 +695,7 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 	doom::map::spawn_things(things, world, resources, &map_handle)?;
 
 	// Spawn player
-	let entity = doom::map::spawn_player(world, resources)?;
+	let entity = doom::map::spawn_player(world, resources, 1)?;
 	<Write<doom::client::Client>>::fetch_mut(resources).entity = Some(entity);
 
 	// Create quadtree and add entities to it
@@ -545,6 +718,21 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 
 	resources.insert(quadtree);
 
+	// Count the secrets in the map
+	let secrets_total = <Read<doom::sector::SectorSecret>>::query()
+		.iter(world)
+		.count();
+
+	// Count the items in the map
+	let items_total = <Read<doom::inventory::ItemPickup>>::query()
+		.iter(world)
+		.filter(|item_pickup| item_pickup.count_item)
+		.count();
+
+	let mut game_session = <Write<doom::game::GameSession>>::fetch_mut(resources);
+	game_session.secrets_total = secrets_total;
+	game_session.items_total = items_total;
+
 	log::debug!(
 		"Loading took {} s",
 		(Instant::now() - start_time).as_secs_f32()
@@ -552,3 +740,98 @@ fn load_map(name: &str, world: &mut World, resources: &mut Resources) -> anyhow:
 
 	Ok(())
 }
+
+fn build_texture_image(
+	doom::image::ReplaceableImage { image, hires, .. }: doom::image::ReplaceableImage,
+	palette: &doom::image::Palette,
+	render_context: &RenderContext,
+) -> anyhow::Result<doom::map::textures::TextureImage> {
+	let world_size = [image.size[0] as f32, image.size[1] as f32];
+
+	// Use the hires replacement if there is one, it's scaled to the original size
+	let (data, size) = if let Some(hires) = hires {
+		(hires.data, hires.size)
+	} else {
+		let data: Vec<_> = image
+			.data
+			.into_iter()
+			.map(|pixel| {
+				if pixel.a == 0xFF {
+					palette[pixel.i as usize]
+				} else {
+					crate::doom::image::RGBAColor::default()
+				}
+			})
+			.collect();
+		(data, image.size)
+	};
+
+	// Create the image
+	let (image, _future) = ImmutableImage::from_iter(
+		data.as_bytes().iter().copied(),
+		Dimensions::Dim2d {
+			width: size[0] as u32,
+			height: size[1] as u32,
+		},
+		Format::R8G8B8A8Unorm,
+		render_context.queues().graphics.clone(),
+	)?;
+
+	Ok(doom::map::textures::TextureImage { image, world_size })
+}
+
+fn exit_map(
+	exit_type: doom::game::ExitType,
+	world: &mut World,
+	resources: &mut Resources,
+) -> anyhow::Result<()> {
+	let next_map = {
+		let (game_session, loader) =
+			<(Read<doom::game::GameSession>, Read<doom::wad::WadLoader>)>::fetch(resources);
+
+		log::info!(
+			"Finished {} in {}{}, items {}/{}, secrets {}/{}",
+			game_session.map,
+			doom::game::format_time(game_session.level_time),
+			game_session
+				.par_time
+				.map_or_else(String::new, |par_time| format!(
+					" (par {})",
+					doom::game::format_time(par_time)
+				)),
+			game_session.items_found,
+			game_session.items_total,
+			game_session.secrets_found,
+			game_session.secrets_total,
+		);
+
+		doom::game::next_map(&game_session.map, exit_type, &*loader)
+	};
+
+	let next_map = match next_map {
+		Some(x) => x,
+		None => {
+			log::info!("The episode is over");
+			return Ok(());
+		}
+	};
+
+	// Keep the player's state when going to the next map
+	let player_carry = {
+		let client = <Read<doom::client::Client>>::fetch(resources);
+		client
+			.entity
+			.map(|entity| doom::game::PlayerCarry::from_entity(world, entity))
+	};
+
+	load_map(&next_map, world, resources)?;
+
+	if let Some(player_carry) = player_carry {
+		let entity = <Read<doom::client::Client>>::fetch(resources)
+			.entity
+			.unwrap();
+		player_carry.apply(world, entity);
+	}
+
+	Ok(())
+}